@@ -65,6 +65,10 @@ pub mod attributes;
 #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
 pub mod metrics;
 
+#[cfg(feature = "logs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "logs")))]
+pub mod logs;
+
 pub mod propagation;
 
 #[cfg(feature = "trace")]
@@ -0,0 +1,46 @@
+use crate::logs::LogRecord;
+use std::borrow::Cow;
+
+/// The interface for emitting [`LogRecord`]s.
+pub trait Logger {
+    /// Emit a [`LogRecord`]. If this `Logger` was created with
+    /// `include_trace_context` set to `true`, the record's trace context will
+    /// be populated from the current context when it has not been set
+    /// explicitly.
+    fn emit(&self, record: LogRecord);
+}
+
+/// Types that can create instances of [`Logger`].
+pub trait LoggerProvider {
+    /// The [`Logger`] type that this provider will return.
+    type Logger: Logger;
+
+    /// Returns a new logger with the given name.
+    ///
+    /// The `name` should be the application name or the name of the library
+    /// providing instrumentation. If the name is empty, then an
+    /// implementation-defined default name may be used instead.
+    ///
+    /// Loggers returned by this method include the trace context of the
+    /// currently active span in emitted records.
+    fn logger(&self, name: impl Into<Cow<'static, str>>) -> Self::Logger {
+        self.versioned_logger(name, None, None, true)
+    }
+
+    /// Returns a new versioned logger with the given name.
+    ///
+    /// The `name` should be the application name or the name of the library
+    /// providing instrumentation. If the name is empty, then an
+    /// implementation-defined default name may be used instead.
+    ///
+    /// If `include_trace_context` is `true`, emitted records that do not
+    /// already carry a trace context will be correlated with the span active
+    /// in the current context.
+    fn versioned_logger(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        version: Option<&'static str>,
+        schema_url: Option<&'static str>,
+        include_trace_context: bool,
+    ) -> Self::Logger;
+}
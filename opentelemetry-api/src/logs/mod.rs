@@ -0,0 +1,103 @@
+//! # OpenTelemetry Logs API
+//!
+//! The logs API is intended to be used by logging libraries (appenders) that
+//! bridge an existing logging framework into OpenTelemetry, rather than being
+//! called directly by application code.
+//!
+//! ## Getting Started
+//!
+//! ```
+//! use opentelemetry_api::logs::{noop::NoopLoggerProvider, LogRecord, Logger, LoggerProvider, Severity};
+//!
+//! let provider = NoopLoggerProvider::new();
+//! let logger = provider.logger("my-appender");
+//!
+//! logger.emit(
+//!     LogRecord::builder()
+//!         .with_severity_number(Severity::Info)
+//!         .with_severity_text("INFO")
+//!         .with_body("user logged in".into())
+//!         .build(),
+//! );
+//! ```
+//!
+//! ## Trace correlation
+//!
+//! Log records carry an optional [`TraceContext`] which identifies the span
+//! that was active when the record was produced. It can be set explicitly via
+//! [`LogRecordBuilder::with_context`], otherwise SDK loggers may populate it
+//! from the current [`Context`].
+//!
+//! [`Context`]: crate::Context
+
+use futures_channel::{mpsc::TrySendError, oneshot::Canceled};
+use std::time::Duration;
+use thiserror::Error;
+
+mod logger;
+pub mod noop;
+mod record;
+
+pub use self::{
+    logger::{Logger, LoggerProvider},
+    record::{AnyValue, LogRecord, LogRecordBuilder, Severity, TraceContext},
+};
+use crate::ExportError;
+
+/// Describe the result of operations in log SDK.
+pub type LogResult<T> = Result<T, LogError>;
+
+/// Errors returned by the log SDK.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum LogError {
+    /// Export failed with the error returned by the exporter.
+    #[error("Exporter {} encountered the following errors: {0}", .0.exporter_name())]
+    ExportFailed(Box<dyn ExportError>),
+
+    /// Export failed to finish after certain period and processor stopped the export.
+    #[error("Exporter timed out after {} seconds", .0.as_secs())]
+    ExportTimedOut(Duration),
+
+    /// Other errors propagated from log SDK that weren't covered above.
+    #[error(transparent)]
+    Other(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+impl<T> From<T> for LogError
+where
+    T: ExportError,
+{
+    fn from(err: T) -> Self {
+        LogError::ExportFailed(Box::new(err))
+    }
+}
+
+impl<T> From<TrySendError<T>> for LogError {
+    fn from(err: TrySendError<T>) -> Self {
+        LogError::Other(Box::new(err.into_send_error()))
+    }
+}
+
+impl From<Canceled> for LogError {
+    fn from(err: Canceled) -> Self {
+        LogError::Other(Box::new(err))
+    }
+}
+
+impl From<String> for LogError {
+    fn from(err_msg: String) -> Self {
+        LogError::Other(Box::new(Custom(err_msg)))
+    }
+}
+
+impl From<&'static str> for LogError {
+    fn from(err_msg: &'static str) -> Self {
+        LogError::Other(Box::new(Custom(err_msg.into())))
+    }
+}
+
+/// Wrap type for string
+#[derive(Error, Debug)]
+#[error("{0}")]
+struct Custom(String);
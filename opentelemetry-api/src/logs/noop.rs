@@ -0,0 +1,52 @@
+//! No-op logs impls
+//!
+//! This implementation is useful for testing purposes as it is intended to
+//! have minimal resource utilization and runtime impact.
+use crate::logs::{LogRecord, Logger, LoggerProvider};
+use std::borrow::Cow;
+
+/// A no-op implementation of a [`LoggerProvider`].
+#[derive(Clone, Debug, Default)]
+pub struct NoopLoggerProvider {
+    _private: (),
+}
+
+impl NoopLoggerProvider {
+    /// Create a new no-op logger provider.
+    pub fn new() -> Self {
+        NoopLoggerProvider { _private: () }
+    }
+}
+
+impl LoggerProvider for NoopLoggerProvider {
+    type Logger = NoopLogger;
+
+    /// Returns a new `NoopLogger` instance.
+    fn versioned_logger(
+        &self,
+        _name: impl Into<Cow<'static, str>>,
+        _version: Option<&'static str>,
+        _schema_url: Option<&'static str>,
+        _include_trace_context: bool,
+    ) -> Self::Logger {
+        NoopLogger::new()
+    }
+}
+
+/// A no-op implementation of a [`Logger`].
+#[derive(Clone, Debug, Default)]
+pub struct NoopLogger {
+    _private: (),
+}
+
+impl NoopLogger {
+    /// Create a new no-op logger.
+    pub fn new() -> Self {
+        NoopLogger { _private: () }
+    }
+}
+
+impl Logger for NoopLogger {
+    /// Ignores all log records.
+    fn emit(&self, _record: LogRecord) {}
+}
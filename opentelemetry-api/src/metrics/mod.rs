@@ -55,6 +55,9 @@ pub enum MetricsError {
     /// Errors when aggregator cannot subtract
     #[error("Aggregator does not subtract")]
     NoSubtraction,
+    /// Errors when a metric stream reaches its maximum number of attribute sets
+    #[error("Metric {0} reached its cardinality limit of {1} attribute sets, further measurements are aggregated in the otel.metric.overflow series")]
+    CardinalityLimitReached(String, usize),
     /// Fail to export metrics
     #[error("Metrics exporter {} failed with {0}", .0.exporter_name())]
     ExportErr(Box<dyn ExportError>),
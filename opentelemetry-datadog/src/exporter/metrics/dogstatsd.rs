@@ -0,0 +1,135 @@
+//! Formatting of [DogStatsD datagrams].
+//!
+//! [DogStatsD datagrams]: https://docs.datadoghq.com/developers/dogstatsd/datagram_shell/?tab=metrics
+use opentelemetry::sdk::metrics::sdk_api::{Number, NumberKind};
+use std::fmt::{self, Display, Formatter, Write};
+
+/// The DogStatsD metric types used by the exporter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MetricType {
+    /// Increments the value of the metric in the agent.
+    Count,
+    /// Replaces the value of the metric in the agent.
+    Gauge,
+}
+
+impl MetricType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Count => "c",
+            MetricType::Gauge => "g",
+        }
+    }
+}
+
+/// A metric datagram, formatted as `<name>:<value>|<type>|#<tags>`.
+#[derive(Debug)]
+pub(crate) struct Datagram<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) value: String,
+    pub(crate) metric_type: MetricType,
+    pub(crate) tags: &'a [String],
+}
+
+impl Display for Datagram<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // metric names only contain ASCII alphanumerics, underscores and periods
+        for c in self.name.chars() {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+                f.write_char(c)?;
+            } else {
+                f.write_char('_')?;
+            }
+        }
+        write!(f, ":{}|{}", self.value, self.metric_type.as_str())?;
+        if !self.tags.is_empty() {
+            write!(f, "|#{}", self.tags.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Format a `key:value` tag, replacing the characters reserved by the protocol.
+pub(crate) fn tag(key: &str, value: &str) -> String {
+    let mut tag = String::with_capacity(key.len() + value.len() + 1);
+    for c in key.chars() {
+        // the first colon separates the key from the value
+        tag.push(if c == ':' { '_' } else { sanitize_tag_char(c) });
+    }
+    tag.push(':');
+    tag.extend(value.chars().map(sanitize_tag_char));
+    tag
+}
+
+fn sanitize_tag_char(c: char) -> char {
+    match c {
+        '|' | ',' | '#' | '@' | '\n' | '\r' => '_',
+        c => c,
+    }
+}
+
+/// Format a number the way the agent parses it, returns `None` for values it can't represent.
+pub(crate) fn format_number(number: &Number, kind: &NumberKind) -> Option<String> {
+    match kind {
+        NumberKind::I64 => Some(number.to_i64(kind).to_string()),
+        NumberKind::U64 => Some(number.to_u64(kind).to_string()),
+        NumberKind::F64 => {
+            let value = number.to_f64(kind);
+            if value.is_finite() {
+                Some(value.to_string())
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_datagram() {
+        let tags = vec![tag("env", "prod"), tag("http.route", "/users|{id}")];
+        let datagram = Datagram {
+            name: "http.server.duration ms",
+            value: "12.5".to_string(),
+            metric_type: MetricType::Gauge,
+            tags: &tags,
+        };
+        assert_eq!(
+            datagram.to_string(),
+            "http.server.duration_ms:12.5|g|#env:prod,http.route:/users_{id}"
+        );
+
+        let datagram = Datagram {
+            name: "requests",
+            value: "3".to_string(),
+            metric_type: MetricType::Count,
+            tags: &[],
+        };
+        assert_eq!(datagram.to_string(), "requests:3|c");
+    }
+
+    #[test]
+    fn test_tag() {
+        assert_eq!(tag("url:port", "host:80"), "url_port:host:80");
+        assert_eq!(tag("a#b", "c,d\ne"), "a_b:c_d_e");
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(
+            format_number(&Number::from(-3i64), &NumberKind::I64),
+            Some("-3".to_string())
+        );
+        assert_eq!(
+            format_number(&Number::from(2.0f64), &NumberKind::F64),
+            Some("2".to_string())
+        );
+        assert_eq!(
+            format_number(&Number::from(f64::NAN), &NumberKind::F64),
+            None
+        );
+    }
+}
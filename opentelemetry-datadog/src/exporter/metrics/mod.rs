@@ -0,0 +1,446 @@
+//! # Datadog Metrics Exporter
+//!
+//! Sends metrics to the [DogStatsD] server of the Datadog agent over UDP or a Unix domain socket.
+//!
+//! | Aggregation | DogStatsD metric |
+//! |---|---|
+//! | Sum of a monotonic instrument | count of the delta since the last export |
+//! | Sum of a non-monotonic instrument | gauge of the current value |
+//! | Last value | gauge |
+//! | Histogram | `<name>.count` and `<name>.sum` counts, `<name>.min` and `<name>.max` gauges |
+//!
+//! DogStatsD has no way to send histogram buckets, and the measurements themselves are not kept
+//! by the aggregator, so histograms are sent as the series that can be computed exactly.
+//!
+//! [DogStatsD]: https://docs.datadoghq.com/developers/dogstatsd/
+mod dogstatsd;
+mod transport;
+
+use self::dogstatsd::{format_number, tag, Datagram, MetricType};
+use self::transport::{PacketWriter, Transport};
+use crate::exporter::model::unified_tags::UnifiedTags;
+use opentelemetry::metrics::{MetricsError, Result};
+use opentelemetry::sdk::export::metrics::aggregation::{
+    AggregationKind, Count, LastValue, MinMax, Sum, Temporality, TemporalitySelector,
+};
+use opentelemetry::sdk::export::metrics::{self, InstrumentationLibraryReader, Record};
+use opentelemetry::sdk::export::ExportError;
+use opentelemetry::sdk::metrics::aggregators::{
+    HistogramAggregator, LastValueAggregator, SumAggregator,
+};
+use opentelemetry::sdk::metrics::sdk_api::Descriptor;
+use opentelemetry::sdk::Resource;
+use opentelemetry::Context;
+use std::fmt::{self, Debug, Formatter};
+
+/// Default DogStatsD port of the agent.
+const DEFAULT_DOGSTATSD_PORT: u16 = 8125;
+
+/// Default host of the agent.
+const DEFAULT_AGENT_HOST: &str = "127.0.0.1";
+
+/// Errors of the DogStatsD metrics exporter.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum DogStatsdError {
+    /// The agent endpoint is invalid
+    #[error("invalid dogstatsd endpoint {0}")]
+    InvalidEndpoint(String),
+    /// A DogStatsD datagram is larger than the max packet size, it was dropped
+    #[error("dogstatsd datagram of {size} bytes over max packet size of {max_packet_size} bytes")]
+    DatagramTooLarge {
+        /// Size of the datagram
+        size: usize,
+        /// The max packet size
+        max_packet_size: usize,
+    },
+    /// Sending metrics to the DogStatsD server of the agent failed
+    #[error("failed to send metrics to the datadog agent, {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl ExportError for DogStatsdError {
+    fn exporter_name(&self) -> &'static str {
+        "datadog"
+    }
+}
+
+/// Create a new DogStatsD metrics exporter builder.
+pub fn new_metrics_exporter() -> MetricsExporterBuilder {
+    MetricsExporterBuilder::default()
+}
+
+/// Builder for [`MetricsExporter`].
+///
+/// The agent endpoint defaults to the `DD_DOGSTATSD_URL` environment variable, or to the
+/// `DD_AGENT_HOST` and `DD_DOGSTATSD_PORT` environment variables. The unified service tags default
+/// to the `DD_SERVICE`, `DD_ENV` and `DD_VERSION` environment variables.
+///
+/// ```no_run
+/// use opentelemetry::sdk::metrics::{controllers, processors, selectors};
+/// use opentelemetry::Context;
+///
+/// # fn main() -> opentelemetry::metrics::Result<()> {
+/// let exporter = opentelemetry_datadog::new_metrics_exporter()
+///     .with_agent_endpoint("unix:///var/run/datadog/dsd.socket")
+///     .with_service_name("my_app")
+///     .with_env("prod")
+///     .build()?;
+/// let controller = controllers::basic(processors::factory(
+///     selectors::simple::histogram([1.0, 5.0, 10.0]),
+///     exporter.temporality_selector(),
+/// ))
+/// .with_exporter(exporter)
+/// .build();
+/// controller.start(&Context::current(), opentelemetry::runtime::Tokio)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct MetricsExporterBuilder {
+    agent_endpoint: Option<String>,
+    max_packet_size: Option<usize>,
+    unified_tags: UnifiedTags,
+}
+
+impl Default for MetricsExporterBuilder {
+    fn default() -> Self {
+        MetricsExporterBuilder {
+            agent_endpoint: None,
+            max_packet_size: None,
+            unified_tags: UnifiedTags::new(),
+        }
+    }
+}
+
+impl Debug for MetricsExporterBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetricsExporterBuilder")
+            .field("agent_endpoint", &self.agent_endpoint)
+            .field("max_packet_size", &self.max_packet_size)
+            .finish()
+    }
+}
+
+impl MetricsExporterBuilder {
+    /// Assign the DogStatsD endpoint of the agent.
+    ///
+    /// Either `unix:///path/to/socket` for a Unix domain socket, or `udp://host:port` or
+    /// `host:port` for UDP. By default it is `127.0.0.1:8125`.
+    pub fn with_agent_endpoint<T: Into<String>>(mut self, endpoint: T) -> Self {
+        self.agent_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Set the max size in bytes of the packets sent to the agent.
+    ///
+    /// Datagrams are packed into packets up to this size, datagrams larger than it are dropped.
+    /// Defaults to 1432 over UDP and 8192 over Unix domain sockets, as recommended by Datadog.
+    pub fn with_max_packet_size(mut self, max_packet_size: usize) -> Self {
+        self.max_packet_size = Some(max_packet_size);
+        self
+    }
+
+    /// Assign the service name added as `service` tag to the metrics
+    pub fn with_service_name<T: Into<String>>(mut self, service_name: T) -> Self {
+        self.unified_tags.set_service(Some(service_name.into()));
+        self
+    }
+
+    /// Assign the version added as `version` tag to the metrics
+    pub fn with_version<T: Into<String>>(mut self, version: T) -> Self {
+        self.unified_tags.set_version(Some(version.into()));
+        self
+    }
+
+    /// Assign the env added as `env` tag to the metrics
+    pub fn with_env<T: Into<String>>(mut self, env: T) -> Self {
+        self.unified_tags.set_env(Some(env.into()));
+        self
+    }
+
+    /// Build the exporter.
+    ///
+    /// Returns an error if the endpoint is invalid or the socket can't be opened.
+    pub fn build(self) -> Result<MetricsExporter> {
+        let agent_endpoint = self.agent_endpoint.unwrap_or_else(default_agent_endpoint);
+        let transport = Transport::open(&agent_endpoint)?;
+        let max_packet_size = self
+            .max_packet_size
+            .unwrap_or_else(|| transport.default_max_packet_size());
+
+        Ok(MetricsExporter {
+            transport,
+            max_packet_size,
+            unified_tags: self.unified_tags,
+        })
+    }
+}
+
+fn default_agent_endpoint() -> String {
+    if let Ok(url) = std::env::var("DD_DOGSTATSD_URL") {
+        return url;
+    }
+    let host = std::env::var("DD_AGENT_HOST").unwrap_or_else(|_| DEFAULT_AGENT_HOST.to_string());
+    let port = std::env::var("DD_DOGSTATSD_PORT")
+        .ok()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_DOGSTATSD_PORT);
+    format!("udp://{}:{}", host, port)
+}
+
+/// Datadog metrics exporter, sends DogStatsD datagrams to the agent.
+pub struct MetricsExporter {
+    transport: Transport,
+    max_packet_size: usize,
+    unified_tags: UnifiedTags,
+}
+
+impl Debug for MetricsExporter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetricsExporter")
+            .field("transport", &self.transport)
+            .field("max_packet_size", &self.max_packet_size)
+            .finish()
+    }
+}
+
+impl MetricsExporter {
+    /// The temporality selector the processor of the exporter should use.
+    pub fn temporality_selector(&self) -> impl TemporalitySelector + Clone {
+        DogStatsdTemporalitySelector
+    }
+
+    /// Tags sent with every metric: the unified service tags and the resource attributes.
+    fn common_tags(&self, resource: &Resource) -> Vec<String> {
+        let unified_tags = [
+            &self.unified_tags.service,
+            &self.unified_tags.env,
+            &self.unified_tags.version,
+        ];
+        unified_tags
+            .iter()
+            .filter_map(|field| {
+                field
+                    .value
+                    .as_ref()
+                    .map(|value| tag(field.get_tag_name(), value))
+            })
+            .chain(
+                resource
+                    .iter()
+                    .map(|(key, value)| tag(key.as_str(), &value.as_str())),
+            )
+            .collect()
+    }
+}
+
+/// DogStatsD counts are deltas while non-monotonic sums are sent as gauges of their current
+/// value.
+#[derive(Clone, Debug)]
+struct DogStatsdTemporalitySelector;
+
+impl TemporalitySelector for DogStatsdTemporalitySelector {
+    fn temporality_for(&self, descriptor: &Descriptor, kind: &AggregationKind) -> Temporality {
+        if kind == &AggregationKind::SUM && !descriptor.instrument_kind().monotonic() {
+            Temporality::Cumulative
+        } else {
+            Temporality::Delta
+        }
+    }
+}
+
+impl TemporalitySelector for MetricsExporter {
+    fn temporality_for(&self, descriptor: &Descriptor, kind: &AggregationKind) -> Temporality {
+        DogStatsdTemporalitySelector.temporality_for(descriptor, kind)
+    }
+}
+
+impl metrics::MetricsExporter for MetricsExporter {
+    fn export(
+        &self,
+        _cx: &Context,
+        res: &Resource,
+        reader: &dyn InstrumentationLibraryReader,
+    ) -> Result<()> {
+        let common_tags = self.common_tags(res);
+        let mut writer = PacketWriter::new(&self.transport, self.max_packet_size);
+        reader.try_for_each(&mut |_library, reader| {
+            reader.try_for_each(self, &mut |record| {
+                let mut tags = common_tags.clone();
+                tags.extend(
+                    record
+                        .attributes()
+                        .iter()
+                        .map(|(key, value)| tag(key.as_str(), &value.as_str())),
+                );
+                for datagram in self.record_to_datagrams(record, &tags)? {
+                    writer.write(&datagram);
+                }
+                Ok(())
+            })
+        })?;
+
+        writer.finish().map_err(Into::into)
+    }
+}
+
+impl MetricsExporter {
+    fn record_to_datagrams(&self, record: &Record<'_>, tags: &[String]) -> Result<Vec<String>> {
+        let aggregator = record.aggregator().ok_or(MetricsError::NoDataCollected)?;
+        let descriptor = record.descriptor();
+        let kind = descriptor.number_kind();
+        let delta_type = |kind: &AggregationKind| match self.temporality_for(descriptor, kind) {
+            Temporality::Delta => MetricType::Count,
+            _ => MetricType::Gauge,
+        };
+        let datagram = |name: &str, value: String, metric_type: MetricType| {
+            Datagram {
+                name,
+                value,
+                metric_type,
+                tags,
+            }
+            .to_string()
+        };
+
+        let mut datagrams = Vec::new();
+        if let Some(sum) = aggregator.as_any().downcast_ref::<SumAggregator>() {
+            let metric_type = delta_type(aggregator.aggregation().kind());
+            if let Some(value) = format_number(&sum.sum()?, kind) {
+                datagrams.push(datagram(descriptor.name(), value, metric_type));
+            }
+        } else if let Some(last_value) = aggregator.as_any().downcast_ref::<LastValueAggregator>() {
+            let (value, _) = last_value.last_value()?;
+            if let Some(value) = format_number(&value, kind) {
+                datagrams.push(datagram(descriptor.name(), value, MetricType::Gauge));
+            }
+        } else if let Some(histogram) = aggregator.as_any().downcast_ref::<HistogramAggregator>() {
+            let count = histogram.count()?;
+            // histograms without recorded values are not exported
+            if count == 0 {
+                return Ok(datagrams);
+            }
+            let name = descriptor.name();
+            let metric_type = delta_type(aggregator.aggregation().kind());
+            datagrams.push(datagram(
+                &format!("{}.count", name),
+                count.to_string(),
+                metric_type,
+            ));
+            if let Some(value) = format_number(&histogram.sum()?, kind) {
+                datagrams.push(datagram(&format!("{}.sum", name), value, metric_type));
+            }
+            for (suffix, value) in [("min", histogram.min()), ("max", histogram.max())] {
+                let value = match value {
+                    Ok(value) => value,
+                    Err(MetricsError::NoDataCollected) => continue,
+                    Err(err) => return Err(err),
+                };
+                if let Some(value) = format_number(&value, kind) {
+                    datagrams.push(datagram(
+                        &format!("{}.{}", name, suffix),
+                        value,
+                        MetricType::Gauge,
+                    ));
+                }
+            }
+        }
+
+        Ok(datagrams)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry::sdk::export::metrics::MetricsExporter as _;
+    use opentelemetry::sdk::metrics::sdk_api::{InstrumentKind, NumberKind};
+    use opentelemetry::sdk::metrics::{controllers, processors, selectors};
+    use opentelemetry::KeyValue;
+    use std::net::UdpSocket;
+    use std::time::Duration;
+
+    #[test]
+    fn test_export() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        let exporter = new_metrics_exporter()
+            .with_agent_endpoint(format!("udp://{}", server.local_addr().unwrap()))
+            .with_service_name("my-service")
+            .with_env("test")
+            .with_version("1.0")
+            .build()
+            .unwrap();
+
+        let controller = controllers::basic(processors::factory(
+            selectors::simple::histogram([10.0, 20.0]),
+            exporter.temporality_selector(),
+        ))
+        .with_resource(Resource::new(vec![KeyValue::new("host.name", "host")]))
+        .with_collect_period(Duration::ZERO)
+        .build();
+        let meter = controller.versioned_meter("test", None, None);
+        let cx = Context::new();
+
+        let attributes = [KeyValue::new("route", "/users")];
+        let counter = meter.u64_counter("requests").init();
+        counter.add(&cx, 2, &attributes);
+        counter.add(&cx, 1, &attributes);
+        let up_down_counter = meter.i64_up_down_counter("connections").init();
+        up_down_counter.add(&cx, 5, &[]);
+        up_down_counter.add(&cx, -2, &[]);
+        let histogram = meter.f64_histogram("latency").init();
+        for value in [1.0, 3.0, 15.0, 30.0, 40.0] {
+            histogram.record(&cx, value, &[]);
+        }
+
+        controller.collect(&cx).unwrap();
+        exporter
+            .export(&cx, controller.resource(), &controller)
+            .unwrap();
+
+        let mut buf = [0; 2048];
+        let size = server.recv(&mut buf).unwrap();
+        let packet = String::from_utf8_lossy(&buf[..size]).into_owned();
+        let mut datagrams: Vec<&str> = packet.split('\n').collect();
+        datagrams.sort_unstable();
+
+        let tags = "service:my-service,env:test,version:1.0,host.name:host";
+        assert_eq!(
+            datagrams,
+            vec![
+                format!("connections:3|g|#{}", tags),
+                format!("latency.count:5|c|#{}", tags),
+                format!("latency.max:40|g|#{}", tags),
+                format!("latency.min:1|g|#{}", tags),
+                format!("latency.sum:89|c|#{}", tags),
+                format!("requests:3|c|#{},route:/users", tags),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_temporality() {
+        let exporter = new_metrics_exporter()
+            .with_agent_endpoint("127.0.0.1:8125")
+            .build()
+            .unwrap();
+        let descriptor =
+            |kind| Descriptor::new("metric".to_string(), kind, NumberKind::F64, None, None);
+
+        assert_eq!(
+            exporter.temporality_for(&descriptor(InstrumentKind::Counter), &AggregationKind::SUM),
+            Temporality::Delta
+        );
+        assert_eq!(
+            exporter.temporality_for(
+                &descriptor(InstrumentKind::UpDownCounter),
+                &AggregationKind::SUM
+            ),
+            Temporality::Cumulative
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Sockets to the DogStatsD server of the Datadog agent.
+use super::DogStatsdError as Error;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+#[cfg(unix)]
+use std::path::PathBuf;
+
+/// Recommended max packet size over UDP, it fits in the MTU of most networks.
+const UDP_MAX_PACKET_SIZE: usize = 1432;
+
+/// Recommended max packet size over Unix domain sockets.
+#[cfg(unix)]
+const UDS_MAX_PACKET_SIZE: usize = 8192;
+
+/// Datagram socket to the agent.
+///
+/// Sockets are non blocking, packets are dropped rather than blocking the export when the agent
+/// can't keep up.
+#[derive(Debug)]
+pub(crate) enum Transport {
+    Udp(UdpSocket, SocketAddr),
+    #[cfg(unix)]
+    Unix(UnixDatagram, PathBuf),
+}
+
+impl Transport {
+    /// Open a socket to `endpoint`, either `unix:///path/to/socket`, `udp://host:port` or
+    /// `host:port`.
+    pub(crate) fn open(endpoint: &str) -> Result<Self, Error> {
+        if let Some(path) = endpoint.strip_prefix("unix://") {
+            #[cfg(unix)]
+            {
+                let socket = UnixDatagram::unbound()?;
+                socket.set_nonblocking(true)?;
+                return Ok(Transport::Unix(socket, PathBuf::from(path)));
+            }
+            #[cfg(not(unix))]
+            return Err(Error::InvalidEndpoint(format!(
+                "unix sockets are not supported on this platform: {}",
+                path
+            )));
+        }
+
+        let address = endpoint
+            .strip_prefix("udp://")
+            .unwrap_or(endpoint)
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addresses| addresses.next())
+            .ok_or_else(|| Error::InvalidEndpoint(endpoint.to_string()))?;
+        let local_address: SocketAddr = if address.is_ipv4() {
+            (Ipv4Addr::UNSPECIFIED, 0).into()
+        } else {
+            (Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+        let socket = UdpSocket::bind(local_address)?;
+        socket.set_nonblocking(true)?;
+        Ok(Transport::Udp(socket, address))
+    }
+
+    /// The recommended max packet size of the transport.
+    pub(crate) fn default_max_packet_size(&self) -> usize {
+        match self {
+            Transport::Udp(..) => UDP_MAX_PACKET_SIZE,
+            #[cfg(unix)]
+            Transport::Unix(..) => UDS_MAX_PACKET_SIZE,
+        }
+    }
+
+    fn send(&self, packet: &[u8]) -> io::Result<usize> {
+        match self {
+            Transport::Udp(socket, address) => socket.send_to(packet, address),
+            #[cfg(unix)]
+            Transport::Unix(socket, path) => socket.send_to(packet, path),
+        }
+    }
+}
+
+/// Packs datagrams, separated by new lines, into packets of at most `max_packet_size` bytes.
+#[derive(Debug)]
+pub(crate) struct PacketWriter<'a> {
+    transport: &'a Transport,
+    max_packet_size: usize,
+    buffer: Vec<u8>,
+    /// The first error, the writer keeps sending the remaining packets after an error.
+    error: Option<Error>,
+}
+
+impl<'a> PacketWriter<'a> {
+    pub(crate) fn new(transport: &'a Transport, max_packet_size: usize) -> Self {
+        PacketWriter {
+            transport,
+            max_packet_size,
+            buffer: Vec::with_capacity(max_packet_size),
+            error: None,
+        }
+    }
+
+    /// Add a datagram to the current packet, sending the packet first if the datagram doesn't fit.
+    ///
+    /// Datagrams larger than the max packet size are dropped.
+    pub(crate) fn write(&mut self, datagram: &str) {
+        if datagram.len() > self.max_packet_size {
+            self.record_error(Error::DatagramTooLarge {
+                size: datagram.len(),
+                max_packet_size: self.max_packet_size,
+            });
+            return;
+        }
+        if !self.buffer.is_empty() && self.buffer.len() + 1 + datagram.len() > self.max_packet_size
+        {
+            self.flush();
+        }
+        if !self.buffer.is_empty() {
+            self.buffer.push(b'\n');
+        }
+        self.buffer.extend_from_slice(datagram.as_bytes());
+    }
+
+    fn flush(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        if let Err(err) = self.transport.send(&self.buffer) {
+            self.record_error(err.into());
+        }
+        self.buffer.clear();
+    }
+
+    fn record_error(&mut self, err: Error) {
+        if self.error.is_none() {
+            self.error = Some(err);
+        }
+    }
+
+    /// Send the last packet, returns the first error met by the writer.
+    pub(crate) fn finish(mut self) -> Result<(), Error> {
+        self.flush();
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn receive(socket: &UdpSocket) -> Vec<String> {
+        let mut packets = Vec::new();
+        let mut buf = [0; 2048];
+        while let Ok(size) = socket.recv(&mut buf) {
+            packets.push(String::from_utf8_lossy(&buf[..size]).into_owned());
+        }
+        packets
+    }
+
+    #[test]
+    fn test_packet_writer() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        let transport = Transport::open(&server.local_addr().unwrap().to_string()).unwrap();
+        assert_eq!(transport.default_max_packet_size(), UDP_MAX_PACKET_SIZE);
+
+        let mut writer = PacketWriter::new(&transport, 20);
+        writer.write("first:1|c");
+        writer.write("second:2|c");
+        writer.write(&format!("{}:1|c", "x".repeat(20)));
+        writer.write("third:3|c");
+        let err = writer.finish().unwrap_err();
+        assert!(matches!(
+            err,
+            Error::DatagramTooLarge {
+                size: 24,
+                max_packet_size: 20
+            }
+        ));
+
+        assert_eq!(receive(&server), vec!["first:1|c\nsecond:2|c", "third:3|c"]);
+    }
+
+    #[test]
+    fn test_invalid_endpoint() {
+        assert!(matches!(
+            Transport::open("udp://localhost"),
+            Err(Error::InvalidEndpoint(_))
+        ));
+    }
+}
@@ -1,10 +1,15 @@
 mod intern;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod model;
 
 pub use model::ApiVersion;
 pub use model::Error;
 pub use model::FieldMappingFn;
 
+#[cfg(feature = "metrics")]
+pub use metrics::{new_metrics_exporter, DogStatsdError, MetricsExporter, MetricsExporterBuilder};
+
 use std::borrow::Cow;
 use std::fmt::{Debug, Formatter};
 
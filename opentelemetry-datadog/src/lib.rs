@@ -58,6 +58,13 @@
 //! [`rt-tokio-current-thread`]: https://tokio.rs
 //! [`rt-async-std`]: https://async.rs
 //!
+//! ## Metrics
+//!
+//! With the `metrics` feature, [`MetricsExporter`] sends metrics to the DogStatsD server of the
+//! Datadog agent, over UDP or a Unix domain socket. The metrics are tagged with the `env`,
+//! `service` and `version` unified service tags, the resource and the metric attributes. See
+//! [`MetricsExporterBuilder`] for the configuration and how to install it.
+//!
 //! ## Bring your own http client
 //!
 //! Users can choose appropriate http clients to align with their runtime.
@@ -388,6 +395,8 @@ mod propagator {
     }
 }
 
+#[cfg(feature = "metrics")]
+pub use exporter::{new_metrics_exporter, DogStatsdError, MetricsExporter, MetricsExporterBuilder};
 pub use exporter::{
     new_pipeline, ApiVersion, DatadogExporter, DatadogPipelineBuilder, Error, FieldMappingFn,
     ModelConfig,
@@ -2,13 +2,15 @@
 use crate::transform::common::get_time;
 use opentelemetry::attributes::merge_iters;
 use opentelemetry::metrics::MetricsError;
-use opentelemetry::sdk::export::metrics::aggregation::{Count, Temporality, TemporalitySelector};
+use opentelemetry::sdk::export::metrics::aggregation::{
+    Count, MinMax, Temporality, TemporalitySelector,
+};
 use opentelemetry::sdk::metrics::aggregators::{
     HistogramAggregator, LastValueAggregator, SumAggregator,
 };
 use opentelemetry::sdk::{
     export::metrics::{
-        aggregation::{Histogram as SdkHistogram, LastValue, Sum as SdkSum},
+        aggregation::{LastValue, Sum as SdkSum},
         Record,
     },
     metrics::sdk_api::{Number, NumberKind},
@@ -532,35 +534,15 @@ pub(crate) fn record_to_metric_line(
 
         metric_line_data.push(metric_line);
     } else if let Some(histogram) = aggregator.as_any().downcast_ref::<HistogramAggregator>() {
-        let (sum, count, buckets) = (histogram.sum()?, histogram.count()?, histogram.histogram()?);
-        let (counts, boundaries) = (buckets.counts(), buckets.boundaries());
-
-        let mut min_idx: i32 = -1;
-        let mut max_idx: i32 = -1;
-
-        for (i, val) in counts.iter().enumerate() {
-            if val > &0.0 {
-                if min_idx == -1 {
-                    min_idx = i as i32;
-                }
-                max_idx = i as i32;
+        let (sum, count) = (histogram.sum()?, histogram.count()?);
+        // Histograms without recorded values are not exported
+        let (min, max) = match (histogram.min(), histogram.max()) {
+            (Ok(min), Ok(max)) => (min, max),
+            (Err(MetricsError::NoDataCollected), _) | (_, Err(MetricsError::NoDataCollected)) => {
+                return Ok(metric_line_data)
             }
-        }
-
-        let min: f64 = if min_idx == -1 {
-            0.0
-        } else if min_idx == 0 {
-            boundaries[0]
-        } else {
-            boundaries[min_idx as usize - 1]
+            (Err(err), _) | (_, Err(err)) => return Err(err),
         };
-
-        let max: f64 = if max_idx as usize == counts.len() - 1 {
-            boundaries[max_idx as usize - 1]
-        } else {
-            boundaries[max_idx as usize]
-        };
-
         let timestamp = if timestamp {
             Some(get_time(record.end_time().to_owned()))
         } else {
@@ -571,8 +553,8 @@ pub(crate) fn record_to_metric_line(
             kind: NumberKind::F64,
             key,
             dimensions: Some(dimensions),
-            min: Some(Number::from(min)),
-            max: Some(Number::from(max)),
+            min: Some(Number::from(min.to_f64(kind))),
+            max: Some(Number::from(max.to_f64(kind))),
             sum: Some(Number::from(sum.to_f64(&NumberKind::I64))),
             count: Some(count),
             delta: None,
@@ -951,8 +933,8 @@ mod tests {
                 key: MetricKey::new("test_histogram"),
                 kind: NumberKind::F64,
                 dimensions: Some(dimensions),
-                min: Some(Number::from(0.3_f64)),
-                max: Some(Number::from(0.3_f64)),
+                min: Some(Number::from(1_f64)),
+                max: Some(Number::from(3_f64)),
                 sum: Some(Number::from(6_f64)),
                 count: Some(3),
                 delta: None,
@@ -972,7 +954,7 @@ mod tests {
 
             assert_eq!(
                 Some(&format!(
-                    "test_histogram,key=VALUE,{}={},test.abc_123-=value.123_foo-bar gauge,min=0.3,max=0.3,sum=6,count=3 {}",
+                    "test_histogram,key=VALUE,{}={},test.abc_123-=value.123_foo-bar gauge,min=1,max=3,sum=6,count=3 {}",
                     METRICS_SOURCE,
                     "opentelemetry",
                     get_time(end_time),
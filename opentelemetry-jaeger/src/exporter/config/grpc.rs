@@ -0,0 +1,336 @@
+use crate::exporter::config::{
+    build_config_and_process, install_tracer_provider_and_get_tracer, HasRequiredConfig,
+    TransformationConfig,
+};
+use crate::exporter::grpc::GrpcCollector;
+use crate::exporter::uploader::{AsyncUploader, Uploader};
+use crate::{Exporter, JaegerTraceRuntime};
+use http::Uri;
+use opentelemetry::sdk::trace::BatchConfig;
+use opentelemetry::{sdk, sdk::trace::Config as TraceConfig, trace::TraceError};
+use std::borrow::BorrowMut;
+use std::convert::TryFrom;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::metadata::MetadataMap;
+#[cfg(feature = "grpc_tls_collector_client")]
+use tonic::transport::ClientTlsConfig;
+use tonic::transport::Endpoint;
+
+/// gRPC endpoint for Jaeger collector.
+/// e.g. "http://localhost:14250"
+const ENV_ENDPOINT: &str = "OTEL_EXPORTER_JAEGER_ENDPOINT";
+
+const DEFAULT_ENDPOINT: &str = "http://localhost:14250";
+
+/// Timeout for Jaeger collector.
+const ENV_TIMEOUT: &str = "OTEL_EXPORTER_JAEGER_TIMEOUT";
+
+/// Default of 10s
+const DEFAULT_COLLECTOR_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// GrpcCollectorPipeline config and build a exporter targeting a jaeger collector using gRPC.
+///
+/// Spans are sent to the `jaeger.api_v2.CollectorService/PostSpans` endpoint, which the
+/// collector serves on port 14250 by default.
+///
+/// The gRPC client runs on [tokio], so the exporter must be built within a tokio runtime.
+///
+/// ## Environment variables
+///
+/// - `OTEL_EXPORTER_JAEGER_ENDPOINT`: set the endpoint of the collector. Usually starts with `http://` or `https://`
+///
+/// - `OTEL_EXPORTER_JAEGER_TIMEOUT`: set the timeout of each export request in milliseconds.
+///
+/// ## TLS
+///
+/// With `grpc_tls_collector_client` feature enabled, use [`with_tls_config`] to connect to the
+/// collector using TLS. The certificates of the system are trusted by default.
+///
+/// [tokio]: https://tokio.rs
+/// [`with_tls_config`]: GrpcCollectorPipeline::with_tls_config
+#[derive(Debug)]
+pub struct GrpcCollectorPipeline {
+    transformation_config: TransformationConfig,
+    trace_config: Option<TraceConfig>,
+    batch_config: Option<BatchConfig>,
+
+    collector_timeout: Duration,
+    collector_endpoint: Option<Result<http::Uri, http::uri::InvalidUri>>,
+    metadata: MetadataMap,
+    #[cfg(feature = "grpc_tls_collector_client")]
+    tls_config: Option<ClientTlsConfig>,
+}
+
+impl Default for GrpcCollectorPipeline {
+    fn default() -> Self {
+        let mut pipeline = Self {
+            transformation_config: Default::default(),
+            trace_config: Default::default(),
+            batch_config: Some(Default::default()),
+            collector_timeout: DEFAULT_COLLECTOR_TIMEOUT,
+            collector_endpoint: None,
+            metadata: MetadataMap::new(),
+            #[cfg(feature = "grpc_tls_collector_client")]
+            tls_config: None,
+        };
+
+        if let Some(timeout) = env::var(ENV_TIMEOUT).ok().filter(|var| !var.is_empty()) {
+            let timeout = match timeout.parse() {
+                Ok(timeout) => Duration::from_millis(timeout),
+                Err(e) => {
+                    eprintln!("{} malformed defaulting to 10000: {}", ENV_TIMEOUT, e);
+                    DEFAULT_COLLECTOR_TIMEOUT
+                }
+            };
+            pipeline = pipeline.with_timeout(timeout);
+        }
+
+        if let Some(endpoint) = env::var(ENV_ENDPOINT).ok().filter(|var| !var.is_empty()) {
+            pipeline = pipeline.with_endpoint(endpoint);
+        }
+
+        pipeline
+    }
+}
+
+// implement the seal trait
+impl HasRequiredConfig for GrpcCollectorPipeline {
+    fn set_transformation_config<T>(&mut self, f: T)
+    where
+        T: FnOnce(&mut TransformationConfig),
+    {
+        f(self.transformation_config.borrow_mut())
+    }
+
+    fn set_trace_config(&mut self, config: TraceConfig) {
+        self.trace_config = Some(config)
+    }
+
+    fn set_batch_config(&mut self, config: BatchConfig) {
+        self.batch_config = Some(config)
+    }
+}
+
+/// Start a new pipeline to configure a exporter that target a jaeger collector using gRPC.
+///
+/// See details for each configurations at [`GrpcCollectorPipeline`].
+///
+/// [`GrpcCollectorPipeline`]: crate::config::grpc::GrpcCollectorPipeline
+pub fn new_grpc_collector_pipeline() -> GrpcCollectorPipeline {
+    GrpcCollectorPipeline::default()
+}
+
+impl GrpcCollectorPipeline {
+    /// Set the timeout of each export request.
+    ///
+    /// Default to be 10s.
+    pub fn with_timeout(self, collector_timeout: Duration) -> Self {
+        Self {
+            collector_timeout,
+            ..self
+        }
+    }
+
+    /// Set the collector endpoint.
+    ///
+    /// E.g. "http://localhost:14250"
+    pub fn with_endpoint<T>(self, collector_endpoint: T) -> Self
+    where
+        http::Uri: core::convert::TryFrom<T>,
+        <http::Uri as core::convert::TryFrom<T>>::Error: Into<http::uri::InvalidUri>,
+    {
+        Self {
+            collector_endpoint: Some(
+                core::convert::TryFrom::try_from(collector_endpoint).map_err(Into::into),
+            ),
+            ..self
+        }
+    }
+
+    /// Set the metadata sent with every export request, e.g. authentication headers.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use tonic::metadata::MetadataMap;
+    ///
+    /// let mut metadata = MetadataMap::new();
+    /// metadata.insert("x-tenant", "my-tenant".parse().unwrap());
+    ///
+    /// let pipeline = opentelemetry_jaeger::new_grpc_collector_pipeline()
+    ///                 .with_metadata(metadata);
+    /// ```
+    pub fn with_metadata(self, metadata: MetadataMap) -> Self {
+        Self { metadata, ..self }
+    }
+
+    /// Set the TLS settings used to connect to the collector.
+    #[cfg(feature = "grpc_tls_collector_client")]
+    pub fn with_tls_config(self, tls_config: ClientTlsConfig) -> Self {
+        Self {
+            tls_config: Some(tls_config),
+            ..self
+        }
+    }
+
+    /// Set the service name of the application. It generally is the name of application.
+    /// Critically, Jaeger backend depends on `Span.Process.ServiceName` to identify the service
+    /// that produced the spans.
+    ///
+    /// Opentelemetry allows set the service name using multiple methods.
+    /// This functions takes priority over all other methods.
+    ///
+    /// If the service name is not set. It will default to be `unknown_service`.
+    pub fn with_service_name<T: Into<String>>(mut self, service_name: T) -> Self {
+        self.set_transformation_config(|mut config| {
+            config.service_name = Some(service_name.into());
+        });
+        self
+    }
+
+    /// Config whether to export information of instrumentation library.
+    ///
+    /// It's required to [report instrumentation library as span tags].
+    /// However it does have a overhead on performance, performance sensitive applications can
+    /// use this function to opt out reporting instrumentation library.
+    ///
+    /// Default to be `true`.
+    ///
+    /// [report instrumentation library as span tags]: https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/trace/sdk_exporters/non-otlp.md#instrumentationscope
+    pub fn with_instrumentation_library_tags(mut self, should_export: bool) -> Self {
+        self.set_transformation_config(|mut config| {
+            config.export_instrument_library = should_export;
+        });
+        self
+    }
+
+    /// Assign the opentelemetry SDK configurations for the exporter pipeline.
+    ///
+    /// For mapping between opentelemetry configurations and Jaeger spans. Please refer [the spec].
+    ///
+    /// [the spec]: https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/trace/sdk_exporters/jaeger.md#mappings
+    pub fn with_trace_config(mut self, config: sdk::trace::Config) -> Self {
+        self.set_trace_config(config);
+        self
+    }
+
+    /// Assign the batch span processor for the exporter pipeline.
+    pub fn with_batch_processor_config(mut self, config: BatchConfig) -> Self {
+        self.set_batch_config(config);
+        self
+    }
+
+    /// Build a `TracerProvider` using a async exporter and configurations from the pipeline.
+    ///
+    /// The exporter will collect spans in a batch and send them to the collector.
+    ///
+    /// It's possible to lose spans up to a batch when the application shuts down. So users should
+    /// use [`shut_down_tracer_provider`] to block the shut down process until
+    /// all remaining spans have been sent.
+    ///
+    /// [`shut_down_tracer_provider`]: opentelemetry::global::shutdown_tracer_provider
+    pub fn build_batch<R: JaegerTraceRuntime>(
+        mut self,
+        runtime: R,
+    ) -> Result<sdk::trace::TracerProvider, TraceError> {
+        let mut builder = sdk::trace::TracerProvider::builder();
+        let export_instrument_library = self.transformation_config.export_instrument_library;
+        let (config, process) = build_config_and_process(
+            self.trace_config.take(),
+            self.transformation_config.service_name.take(),
+        );
+        let batch_config = self.batch_config.take();
+        let uploader = self.build_uploader::<R>()?;
+        let exporter = Exporter::new(process.into(), export_instrument_library, uploader);
+        let batch_processor = sdk::trace::BatchSpanProcessor::builder(exporter, runtime)
+            .with_batch_config(batch_config.unwrap_or_default())
+            .build();
+
+        builder = builder.with_span_processor(batch_processor);
+        builder = builder.with_config(config);
+
+        Ok(builder.build())
+    }
+
+    /// Similar to [`build_batch`][GrpcCollectorPipeline::build_batch] but also returns a tracer from the
+    /// tracer provider.
+    ///
+    /// The tracer name is `opentelemetry-jaeger`. The tracer version will be the version of this crate.
+    pub fn install_batch<R: JaegerTraceRuntime>(
+        self,
+        runtime: R,
+    ) -> Result<sdk::trace::Tracer, TraceError> {
+        let tracer_provider = self.build_batch(runtime)?;
+        install_tracer_provider_and_get_tracer(tracer_provider)
+    }
+
+    /// Build an jaeger exporter targeting a jaeger collector using gRPC.
+    pub fn build_collector_exporter<R>(mut self) -> Result<crate::Exporter, TraceError>
+    where
+        R: JaegerTraceRuntime,
+    {
+        let export_instrument_library = self.transformation_config.export_instrument_library;
+        let (_, process) = build_config_and_process(
+            self.trace_config.take(),
+            self.transformation_config.service_name.take(),
+        );
+        let uploader = self.build_uploader::<R>()?;
+        let exporter = Exporter::new(process.into(), export_instrument_library, uploader);
+        Ok(exporter)
+    }
+
+    fn build_uploader<R>(self) -> Result<Arc<dyn Uploader>, crate::Error>
+    where
+        R: JaegerTraceRuntime,
+    {
+        let endpoint = self
+            .collector_endpoint
+            .transpose()
+            .map_err::<crate::Error, _>(|err| crate::Error::ConfigError {
+                pipeline_name: "grpc collector",
+                config_name: "collector_endpoint",
+                reason: format!("invalid uri, {}", err),
+            })?
+            .unwrap_or_else(|| {
+                Uri::try_from(DEFAULT_ENDPOINT).unwrap() // default endpoint should always valid
+            });
+        let endpoint = Endpoint::from(endpoint).timeout(self.collector_timeout);
+
+        #[cfg(feature = "grpc_tls_collector_client")]
+        let endpoint = match self.tls_config {
+            Some(tls_config) => {
+                endpoint
+                    .tls_config(tls_config)
+                    .map_err::<crate::Error, _>(|err| crate::Error::ConfigError {
+                        pipeline_name: "grpc collector",
+                        config_name: "tls_config",
+                        reason: err.to_string(),
+                    })?
+            }
+            None => endpoint,
+        };
+
+        let collector = GrpcCollector::new(endpoint.connect_lazy(), self.metadata);
+        Ok(Arc::new(AsyncUploader::<R>::GrpcCollector(collector)))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "rt-tokio")]
+mod tests {
+    use super::*;
+    use opentelemetry::runtime::Tokio;
+
+    #[test]
+    fn test_set_grpc_collector_endpoint() {
+        let invalid_uri = new_grpc_collector_pipeline()
+            .with_endpoint("127.0.0.1:14250/api_v2")
+            .build_uploader::<Tokio>();
+        assert!(invalid_uri.is_err());
+        assert_eq!(
+            format!("{:?}", invalid_uri.err().unwrap()),
+            "ConfigError { pipeline_name: \"grpc collector\", config_name: \"collector_endpoint\", reason: \"invalid uri, invalid format\" }",
+        );
+    }
+}
@@ -3,7 +3,7 @@
 //! The jaeger exporter can send spans to [jaeger agent] or [jaeger collector]. The agent is usually
 //! deployed along with the application like a sidecar. The collector is usually deployed a stand alone
 //! application and receive spans from multiple sources. The exporter will use UDP to send spans to
-//! agents and use HTTP/TCP to send spans to collectors. See [jaeger deployment guide] for more details.
+//! agents and use HTTP/TCP or gRPC to send spans to collectors. See [jaeger deployment guide] for more details.
 //!
 //! [jaeger agent]: https://www.jaegertracing.io/docs/1.31/deployment/#agent
 //! [jaeger collector]: https://www.jaegertracing.io/docs/1.31/deployment/#collector
@@ -19,6 +19,9 @@ pub mod agent;
 /// Config a exporter that bypass the agent and send spans directly to [jaeger collector](https://www.jaegertracing.io/docs/1.31/deployment/#collector).
 #[cfg(any(feature = "collector_client", feature = "wasm_collector_client"))]
 pub mod collector;
+/// Config a exporter that sends spans to the gRPC endpoint of a [jaeger collector](https://www.jaegertracing.io/docs/1.31/deployment/#collector).
+#[cfg(feature = "grpc_collector_client")]
+pub mod grpc;
 
 // configurations and overrides on how to transform OTLP spans to Jaeger spans.
 #[derive(Debug)]
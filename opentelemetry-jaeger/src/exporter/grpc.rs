@@ -0,0 +1,323 @@
+//! # gRPC Jaeger Collector Client
+//!
+//! Sends spans to the `jaeger.api_v2.CollectorService/PostSpans` endpoint of a Jaeger collector.
+//! The exporter builds thrift batches, they are converted into the api_v2 protobuf model before
+//! being sent.
+//!
+//! The generated `jaeger_api_v2` module only covers the query service of
+//! [jaeger-idl](https://github.com/jaegertracing/jaeger-idl/blob/main/proto/api_v2/collector.proto),
+//! the messages of the collector service and its single unary call are declared here.
+use crate::exporter::jaeger_api_v2 as api_v2;
+use crate::exporter::thrift::jaeger;
+use http::uri::PathAndQuery;
+use opentelemetry::sdk::export::trace::ExportResult;
+use tonic::client::Grpc;
+use tonic::codec::ProstCodec;
+use tonic::metadata::{KeyAndValueRef, MetadataMap};
+use tonic::transport::Channel;
+
+const POST_SPANS_PATH: &str = "/jaeger.api_v2.CollectorService/PostSpans";
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct PostSpansRequest {
+    #[prost(message, optional, tag = "1")]
+    batch: Option<api_v2::Batch>,
+}
+
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+struct PostSpansResponse {}
+
+/// `GrpcCollector` submits batches to a Jaeger collector using gRPC.
+#[derive(Debug)]
+pub(crate) struct GrpcCollector {
+    client: Grpc<Channel>,
+    metadata: MetadataMap,
+}
+
+impl GrpcCollector {
+    /// Create a new gRPC collector client, `metadata` is sent with every request.
+    pub(crate) fn new(channel: Channel, metadata: MetadataMap) -> Self {
+        GrpcCollector {
+            client: Grpc::new(channel),
+            metadata,
+        }
+    }
+
+    /// Submit a Jaeger batch
+    pub(crate) async fn submit_batch(&self, batch: jaeger::Batch) -> ExportResult {
+        let mut request = tonic::Request::new(PostSpansRequest {
+            batch: Some(batch.into()),
+        });
+        for key_and_value in self.metadata.iter() {
+            match key_and_value {
+                KeyAndValueRef::Ascii(key, value) => {
+                    request.metadata_mut().append(key, value.to_owned());
+                }
+                KeyAndValueRef::Binary(key, value) => {
+                    request.metadata_mut().append_bin(key, value.to_owned());
+                }
+            }
+        }
+
+        // cloning the client is cheap, the clones share the same channel
+        let mut client = self.client.clone();
+        client.ready().await.map_err(|err| {
+            crate::Error::from(tonic::Status::unavailable(format!(
+                "the collector channel is not ready, {}",
+                err
+            )))
+        })?;
+        client
+            .unary::<_, PostSpansResponse, _>(
+                request,
+                PathAndQuery::from_static(POST_SPANS_PATH),
+                ProstCodec::default(),
+            )
+            .await
+            .map_err::<crate::Error, _>(Into::into)?;
+        Ok(())
+    }
+}
+
+impl From<jaeger::Batch> for api_v2::Batch {
+    fn from(batch: jaeger::Batch) -> Self {
+        api_v2::Batch {
+            spans: batch.spans.into_iter().map(Into::into).collect(),
+            process: Some(batch.process.into()),
+        }
+    }
+}
+
+impl From<jaeger::Process> for api_v2::Process {
+    fn from(process: jaeger::Process) -> Self {
+        api_v2::Process {
+            service_name: process.service_name,
+            tags: process.tags.into_iter().flatten().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<jaeger::Span> for api_v2::Span {
+    fn from(span: jaeger::Span) -> Self {
+        let trace_id = trace_id_to_bytes(span.trace_id_low, span.trace_id_high);
+
+        // api_v2 spans have no parent span id, the parent is a `CHILD_OF` reference.
+        let mut references = Vec::new();
+        if span.parent_span_id != 0 {
+            references.push(api_v2::SpanRef {
+                trace_id: trace_id.clone(),
+                span_id: span.parent_span_id.to_be_bytes().to_vec(),
+                ref_type: api_v2::SpanRefType::ChildOf as i32,
+            });
+        }
+        references.extend(span.references.into_iter().flatten().map(Into::into));
+
+        api_v2::Span {
+            trace_id,
+            span_id: span.span_id.to_be_bytes().to_vec(),
+            operation_name: span.operation_name,
+            references,
+            flags: span.flags as u32,
+            start_time: Some(timestamp_from_micros(span.start_time)),
+            duration: Some(duration_from_micros(span.duration)),
+            tags: span.tags.into_iter().flatten().map(Into::into).collect(),
+            logs: span.logs.into_iter().flatten().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<jaeger::SpanRef> for api_v2::SpanRef {
+    fn from(span_ref: jaeger::SpanRef) -> Self {
+        let ref_type = match span_ref.ref_type {
+            jaeger::SpanRefType::ChildOf => api_v2::SpanRefType::ChildOf,
+            jaeger::SpanRefType::FollowsFrom => api_v2::SpanRefType::FollowsFrom,
+        };
+        api_v2::SpanRef {
+            trace_id: trace_id_to_bytes(span_ref.trace_id_low, span_ref.trace_id_high),
+            span_id: span_ref.span_id.to_be_bytes().to_vec(),
+            ref_type: ref_type as i32,
+        }
+    }
+}
+
+impl From<jaeger::Log> for api_v2::Log {
+    fn from(log: jaeger::Log) -> Self {
+        api_v2::Log {
+            timestamp: Some(timestamp_from_micros(log.timestamp)),
+            fields: log.fields.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<jaeger::Tag> for api_v2::KeyValue {
+    fn from(tag: jaeger::Tag) -> Self {
+        let mut key_value = api_v2::KeyValue {
+            key: tag.key,
+            ..Default::default()
+        };
+        let v_type = match tag.v_type {
+            jaeger::TagType::String => {
+                key_value.v_str = tag.v_str.unwrap_or_default();
+                api_v2::ValueType::String
+            }
+            jaeger::TagType::Double => {
+                key_value.v_float64 = tag.v_double.map(|v| v.into_inner()).unwrap_or_default();
+                api_v2::ValueType::Float64
+            }
+            jaeger::TagType::Bool => {
+                key_value.v_bool = tag.v_bool.unwrap_or_default();
+                api_v2::ValueType::Bool
+            }
+            jaeger::TagType::Long => {
+                key_value.v_int64 = tag.v_long.unwrap_or_default();
+                api_v2::ValueType::Int64
+            }
+            jaeger::TagType::Binary => {
+                key_value.v_binary = tag.v_binary.unwrap_or_default();
+                api_v2::ValueType::Binary
+            }
+        };
+        key_value.v_type = v_type as i32;
+        key_value
+    }
+}
+
+/// api_v2 trace ids are the 16 big-endian bytes of the 128 bits id.
+fn trace_id_to_bytes(trace_id_low: i64, trace_id_high: i64) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(16);
+    bytes.extend_from_slice(&trace_id_high.to_be_bytes());
+    bytes.extend_from_slice(&trace_id_low.to_be_bytes());
+    bytes
+}
+
+fn timestamp_from_micros(micros: i64) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: micros.div_euclid(1_000_000),
+        nanos: (micros.rem_euclid(1_000_000) * 1_000) as i32,
+    }
+}
+
+fn duration_from_micros(micros: i64) -> prost_types::Duration {
+    prost_types::Duration {
+        seconds: micros / 1_000_000,
+        nanos: ((micros % 1_000_000) * 1_000) as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::KeyValue;
+
+    #[test]
+    fn test_convert_span() {
+        let span = jaeger::Span {
+            trace_id_low: 2,
+            trace_id_high: 1,
+            span_id: 3,
+            parent_span_id: 4,
+            operation_name: "operation".to_string(),
+            references: Some(vec![jaeger::SpanRef::new(
+                jaeger::SpanRefType::FollowsFrom,
+                6,
+                5,
+                7,
+            )]),
+            flags: 1,
+            start_time: 1_500_000,
+            duration: 2_000_001,
+            tags: Some(vec![KeyValue::new("key", "value").into()]),
+            logs: Some(vec![jaeger::Log::new(
+                1_000_000,
+                vec![KeyValue::new("event", 1.5).into()],
+            )]),
+        };
+
+        let span = api_v2::Span::from(span);
+        let trace_id = [1u64.to_be_bytes(), 2u64.to_be_bytes()].concat();
+        assert_eq!(span.trace_id, trace_id);
+        assert_eq!(span.span_id, 3i64.to_be_bytes());
+        assert_eq!(span.operation_name, "operation");
+        assert_eq!(span.flags, 1);
+        assert_eq!(
+            span.start_time,
+            Some(prost_types::Timestamp {
+                seconds: 1,
+                nanos: 500_000_000
+            })
+        );
+        assert_eq!(
+            span.duration,
+            Some(prost_types::Duration {
+                seconds: 2,
+                nanos: 1_000
+            })
+        );
+
+        // the parent span comes first as a CHILD_OF reference
+        assert_eq!(span.references.len(), 2);
+        assert_eq!(span.references[0].trace_id, trace_id);
+        assert_eq!(span.references[0].span_id, 4i64.to_be_bytes());
+        assert_eq!(
+            span.references[0].ref_type,
+            api_v2::SpanRefType::ChildOf as i32
+        );
+        assert_eq!(
+            span.references[1].trace_id,
+            [5u64.to_be_bytes(), 6u64.to_be_bytes()].concat()
+        );
+        assert_eq!(
+            span.references[1].ref_type,
+            api_v2::SpanRefType::FollowsFrom as i32
+        );
+
+        assert_eq!(span.tags.len(), 1);
+        assert_eq!(span.tags[0].key, "key");
+        assert_eq!(span.tags[0].v_type, api_v2::ValueType::String as i32);
+        assert_eq!(span.tags[0].v_str, "value");
+
+        assert_eq!(span.logs.len(), 1);
+        assert_eq!(
+            span.logs[0].timestamp,
+            Some(prost_types::Timestamp {
+                seconds: 1,
+                nanos: 0
+            })
+        );
+        assert_eq!(
+            span.logs[0].fields[0].v_type,
+            api_v2::ValueType::Float64 as i32
+        );
+        assert_eq!(span.logs[0].fields[0].v_float64, 1.5);
+    }
+
+    #[test]
+    fn test_post_spans_request_encoding() {
+        // `batch` is the field 1 of `PostSpansRequest` in collector.proto
+        let request = PostSpansRequest {
+            batch: Some(api_v2::Batch::default()),
+        };
+        assert_eq!(prost::Message::encode_to_vec(&request), vec![0x0a, 0x00]);
+    }
+
+    #[test]
+    fn test_convert_root_span_has_no_parent_reference() {
+        let span = jaeger::Span {
+            trace_id_low: 1,
+            trace_id_high: 0,
+            span_id: 2,
+            parent_span_id: 0,
+            operation_name: "root".to_string(),
+            references: None,
+            flags: 0,
+            start_time: 0,
+            duration: 0,
+            tags: None,
+            logs: None,
+        };
+        let span = api_v2::Span::from(span);
+        assert!(span.references.is_empty());
+        assert!(span.tags.is_empty());
+    }
+}
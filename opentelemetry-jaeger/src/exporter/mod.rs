@@ -3,6 +3,16 @@
 mod agent;
 #[cfg(any(feature = "collector_client", feature = "wasm_collector_client"))]
 mod collector;
+#[cfg(feature = "grpc_collector_client")]
+mod grpc;
+#[cfg(all(feature = "grpc_collector_client", not(feature = "integration_test")))]
+#[allow(unreachable_pub, dead_code, clippy::derive_partial_eq_without_eq)] // generated file
+pub(crate) mod jaeger_api_v2;
+// The integration tests query the traces through `crate::testing`.
+#[cfg(feature = "integration_test")]
+#[allow(unused, missing_docs, clippy::derive_partial_eq_without_eq)]
+// tonic don't derive Eq. We shouldn't manually change it.
+pub mod jaeger_api_v2;
 pub(crate) mod runtime;
 #[allow(clippy::all, unreachable_pub, dead_code)]
 #[rustfmt::skip] // don't format generated files
@@ -272,9 +282,15 @@ pub enum Error {
     /// If the spans was sent to jaeger collector. Refer [CollectorPipeline](config::collector::CollectorPipeline) for more details.
     ThriftAgentError(::thrift::Error),
 
+    /// Error from the gRPC collector.
+    ///
+    /// If the spans was sent to jaeger collector using gRPC. Refer [GrpcCollectorPipeline](config::grpc::GrpcCollectorPipeline) for more details.
+    #[cfg(feature = "grpc_collector_client")]
+    GrpcCollectorError(tonic::Status),
+
     /// Pipeline fails because one of the configurations is invalid.
     ConfigError {
-        /// the name of the pipeline. It can be `agent`, `collector` or `wasm collector`
+        /// the name of the pipeline. It can be `agent`, `collector`, `wasm collector` or `grpc collector`
         pipeline_name: &'static str,
         /// config name that has the error.
         config_name: &'static str,
@@ -291,6 +307,13 @@ impl From<::thrift::Error> for Error {
     }
 }
 
+#[cfg(feature = "grpc_collector_client")]
+impl From<tonic::Status> for Error {
+    fn from(value: tonic::Status) -> Self {
+        Error::GrpcCollectorError(value)
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -320,6 +343,10 @@ impl Display for Error {
                     write!(f, "thrift agent failed, {}", error)
                 }
             },
+            #[cfg(feature = "grpc_collector_client")]
+            Error::GrpcCollectorError(status) => {
+                write!(f, "grpc collector failed, {}", status)
+            }
             Error::ConfigError {
                 pipeline_name,
                 config_name,
@@ -1,6 +1,8 @@
 //! # Jaeger Span Uploader
 #[cfg(any(feature = "collector_client", feature = "wasm_collector_client"))]
 use crate::exporter::collector;
+#[cfg(feature = "grpc_collector_client")]
+use crate::exporter::grpc;
 use crate::exporter::{agent, jaeger};
 use async_trait::async_trait;
 use opentelemetry::sdk::export::trace;
@@ -47,6 +49,9 @@ pub(crate) enum AsyncUploader<R: JaegerTraceRuntime> {
     Collector(collector::AsyncHttpClient),
     #[cfg(feature = "wasm_collector_client")]
     WasmCollector(collector::WasmCollector),
+    /// Collector gRPC client
+    #[cfg(feature = "grpc_collector_client")]
+    GrpcCollector(grpc::GrpcCollector),
 }
 
 #[async_trait]
@@ -74,6 +79,11 @@ impl<R: JaegerTraceRuntime> Uploader for AsyncUploader<R> {
                     .await
                     .map_err::<crate::Error, _>(Into::into)?;
             }
+            #[cfg(feature = "grpc_collector_client")]
+            Self::GrpcCollector(collector) => {
+                // TODO Implement retry behaviour
+                collector.submit_batch(batch).await?;
+            }
         }
         Ok(())
     }
@@ -259,6 +259,10 @@
 //!
 //! * `wasm_collector_client`: Enable collector in wasm.
 //!
+//! * `grpc_collector_client`: Export span data to a Jaeger collector using gRPC. Requires a tokio runtime.
+//!
+//! * `grpc_tls_collector_client`: Enable TLS for the gRPC collector client.
+//!
 //! Support for recording and exporting telemetry asynchronously can be added
 //! via the following flags, it extends the [`opentelemetry`] feature:
 //!
@@ -313,6 +317,8 @@ pub use exporter::config;
 pub use exporter::config::collector::new_collector_pipeline;
 #[cfg(feature = "wasm_collector_client")]
 pub use exporter::config::collector::new_wasm_collector_pipeline;
+#[cfg(feature = "grpc_collector_client")]
+pub use exporter::config::grpc::new_grpc_collector_pipeline;
 pub use exporter::{
     config::agent::new_agent_pipeline, runtime::JaegerTraceRuntime, Error, Exporter, Process,
 };
@@ -1,6 +1,4 @@
-#[allow(unused, missing_docs, clippy::derive_partial_eq_without_eq)]
-// tonic don't derive Eq. We shouldn't manually change it.)]
-pub mod jaeger_api_v2;
+pub use crate::exporter::jaeger_api_v2;
 
 #[allow(missing_docs)]
 pub mod jaeger_client {
@@ -1,9 +1,10 @@
-use crate::ExportConfig;
+use crate::{Compression, ExportConfig, RetryConfig};
 #[cfg(feature = "serialize")]
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::default_headers;
+use super::retry::{grpc_retry_delay, RetryAction};
 
 /// Configuration of grpcio
 #[derive(Debug)]
@@ -22,6 +23,9 @@ pub struct GrpcioConfig {
 
     /// The number of GRPC worker threads to poll queues.
     pub completion_queue_count: usize,
+
+    /// The retry policy of failed exports.
+    pub retry_config: RetryConfig,
 }
 
 impl Default for GrpcioConfig {
@@ -32,6 +36,7 @@ impl Default for GrpcioConfig {
             compression: None,
             use_tls: None,
             completion_queue_count: 2,
+            retry_config: RetryConfig::default(),
         }
     }
 }
@@ -46,14 +51,6 @@ pub struct Credentials {
     pub key: String,
 }
 
-/// The compression algorithm to use when sending data.
-#[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
-#[derive(Clone, Copy, Debug)]
-pub enum Compression {
-    /// Compresses data using gzip.
-    Gzip,
-}
-
 impl From<Compression> for grpcio::CompressionAlgorithms {
     fn from(compression: Compression) -> Self {
         match compression {
@@ -70,6 +67,7 @@ impl From<Compression> for grpcio::CompressionAlgorithms {
 /// - config compression
 /// - select whether to use TLS
 /// - set the number of GRPC worker threads to poll queues
+/// - config the retry policy
 ///
 /// [grpcio]: https://github.com/tikv/grpc-rs
 #[derive(Default, Debug)]
@@ -110,6 +108,25 @@ impl GrpcioExporterBuilder {
         self.grpcio_config.completion_queue_count = count;
         self
     }
+
+    /// Set the retry policy of failed exports.
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.grpcio_config.retry_config = retry_config;
+        self
+    }
+}
+
+/// Retry the calls failing with a transient error, honoring the server's `RetryInfo`.
+pub(crate) fn classify_error(err: &grpcio::Error) -> RetryAction {
+    match err {
+        grpcio::Error::RpcFailure(status)
+            if status.code() == grpcio::RpcStatusCode::UNAVAILABLE
+                || status.code() == grpcio::RpcStatusCode::RESOURCE_EXHAUSTED =>
+        {
+            RetryAction::Retry(grpc_retry_delay(status.details()))
+        }
+        _ => RetryAction::Abort,
+    }
 }
 
 #[cfg(test)]
@@ -1,9 +1,13 @@
-use crate::{ExportConfig, Protocol};
+use crate::{Compression, ExportConfig, Protocol, RetryConfig};
+use futures::future::{self, Either};
+use http::{header::RETRY_AFTER, Request, StatusCode};
 use opentelemetry_http::HttpClient;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use super::default_headers;
+use super::retry::{retry_with_backoff, RetryAction};
 
 /// Configuration of the http transport
 #[cfg(feature = "http-proto")]
@@ -22,6 +26,12 @@ pub struct HttpConfig {
 
     /// Additional headers to send to the collector.
     pub headers: Option<HashMap<String, String>>,
+
+    /// The compression algorithm to use when communicating with the collector.
+    pub compression: Option<Compression>,
+
+    /// The retry policy of failed exports.
+    pub retry_config: RetryConfig,
 }
 
 #[cfg(any(
@@ -53,6 +63,8 @@ impl Default for HttpConfig {
             ))]
             client: None,
             headers: None,
+            compression: None,
+            retry_config: RetryConfig::default(),
         }
     }
 }
@@ -96,4 +108,197 @@ impl HttpExporterBuilder {
         self.http_config.headers = Some(inst_headers);
         self
     }
+
+    /// Set the compression algorithm to use when communicating with the collector.
+    ///
+    /// Gzip compression requires the `gzip-http` feature.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.http_config.compression = Some(compression);
+        self
+    }
+
+    /// Set the retry policy of failed exports.
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.http_config.retry_config = retry_config;
+        self
+    }
+}
+
+/// Compress the encoded request body, returning it along with its `Content-Encoding`.
+pub(crate) fn compress_body(
+    buf: Vec<u8>,
+    compression: Option<Compression>,
+) -> Result<(Vec<u8>, Option<&'static str>), crate::Error> {
+    match compression {
+        #[cfg(feature = "gzip-http")]
+        Some(Compression::Gzip) => {
+            use flate2::{write::GzEncoder, Compression as GzCompression};
+            use std::io::Write;
+
+            let mut encoder = GzEncoder::new(Vec::new(), GzCompression::default());
+            encoder.write_all(&buf)?;
+            Ok((encoder.finish()?, Some("gzip")))
+        }
+        #[cfg(not(feature = "gzip-http"))]
+        Some(compression) => Err(crate::Error::FeatureRequiredForCompressionAlgorithm(
+            "gzip-http",
+            compression,
+        )),
+        None => Ok((buf, None)),
+    }
+}
+
+enum AttemptError {
+    Status {
+        status: StatusCode,
+        retry_after: Option<Duration>,
+    },
+    Failed(crate::Error),
+}
+
+/// Send an export request, retrying the responses the OTLP specification deems transient.
+///
+/// The request is abandoned once `timeout` has elapsed, including the time spent waiting
+/// between attempts.
+pub(crate) async fn send_with_retry(
+    client: Arc<dyn HttpClient>,
+    request: Request<Vec<u8>>,
+    retry_config: RetryConfig,
+    timeout: Duration,
+) -> Result<(), crate::Error> {
+    let (parts, body) = request.into_parts();
+
+    let result = retry_with_backoff(retry_config, timeout, classify_error, |remaining| {
+        let mut request = Request::new(body.clone());
+        *request.method_mut() = parts.method.clone();
+        *request.uri_mut() = parts.uri.clone();
+        *request.headers_mut() = parts.headers.clone();
+
+        let client = Arc::clone(&client);
+        async move {
+            let response =
+                match future::select(client.send(request), futures_timer::Delay::new(remaining))
+                    .await
+                {
+                    Either::Left((response, _)) => response
+                        .map_err(|err| AttemptError::Failed(crate::Error::HttpClient(err)))?,
+                    Either::Right(_) => {
+                        return Err(AttemptError::Failed(crate::Error::RequestTimedOut(timeout)))
+                    }
+                };
+
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(AttemptError::Status {
+                    status: response.status(),
+                    retry_after: response
+                        .headers()
+                        .get(RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(parse_retry_after),
+                })
+            }
+        }
+    })
+    .await;
+
+    result.map_err(|err| match err {
+        AttemptError::Status { status, .. } => crate::Error::RequestStatus(status),
+        AttemptError::Failed(err) => err,
+    })
+}
+
+fn classify_error(err: &AttemptError) -> RetryAction {
+    match err {
+        AttemptError::Status {
+            status,
+            retry_after,
+        } if matches!(status.as_u16(), 429 | 502 | 503 | 504) => RetryAction::Retry(*retry_after),
+        _ => RetryAction::Abort,
+    }
+}
+
+/// Parse a `Retry-After` header given in seconds. HTTP dates are not supported, the backoff
+/// of the retry policy is used for them instead.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use opentelemetry_http::{Bytes, HttpError, Response};
+    use std::sync::Mutex;
+
+    #[derive(Debug)]
+    struct StatusSequenceClient {
+        statuses: Mutex<Vec<u16>>,
+        attempts: Mutex<usize>,
+    }
+
+    impl StatusSequenceClient {
+        fn new(statuses: Vec<u16>) -> Self {
+            StatusSequenceClient {
+                statuses: Mutex::new(statuses),
+                attempts: Mutex::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl HttpClient for StatusSequenceClient {
+        async fn send(&self, _request: Request<Vec<u8>>) -> Result<Response<Bytes>, HttpError> {
+            *self.attempts.lock().unwrap() += 1;
+            let status = self.statuses.lock().unwrap().remove(0);
+            Ok(Response::builder()
+                .status(status)
+                .header(RETRY_AFTER, "0")
+                .body(Bytes::new())?)
+        }
+    }
+
+    fn retry_config() -> RetryConfig {
+        RetryConfig {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(60),
+            max_backoff: Duration::from_secs(60),
+            jitter: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_retries_transient_statuses() {
+        let client = Arc::new(StatusSequenceClient::new(vec![503, 429, 200]));
+        // the backoff would exceed the timeout, so retries must use the Retry-After delay
+        let result = send_with_retry(
+            client.clone(),
+            Request::new(vec![]),
+            retry_config(),
+            Duration::from_secs(10),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(*client.attempts.lock().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_does_not_retry_other_statuses() {
+        let client = Arc::new(StatusSequenceClient::new(vec![400, 200]));
+        let result = send_with_retry(
+            client.clone(),
+            Request::new(vec![]),
+            retry_config(),
+            Duration::from_secs(10),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(crate::Error::RequestStatus(StatusCode::BAD_REQUEST))
+        ));
+        assert_eq!(*client.attempts.lock().unwrap(), 1);
+    }
 }
@@ -9,7 +9,10 @@ use crate::exporter::http::HttpExporterBuilder;
 #[cfg(feature = "grpc-tonic")]
 use crate::exporter::tonic::TonicExporterBuilder;
 use crate::Protocol;
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 use std::time::Duration;
 
@@ -19,7 +22,7 @@ use std::time::Duration;
 pub const OTEL_EXPORTER_OTLP_ENDPOINT: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
 /// Default target to which the exporter is going to send signals.
 pub const OTEL_EXPORTER_OTLP_ENDPOINT_DEFAULT: &str = OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT;
-/// Protocol the exporter will use. Either `http/protobuf` or `grpc`.
+/// Protocol the exporter will use. Either `http/protobuf`, `http/json` or `grpc`.
 pub const OTEL_EXPORTER_OTLP_PROTOCOL: &str = "OTEL_EXPORTER_OTLP_PROTOCOL";
 
 #[cfg(feature = "http-proto")]
@@ -33,12 +36,15 @@ pub const OTEL_EXPORTER_OTLP_PROTOCOL_DEFAULT: &str = OTEL_EXPORTER_OTLP_PROTOCO
 pub const OTEL_EXPORTER_OTLP_PROTOCOL_DEFAULT: &str = OTEL_EXPORTER_OTLP_PROTOCOL_GRPC;
 
 const OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_PROTOBUF: &str = "http/protobuf";
+const OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_JSON: &str = "http/json";
 const OTEL_EXPORTER_OTLP_PROTOCOL_GRPC: &str = "grpc";
 
 /// Max waiting time for the backend to process each signal batch, defaults to 10 seconds.
 pub const OTEL_EXPORTER_OTLP_TIMEOUT: &str = "OTEL_EXPORTER_OTLP_TIMEOUT";
 /// Default max waiting time for the backend to process each signal batch.
 pub const OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT: u64 = 10;
+/// Compression algorithm to use for all signals, only `gzip` is supported.
+pub const OTEL_EXPORTER_OTLP_COMPRESSION: &str = "OTEL_EXPORTER_OTLP_COMPRESSION";
 
 // Endpoints per protocol https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/protocol/exporter.md
 const OTEL_EXPORTER_OTLP_GRPC_ENDPOINT_DEFAULT: &str = "http://localhost:4317";
@@ -48,6 +54,7 @@ const OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT: &str = "http://localhost:4318";
 pub(crate) mod grpcio;
 #[cfg(feature = "http-proto")]
 pub(crate) mod http;
+pub(crate) mod retry;
 #[cfg(feature = "grpc-tonic")]
 pub(crate) mod tonic;
 
@@ -76,6 +83,52 @@ impl Default for ExportConfig {
     }
 }
 
+/// The compression algorithm to use when sending data.
+#[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Compression {
+    /// Compresses data using gzip.
+    Gzip,
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::Gzip => write!(f, "gzip"),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gzip" => Ok(Compression::Gzip),
+            _ => Err(crate::Error::UnsupportedCompressionAlgorithm(s.to_string())),
+        }
+    }
+}
+
+/// Resolve the compression of a signal exporter.
+///
+/// Compression configured on the builder takes precedence, then the signal specific
+/// environment variable and finally [`OTEL_EXPORTER_OTLP_COMPRESSION`].
+#[cfg(any(feature = "grpc-tonic", feature = "grpc-sys", feature = "http-proto"))]
+pub(crate) fn resolve_compression(
+    configured: Option<Compression>,
+    signal_env_var: &str,
+) -> Result<Option<Compression>, crate::Error> {
+    if configured.is_some() {
+        return Ok(configured);
+    }
+
+    match std::env::var(signal_env_var).or_else(|_| std::env::var(OTEL_EXPORTER_OTLP_COMPRESSION)) {
+        Ok(val) => val.parse().map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
 /// default protocol based on enabled features
 fn default_protocol() -> Protocol {
     match OTEL_EXPORTER_OTLP_PROTOCOL_DEFAULT {
@@ -89,7 +142,9 @@ fn default_protocol() -> Protocol {
 fn default_endpoint(protocol: Protocol) -> String {
     match protocol {
         Protocol::Grpc => OTEL_EXPORTER_OTLP_GRPC_ENDPOINT_DEFAULT.to_string(),
-        Protocol::HttpBinary => OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT.to_string(),
+        Protocol::HttpBinary | Protocol::HttpJson => {
+            OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT.to_string()
+        }
     }
 }
 
@@ -181,6 +236,7 @@ impl<B: HasExportConfig> WithExportConfig for B {
             .as_str()
         {
             OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_PROTOBUF => Protocol::HttpBinary,
+            OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_JSON => Protocol::HttpJson,
             OTEL_EXPORTER_OTLP_PROTOCOL_GRPC => Protocol::Grpc,
             _ => default_protocol(),
         };
@@ -215,10 +271,12 @@ mod tests {
     // If an env test fails then the mutex will be poisoned and the following error will be displayed.
     const LOCK_POISONED_MESSAGE: &str = "one of the other pipeline builder from env tests failed";
     use crate::exporter::{
-        default_endpoint, default_protocol, WithExportConfig, OTEL_EXPORTER_OTLP_ENDPOINT,
+        default_endpoint, default_protocol, resolve_compression, Compression, WithExportConfig,
+        OTEL_EXPORTER_OTLP_COMPRESSION, OTEL_EXPORTER_OTLP_ENDPOINT,
         OTEL_EXPORTER_OTLP_GRPC_ENDPOINT_DEFAULT, OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT,
-        OTEL_EXPORTER_OTLP_PROTOCOL_GRPC, OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_PROTOBUF,
-        OTEL_EXPORTER_OTLP_TIMEOUT, OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT,
+        OTEL_EXPORTER_OTLP_PROTOCOL_GRPC, OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_JSON,
+        OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_PROTOBUF, OTEL_EXPORTER_OTLP_TIMEOUT,
+        OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT,
     };
     use crate::{new_exporter, Protocol, OTEL_EXPORTER_OTLP_PROTOCOL};
     use std::sync::Mutex;
@@ -278,6 +336,27 @@ mod tests {
         assert!(std::env::var(OTEL_EXPORTER_OTLP_PROTOCOL).is_err());
     }
 
+    #[test]
+    fn test_pipeline_builder_from_env_protocol_http_json() {
+        let _env_lock = ENV_LOCK.lock().expect(LOCK_POISONED_MESSAGE);
+        std::env::set_var(
+            OTEL_EXPORTER_OTLP_PROTOCOL,
+            OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_JSON,
+        );
+        let exporter_builder = new_exporter().tonic().with_env();
+        assert_eq!(
+            exporter_builder.exporter_config.protocol,
+            Protocol::HttpJson
+        );
+        assert_eq!(
+            exporter_builder.exporter_config.endpoint,
+            OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT
+        );
+
+        std::env::remove_var(OTEL_EXPORTER_OTLP_PROTOCOL);
+        assert!(std::env::var(OTEL_EXPORTER_OTLP_PROTOCOL).is_err());
+    }
+
     #[test]
     fn test_pipeline_builder_from_env_protocol_grpc() {
         let _env_lock = ENV_LOCK.lock().expect(LOCK_POISONED_MESSAGE);
@@ -342,4 +421,51 @@ mod tests {
         std::env::remove_var(OTEL_EXPORTER_OTLP_TIMEOUT);
         assert!(std::env::var(OTEL_EXPORTER_OTLP_TIMEOUT).is_err());
     }
+
+    #[test]
+    fn test_resolve_compression_from_env() {
+        let _env_lock = ENV_LOCK.lock().expect(LOCK_POISONED_MESSAGE);
+        const SIGNAL_COMPRESSION: &str = "OTEL_EXPORTER_OTLP_TEST_COMPRESSION";
+        assert_eq!(resolve_compression(None, SIGNAL_COMPRESSION).unwrap(), None);
+
+        std::env::set_var(OTEL_EXPORTER_OTLP_COMPRESSION, "gzip");
+        assert_eq!(
+            resolve_compression(None, SIGNAL_COMPRESSION).unwrap(),
+            Some(Compression::Gzip)
+        );
+
+        // the signal specific variable takes precedence over the general one
+        std::env::set_var(SIGNAL_COMPRESSION, "zstd");
+        assert!(matches!(
+            resolve_compression(None, SIGNAL_COMPRESSION),
+            Err(crate::Error::UnsupportedCompressionAlgorithm(algorithm)) if algorithm == "zstd"
+        ));
+
+        // the configured compression takes precedence over environment variables
+        assert_eq!(
+            resolve_compression(Some(Compression::Gzip), SIGNAL_COMPRESSION).unwrap(),
+            Some(Compression::Gzip)
+        );
+
+        std::env::remove_var(SIGNAL_COMPRESSION);
+        std::env::remove_var(OTEL_EXPORTER_OTLP_COMPRESSION);
+        assert!(std::env::var(OTEL_EXPORTER_OTLP_COMPRESSION).is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "grpc-sys", feature = "trace"))]
+    fn test_grpcio_span_exporter_rejects_invalid_compression() {
+        use crate::exporter::grpcio::GrpcioConfig;
+        use crate::{ExportConfig, SpanExporter, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION};
+
+        let _env_lock = ENV_LOCK.lock().expect(LOCK_POISONED_MESSAGE);
+        std::env::set_var(OTEL_EXPORTER_OTLP_TRACES_COMPRESSION, "zstd");
+        let result = SpanExporter::new_grpcio(ExportConfig::default(), GrpcioConfig::default());
+        std::env::remove_var(OTEL_EXPORTER_OTLP_TRACES_COMPRESSION);
+
+        assert!(matches!(
+            result,
+            Err(crate::Error::UnsupportedCompressionAlgorithm(algorithm)) if algorithm == "zstd"
+        ));
+    }
 }
@@ -0,0 +1,314 @@
+//! Retry policy of the OTLP exporters.
+//!
+//! Failed exports are retried with an exponential backoff when the collector reports a transient
+//! failure, as recommended by the [OTLP specification]. Every transport decides which of its
+//! errors are retryable and may ask for a specific delay when the server throttles requests.
+//!
+//! [OTLP specification]: https://github.com/open-telemetry/opentelemetry-specification/blob/v1.13.0/specification/protocol/otlp.md#failures
+use rand::Rng;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Configuration of the retry policy applied when an export fails with a retryable error.
+///
+/// The backoff starts at [`initial_backoff`] and doubles after each retry until it reaches
+/// [`max_backoff`]. A delay requested by the server, e.g. through a `Retry-After` header, replaces
+/// the computed backoff. No attempt is made once the export timeout has elapsed.
+///
+/// [`initial_backoff`]: RetryConfig::initial_backoff
+/// [`max_backoff`]: RetryConfig::max_backoff
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryConfig {
+    /// Maximum number of attempts, including the first one. `1` disables retries.
+    pub max_attempts: usize,
+
+    /// Backoff before the first retry.
+    pub initial_backoff: Duration,
+
+    /// Upper bound of the backoff between two attempts.
+    pub max_backoff: Duration,
+
+    /// Fraction of the backoff that is randomly removed, between `0.0` and `1.0`.
+    pub jitter: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// A policy that makes a single attempt.
+    pub fn disabled() -> Self {
+        RetryConfig {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .checked_mul(2u32.saturating_pow(retry))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter > 0.0 {
+            backoff.mul_f64(1.0 - rand::thread_rng().gen_range(0.0..jitter))
+        } else {
+            backoff
+        }
+    }
+}
+
+/// Whether a failed attempt should be retried.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum RetryAction {
+    /// Retry after the given delay, or after the policy's backoff if the server didn't ask for
+    /// one.
+    Retry(Option<Duration>),
+    /// The error is not transient, give up.
+    Abort,
+}
+
+/// Run `attempt` until it succeeds, fails with an error `classify` deems not retryable, the
+/// policy runs out of attempts or the next attempt would start after `timeout` has elapsed.
+///
+/// Each attempt is given the time left until the deadline and is expected to give up once it is
+/// exceeded.
+pub(crate) async fn retry_with_backoff<T, E, F, Fut>(
+    config: RetryConfig,
+    timeout: Duration,
+    classify: impl Fn(&E) -> RetryAction,
+    mut attempt: F,
+) -> Result<T, E>
+where
+    F: FnMut(Duration) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let deadline = Instant::now() + timeout;
+    let mut retry = 0;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let err = match attempt(remaining).await {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+
+        retry += 1;
+        let delay = match classify(&err) {
+            RetryAction::Retry(_) if retry >= config.max_attempts => return Err(err),
+            RetryAction::Retry(Some(delay)) => delay,
+            RetryAction::Retry(None) => config.backoff(retry as u32 - 1),
+            RetryAction::Abort => return Err(err),
+        };
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if delay >= remaining {
+            return Err(err);
+        }
+        futures_timer::Delay::new(delay).await;
+    }
+}
+
+/// The delay of the `google.rpc.RetryInfo` detail in the binary `grpc-status-details-bin`
+/// metadata of a failed call, if the server sent one.
+#[cfg(any(feature = "grpc-tonic", feature = "grpc-sys"))]
+pub(crate) fn grpc_retry_delay(status_details: &[u8]) -> Option<Duration> {
+    use prost::Message;
+
+    const RETRY_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.RetryInfo";
+
+    let status = rpc::Status::decode(status_details).ok()?;
+    status
+        .details
+        .iter()
+        .filter(|detail| detail.type_url == RETRY_INFO_TYPE_URL)
+        .find_map(|detail| rpc::RetryInfo::decode(detail.value.as_slice()).ok())
+        .and_then(|info| info.retry_delay)
+        .and_then(|delay| {
+            Some(Duration::new(
+                u64::try_from(delay.seconds).ok()?,
+                u32::try_from(delay.nanos).ok()?,
+            ))
+        })
+}
+
+/// The subset of the `google.rpc` and `google.protobuf` messages needed to read `RetryInfo`.
+#[cfg(any(feature = "grpc-tonic", feature = "grpc-sys"))]
+mod rpc {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(super) struct Status {
+        #[prost(int32, tag = "1")]
+        pub(super) code: i32,
+        #[prost(string, tag = "2")]
+        pub(super) message: String,
+        #[prost(message, repeated, tag = "3")]
+        pub(super) details: Vec<Any>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(super) struct Any {
+        #[prost(string, tag = "1")]
+        pub(super) type_url: String,
+        #[prost(bytes = "vec", tag = "2")]
+        pub(super) value: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(super) struct RetryInfo {
+        #[prost(message, optional, tag = "1")]
+        pub(super) retry_delay: Option<Duration>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(super) struct Duration {
+        #[prost(int64, tag = "1")]
+        pub(super) seconds: i64,
+        #[prost(int32, tag = "2")]
+        pub(super) nanos: i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn no_delay() -> RetryConfig {
+        RetryConfig {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            jitter: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let config = RetryConfig {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            jitter: 0.0,
+        };
+        assert_eq!(config.backoff(0), Duration::from_millis(100));
+        assert_eq!(config.backoff(2), Duration::from_millis(400));
+        assert_eq!(config.backoff(3), Duration::from_millis(500));
+        assert_eq!(config.backoff(64), Duration::from_millis(500));
+
+        let jittered = RetryConfig {
+            jitter: 0.5,
+            ..config
+        }
+        .backoff(0);
+        assert!(jittered > Duration::from_millis(50) && jittered <= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_retries_retryable_errors_until_max_attempts() {
+        let attempts = Cell::new(0);
+        let result: Result<(), &str> = retry_with_backoff(
+            no_delay(),
+            Duration::from_secs(10),
+            |_| RetryAction::Retry(None),
+            |_| {
+                attempts.set(attempts.get() + 1);
+                async { Err("unavailable") }
+            },
+        )
+        .await;
+        assert_eq!(result, Err("unavailable"));
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stops_on_success_or_non_retryable_error() {
+        let attempts = Cell::new(0);
+        let result: Result<usize, &str> = retry_with_backoff(
+            no_delay(),
+            Duration::from_secs(10),
+            |_| RetryAction::Retry(None),
+            |_| {
+                attempts.set(attempts.get() + 1);
+                let attempt = attempts.get();
+                async move {
+                    if attempt == 2 {
+                        Ok(attempt)
+                    } else {
+                        Err("unavailable")
+                    }
+                }
+            },
+        )
+        .await;
+        assert_eq!(result, Ok(2));
+
+        attempts.set(0);
+        let result: Result<(), &str> = retry_with_backoff(
+            no_delay(),
+            Duration::from_secs(10),
+            |_| RetryAction::Abort,
+            |_| {
+                attempts.set(attempts.get() + 1);
+                async { Err("invalid argument") }
+            },
+        )
+        .await;
+        assert_eq!(result, Err("invalid argument"));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_past_timeout() {
+        let attempts = Cell::new(0);
+        let result: Result<(), &str> = retry_with_backoff(
+            no_delay(),
+            Duration::from_millis(50),
+            // the server asks for a delay longer than the export timeout
+            |_| RetryAction::Retry(Some(Duration::from_secs(1))),
+            |remaining| {
+                attempts.set(attempts.get() + 1);
+                assert!(remaining <= Duration::from_millis(50));
+                async { Err("resource exhausted") }
+            },
+        )
+        .await;
+        assert_eq!(result, Err("resource exhausted"));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    #[cfg(any(feature = "grpc-tonic", feature = "grpc-sys"))]
+    fn test_grpc_retry_delay() {
+        use prost::Message;
+
+        let status = rpc::Status {
+            code: 8,
+            message: "slow down".into(),
+            details: vec![rpc::Any {
+                type_url: "type.googleapis.com/google.rpc.RetryInfo".into(),
+                value: rpc::RetryInfo {
+                    retry_delay: Some(rpc::Duration {
+                        seconds: 2,
+                        nanos: 500_000_000,
+                    }),
+                }
+                .encode_to_vec(),
+            }],
+        };
+
+        assert_eq!(
+            grpc_retry_delay(&status.encode_to_vec()),
+            Some(Duration::from_millis(2500))
+        );
+        assert_eq!(grpc_retry_delay(&[]), None);
+    }
+}
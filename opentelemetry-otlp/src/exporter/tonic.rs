@@ -1,9 +1,11 @@
-use crate::ExportConfig;
+use crate::{Compression, ExportConfig, RetryConfig};
+use tonic::codec::CompressionEncoding;
 use tonic::metadata::MetadataMap;
 #[cfg(feature = "tls")]
 use tonic::transport::ClientTlsConfig;
 
 use super::default_headers;
+use super::retry::{grpc_retry_delay, RetryAction};
 
 /// Configuration for [tonic]
 ///
@@ -16,6 +18,28 @@ pub struct TonicConfig {
     /// TLS settings for the collector endpoint.
     #[cfg(feature = "tls")]
     pub tls_config: Option<ClientTlsConfig>,
+
+    /// The compression algorithm to use when communicating with the collector.
+    pub compression: Option<Compression>,
+
+    /// The retry policy of failed exports.
+    pub retry_config: RetryConfig,
+}
+
+impl TryFrom<Compression> for CompressionEncoding {
+    type Error = crate::Error;
+
+    fn try_from(value: Compression) -> Result<Self, Self::Error> {
+        match value {
+            #[cfg(feature = "gzip-tonic")]
+            Compression::Gzip => Ok(CompressionEncoding::Gzip),
+            #[cfg(not(feature = "gzip-tonic"))]
+            Compression::Gzip => Err(crate::Error::FeatureRequiredForCompressionAlgorithm(
+                "gzip-tonic",
+                Compression::Gzip,
+            )),
+        }
+    }
 }
 
 /// Build a trace exporter that uses [tonic] as grpc layer and opentelemetry protocol.
@@ -23,6 +47,8 @@ pub struct TonicConfig {
 /// It allows users to
 /// - add additional metadata
 /// - set tls config(with `tls` feature enabled)
+/// - config compression(with `gzip-tonic` feature enabled)
+/// - config the retry policy
 /// - bring custom [channel]
 ///
 /// [tonic]: <https://github.com/hyperium/tonic>
@@ -44,6 +70,8 @@ impl Default for TonicExporterBuilder {
             )),
             #[cfg(feature = "tls")]
             tls_config: None,
+            compression: None,
+            retry_config: RetryConfig::default(),
         };
 
         TonicExporterBuilder {
@@ -77,6 +105,20 @@ impl TonicExporterBuilder {
         self
     }
 
+    /// Set the compression algorithm to use when communicating with the collector.
+    ///
+    /// Gzip compression requires the `gzip-tonic` feature.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.tonic_config.compression = Some(compression);
+        self
+    }
+
+    /// Set the retry policy of failed exports.
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.tonic_config.retry_config = retry_config;
+        self
+    }
+
     /// Use `channel` as tonic's transport channel.
     /// this will override tls config and should only be used
     /// when working with non-HTTP transports.
@@ -89,9 +131,23 @@ impl TonicExporterBuilder {
     }
 }
 
+/// Retry the calls failing with a transient error, honoring the server's `RetryInfo`.
+pub(crate) fn classify_status(status: &tonic::Status) -> RetryAction {
+    match status.code() {
+        tonic::Code::Unavailable | tonic::Code::ResourceExhausted => {
+            RetryAction::Retry(grpc_retry_delay(status.details()))
+        }
+        _ => RetryAction::Abort,
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "gzip-tonic")]
+    use crate::Compression;
     use crate::TonicExporterBuilder;
+    #[cfg(feature = "gzip-tonic")]
+    use tonic::codec::CompressionEncoding;
     use tonic::metadata::{MetadataMap, MetadataValue};
 
     #[test]
@@ -123,4 +179,15 @@ mod tests {
                 .len()
         );
     }
+
+    #[test]
+    #[cfg(feature = "gzip-tonic")]
+    fn test_with_gzip_compression() {
+        let builder = TonicExporterBuilder::default().with_compression(Compression::Gzip);
+        assert_eq!(builder.tonic_config.compression, Some(Compression::Gzip));
+        assert_eq!(
+            CompressionEncoding::try_from(Compression::Gzip).unwrap(),
+            CompressionEncoding::Gzip
+        );
+    }
 }
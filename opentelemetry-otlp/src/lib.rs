@@ -187,22 +187,24 @@ mod metric;
 mod span;
 mod transform;
 
+pub use crate::exporter::retry::RetryConfig;
 pub use crate::exporter::ExportConfig;
 #[cfg(feature = "trace")]
 pub use crate::span::{
-    OtlpTracePipeline, SpanExporter, SpanExporterBuilder, OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
-    OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
+    OtlpTracePipeline, SpanExporter, SpanExporterBuilder, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+    OTEL_EXPORTER_OTLP_TRACES_ENDPOINT, OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
 };
 
 #[cfg(feature = "metrics")]
 pub use crate::metric::{
-    MetricsExporter, MetricsExporterBuilder, OtlpMetricPipeline,
-    OTEL_EXPORTER_OTLP_METRICS_ENDPOINT, OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
+    ExportQueueConfig, MetricsExporter, MetricsExporterBuilder, OtlpMetricPipeline,
+    OTEL_EXPORTER_OTLP_METRICS_COMPRESSION, OTEL_EXPORTER_OTLP_METRICS_ENDPOINT,
+    OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
 };
 
 pub use crate::exporter::{
-    HasExportConfig, WithExportConfig, OTEL_EXPORTER_OTLP_ENDPOINT,
-    OTEL_EXPORTER_OTLP_ENDPOINT_DEFAULT, OTEL_EXPORTER_OTLP_PROTOCOL,
+    Compression, HasExportConfig, WithExportConfig, OTEL_EXPORTER_OTLP_COMPRESSION,
+    OTEL_EXPORTER_OTLP_ENDPOINT, OTEL_EXPORTER_OTLP_ENDPOINT_DEFAULT, OTEL_EXPORTER_OTLP_PROTOCOL,
     OTEL_EXPORTER_OTLP_PROTOCOL_DEFAULT, OTEL_EXPORTER_OTLP_TIMEOUT,
     OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT,
 };
@@ -212,7 +214,7 @@ use opentelemetry::sdk::export::ExportError;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[cfg(feature = "grpc-sys")]
-pub use crate::exporter::grpcio::{Compression, Credentials, GrpcioExporterBuilder};
+pub use crate::exporter::grpcio::{Credentials, GrpcioExporterBuilder};
 #[cfg(feature = "http-proto")]
 pub use crate::exporter::http::HttpExporterBuilder;
 #[cfg(feature = "grpc-tonic")]
@@ -241,8 +243,7 @@ impl OtlpExporterPipeline {
 
     /// Use grpcio as grpc layer, return a `GrpcioExporterBuilder` to config the grpcio and build the exporter.
     ///
-    /// This exporter can only be used in `tracing` pipeline. Support for `metrics` pipeline will be
-    /// added in the future.
+    /// This exporter can be used in both `tracing` and `metrics` pipeline.
     #[cfg(feature = "grpc-sys")]
     pub fn grpcio(self) -> GrpcioExporterBuilder {
         GrpcioExporterBuilder::default()
@@ -251,8 +252,7 @@ impl OtlpExporterPipeline {
     /// Use HTTP as transport layer, return a `HttpExporterBuilder` to config the http transport
     /// and build the exporter.
     ///
-    /// This exporter can only be used in `tracing` pipeline. Support for `metrics` pipeline will be
-    /// added in the future.
+    /// This exporter can be used in both `tracing` and `metrics` pipeline.
     #[cfg(feature = "http-proto")]
     pub fn http(self) -> HttpExporterBuilder {
         HttpExporterBuilder::default()
@@ -320,6 +320,21 @@ pub enum Error {
     #[error("http request failed with {0}")]
     RequestFailed(#[from] http::Error),
 
+    /// The http client failed to send the request.
+    #[cfg(feature = "http-proto")]
+    #[error("http request failed with {0}")]
+    HttpClient(opentelemetry_http::HttpError),
+
+    /// The collector responded with an unsuccessful status code.
+    #[cfg(feature = "http-proto")]
+    #[error("http request failed with status {0}")]
+    RequestStatus(http::StatusCode),
+
+    /// The collector didn't respond before the export timeout.
+    #[cfg(feature = "http-proto")]
+    #[error("http request timed out after {0:?}")]
+    RequestTimedOut(std::time::Duration),
+
     /// The provided value is invalid in HTTP headers.
     #[cfg(feature = "http-proto")]
     #[error("http header value error {0}")]
@@ -335,11 +350,43 @@ pub enum Error {
     #[error("prost encoding error {0}")]
     EncodeError(#[from] prost::EncodeError),
 
+    /// Compressing the request body failed
+    #[cfg(feature = "gzip-http")]
+    #[error("compression error {0}")]
+    CompressionError(#[from] std::io::Error),
+
+    /// Serializing the request as OTLP/JSON failed
+    #[cfg(feature = "http-json")]
+    #[error("json encoding error {0}")]
+    JsonEncodeError(#[from] serde_json::Error),
+
+    /// The compression algorithm is not supported.
+    #[error("unsupported compression algorithm '{0}'")]
+    UnsupportedCompressionAlgorithm(String),
+
+    /// The compression algorithm needs a crate feature that is not enabled.
+    #[error("feature '{0}' is required to use the compression algorithm '{1}'")]
+    FeatureRequiredForCompressionAlgorithm(&'static str, Compression),
+
+    /// The selected protocol is not supported by the exporter or the enabled features.
+    #[error("protocol {0:?} is not supported, check the enabled features")]
+    UnsupportedProtocol(Protocol),
+
     /// The lock in exporters has been poisoned.
     #[cfg(feature = "metrics")]
     #[error("the lock of the {0} has been poisoned")]
     PoisonedLock(&'static str),
 
+    /// The metrics export queue is full, the collected metrics are dropped.
+    #[cfg(feature = "metrics")]
+    #[error("the metrics export queue is full, dropping the collected metrics")]
+    ExportQueueFull,
+
+    /// The exporter has been shut down and doesn't accept new data.
+    #[cfg(feature = "metrics")]
+    #[error("the exporter has been shut down")]
+    ExporterShutdown,
+
     /// The pipeline will need a exporter to complete setup. Throw this error if none is provided.
     #[error("no exporter builder is provided, please provide one using with_exporter() method")]
     NoExporterBuilder,
@@ -373,10 +420,10 @@ impl ExportError for Error {
 pub enum Protocol {
     /// GRPC protocol
     Grpc,
-    // TODO add support for other protocols
-    // HttpJson,
     /// HTTP protocol with binary protobuf
     HttpBinary,
+    /// HTTP protocol with the JSON encoding of protobuf, requires the `http-json` feature
+    HttpJson,
 }
 
 #[cfg(feature = "metrics")]
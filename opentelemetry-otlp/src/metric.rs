@@ -2,15 +2,19 @@
 //!
 //! Defines a [MetricsExporter] to send metric data to backend via OTEL protocol.
 //!
-//! Currently, OTEL metrics exporter only support GRPC connection via tonic on tokio runtime.
+//! Metrics can be sent with tonic or grpcio as grpc layer, or over HTTP with binary protobuf.
 
-use crate::exporter::{
-    tonic::{TonicConfig, TonicExporterBuilder},
-    ExportConfig,
-};
+#[cfg(any(feature = "grpc-sys", feature = "grpc-tonic"))]
+use crate::exporter::retry::retry_with_backoff;
+use crate::exporter::{resolve_compression, retry::RetryConfig, ExportConfig};
 use crate::transform::{record_to_metric, sink, CheckpointedMetrics};
 use crate::{Error, OtlpPipeline};
 use core::fmt;
+use futures::{
+    channel::{mpsc, oneshot},
+    future::BoxFuture,
+    SinkExt, StreamExt,
+};
 use opentelemetry::{global, metrics::Result, runtime::Runtime};
 use opentelemetry::{
     sdk::{
@@ -28,21 +32,52 @@ use opentelemetry::{
     },
     Context,
 };
-#[cfg(feature = "grpc-tonic")]
-use opentelemetry_proto::tonic::collector::metrics::v1::{
-    metrics_service_client::MetricsServiceClient, ExportMetricsServiceRequest,
-};
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
 use std::fmt::{Debug, Formatter};
-#[cfg(feature = "grpc-tonic")]
 use std::str::FromStr;
 use std::sync::Mutex;
 use std::time;
 use std::time::Duration;
-use tonic::metadata::KeyAndValueRef;
-#[cfg(feature = "grpc-tonic")]
-use tonic::transport::Channel;
+
 #[cfg(feature = "grpc-tonic")]
-use tonic::Request;
+use {
+    crate::exporter::tonic::{TonicConfig, TonicExporterBuilder},
+    opentelemetry_proto::tonic::collector::metrics::v1::metrics_service_client::MetricsServiceClient,
+    tonic::{
+        metadata::{KeyAndValueRef, MetadataMap},
+        transport::Channel,
+        Request,
+    },
+};
+
+#[cfg(feature = "grpc-sys")]
+use {
+    crate::exporter::grpcio::{GrpcioConfig, GrpcioExporterBuilder},
+    grpcio::{
+        CallOption, Channel as GrpcChannel, ChannelBuilder, ChannelCredentialsBuilder, Environment,
+        MetadataBuilder,
+    },
+    opentelemetry_proto::grpcio::{
+        metrics_service::ExportMetricsServiceRequest as GrpcRequest,
+        metrics_service_grpc::MetricsServiceClient as GrpcioMetricsServiceClient,
+    },
+};
+
+#[cfg(feature = "http-proto")]
+use {
+    crate::exporter::http::{compress_body, send_with_retry, HttpConfig, HttpExporterBuilder},
+    crate::{Compression, Protocol},
+    http::{
+        header::{HeaderName, HeaderValue, CONTENT_ENCODING, CONTENT_TYPE},
+        Method, Uri,
+    },
+    opentelemetry_http::HttpClient,
+    prost::Message,
+    std::sync::Arc,
+};
+
+#[cfg(any(feature = "grpc-sys", feature = "http-proto"))]
+use std::collections::HashMap;
 
 /// Target to which the exporter is going to send metrics, defaults to https://localhost:4317/v1/metrics.
 /// Learn about the relationship between this constant and default/spans/logs at
@@ -50,6 +85,8 @@ use tonic::Request;
 pub const OTEL_EXPORTER_OTLP_METRICS_ENDPOINT: &str = "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT";
 /// Max waiting time for the backend to process each metrics batch, defaults to 10s.
 pub const OTEL_EXPORTER_OTLP_METRICS_TIMEOUT: &str = "OTEL_EXPORTER_OTLP_METRICS_TIMEOUT";
+/// Compression algorithm to use for metrics, overrides `OTEL_EXPORTER_OTLP_COMPRESSION`.
+pub const OTEL_EXPORTER_OTLP_METRICS_COMPRESSION: &str = "OTEL_EXPORTER_OTLP_METRICS_COMPRESSION";
 
 impl OtlpPipeline {
     /// Create a OTLP metrics pipeline.
@@ -72,24 +109,38 @@ impl OtlpPipeline {
             resource: None,
             period: None,
             timeout: None,
+            export_queue_config: ExportQueueConfig::default(),
         }
     }
 }
 
 /// OTLP metrics exporter builder.
 #[derive(Debug)]
+// This enum only used during initialization stage of application. The overhead should be OK.
+#[allow(clippy::large_enum_variant)]
 #[non_exhaustive]
 pub enum MetricsExporterBuilder {
     /// Tonic metrics exporter builder
     #[cfg(feature = "grpc-tonic")]
     Tonic(TonicExporterBuilder),
+    /// Grpcio metrics exporter builder
+    #[cfg(feature = "grpc-sys")]
+    Grpcio(GrpcioExporterBuilder),
+    /// Http metrics exporter builder
+    #[cfg(feature = "http-proto")]
+    Http(HttpExporterBuilder),
 }
 
 impl MetricsExporterBuilder {
     /// Build a OTLP metrics exporter with given configuration.
-    pub fn build_metrics_exporter(
+    ///
+    /// The exporter sends the metrics from a background task spawned on `rt`, the collected
+    /// metrics wait in a queue configured by `export_queue_config` until they are sent.
+    pub fn build_metrics_exporter<RT: Runtime>(
         self,
         temporality_selector: Box<dyn TemporalitySelector + Send + Sync>,
+        export_queue_config: ExportQueueConfig,
+        rt: RT,
     ) -> Result<MetricsExporter> {
         match self {
             #[cfg(feature = "grpc-tonic")]
@@ -97,21 +148,51 @@ impl MetricsExporterBuilder {
                 builder.exporter_config,
                 builder.tonic_config,
                 temporality_selector,
+                export_queue_config,
+                rt,
+            )?),
+            #[cfg(feature = "grpc-sys")]
+            MetricsExporterBuilder::Grpcio(builder) => Ok(MetricsExporter::new_grpcio(
+                builder.exporter_config,
+                builder.grpcio_config,
+                temporality_selector,
+                export_queue_config,
+                rt,
+            )?),
+            #[cfg(feature = "http-proto")]
+            MetricsExporterBuilder::Http(builder) => Ok(MetricsExporter::new_http(
+                builder.exporter_config,
+                builder.http_config,
+                temporality_selector,
+                export_queue_config,
+                rt,
             )?),
         }
     }
 }
 
+#[cfg(feature = "grpc-tonic")]
 impl From<TonicExporterBuilder> for MetricsExporterBuilder {
     fn from(exporter: TonicExporterBuilder) -> Self {
         MetricsExporterBuilder::Tonic(exporter)
     }
 }
 
+#[cfg(feature = "grpc-sys")]
+impl From<GrpcioExporterBuilder> for MetricsExporterBuilder {
+    fn from(exporter: GrpcioExporterBuilder) -> Self {
+        MetricsExporterBuilder::Grpcio(exporter)
+    }
+}
+
+#[cfg(feature = "http-proto")]
+impl From<HttpExporterBuilder> for MetricsExporterBuilder {
+    fn from(exporter: HttpExporterBuilder) -> Self {
+        MetricsExporterBuilder::Http(exporter)
+    }
+}
+
 /// Pipeline to build OTLP metrics exporter
-///
-/// Note that currently the OTLP metrics exporter only supports tonic as it's grpc layer and tokio as
-/// runtime.
 pub struct OtlpMetricPipeline<AS, TS, RT> {
     rt: RT,
     aggregator_selector: AS,
@@ -120,6 +201,7 @@ pub struct OtlpMetricPipeline<AS, TS, RT> {
     resource: Option<Resource>,
     period: Option<time::Duration>,
     timeout: Option<time::Duration>,
+    export_queue_config: ExportQueueConfig,
 }
 
 impl<AS, TS, RT> OtlpMetricPipeline<AS, TS, RT>
@@ -160,12 +242,24 @@ where
         }
     }
 
+    /// Configure the queue holding the metrics waiting to be sent, and what happens when it's full.
+    pub fn with_export_queue_config(self, export_queue_config: ExportQueueConfig) -> Self {
+        OtlpMetricPipeline {
+            export_queue_config,
+            ..self
+        }
+    }
+
     /// Build push controller.
     pub fn build(self) -> Result<BasicController> {
         let exporter = self
             .exporter_pipeline
             .ok_or(Error::NoExporterBuilder)?
-            .build_metrics_exporter(Box::new(self.temporality_selector.clone()))?;
+            .build_metrics_exporter(
+                Box::new(self.temporality_selector.clone()),
+                self.export_queue_config,
+                self.rt.clone(),
+            )?;
 
         let mut builder = controllers::basic(processors::factory(
             self.aggregator_selector,
@@ -198,30 +292,222 @@ impl<AS, TS, RT> fmt::Debug for OtlpMetricPipeline<AS, TS, RT> {
             .field("resource", &self.resource)
             .field("period", &self.period)
             .field("timeout", &self.timeout)
+            .field("export_queue_config", &self.export_queue_config)
             .finish()
     }
 }
 
+/// Configuration of the queue holding the collected metrics until they are sent to the collector.
+///
+/// Collection never waits on the network: when the queue is full, the newly collected metrics are
+/// dropped and the export returns [`Error::ExportQueueFull`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExportQueueConfig {
+    /// Number of collections that can wait while an export is in progress, defaults to 2.
+    ///
+    /// The queue always holds at least one collection, a size of 0 behaves like 1.
+    pub max_queue_size: usize,
+}
+
+impl Default for ExportQueueConfig {
+    fn default() -> Self {
+        ExportQueueConfig { max_queue_size: 2 }
+    }
+}
+
 enum ExportMsg {
+    Export(ExportMetricsServiceRequest),
+    Shutdown(oneshot::Sender<()>),
+}
+
+/// Transport used to send the metrics to the collector.
+enum MetricsClient {
     #[cfg(feature = "grpc-tonic")]
-    Export(tonic::Request<ExportMetricsServiceRequest>),
-    Shutdown,
+    Tonic {
+        client: MetricsServiceClient<Channel>,
+        metadata: Option<MetadataMap>,
+        timeout: Duration,
+        retry_config: RetryConfig,
+    },
+    #[cfg(feature = "grpc-sys")]
+    Grpcio {
+        client: GrpcioMetricsServiceClient,
+        headers: Option<HashMap<String, String>>,
+        timeout: Duration,
+        retry_config: RetryConfig,
+    },
+    #[cfg(feature = "http-proto")]
+    Http {
+        client: Arc<dyn HttpClient>,
+        collector_endpoint: Uri,
+        headers: Option<HashMap<String, String>>,
+        compression: Option<Compression>,
+        timeout: Duration,
+        retry_config: RetryConfig,
+    },
+}
+
+impl MetricsClient {
+    fn export(&self, request: ExportMetricsServiceRequest) -> BoxFuture<'static, Result<()>> {
+        match self {
+            #[cfg(feature = "grpc-tonic")]
+            MetricsClient::Tonic {
+                client,
+                metadata,
+                timeout,
+                retry_config,
+            } => {
+                let (client, metadata) = (client.clone(), metadata.clone());
+                let (timeout, retry_config) = (*timeout, *retry_config);
+                Box::pin(async move {
+                    retry_with_backoff(
+                        retry_config,
+                        timeout,
+                        crate::exporter::tonic::classify_status,
+                        |remaining| {
+                            let mut request = Request::new(request.clone());
+                            request.set_timeout(remaining);
+                            if let Some(metadata) = &metadata {
+                                for key_and_value in metadata.iter() {
+                                    match key_and_value {
+                                        KeyAndValueRef::Ascii(key, value) => {
+                                            request.metadata_mut().append(key, value.to_owned())
+                                        }
+                                        KeyAndValueRef::Binary(key, value) => {
+                                            request.metadata_mut().append_bin(key, value.to_owned())
+                                        }
+                                    };
+                                }
+                            }
+
+                            let mut client = client.clone();
+                            async move { client.export(request).await }
+                        },
+                    )
+                    .await
+                    .map_err::<crate::Error, _>(Into::into)?;
+                    Ok(())
+                })
+            }
+            #[cfg(feature = "grpc-sys")]
+            MetricsClient::Grpcio {
+                client,
+                headers,
+                timeout,
+                retry_config,
+            } => {
+                // The grpcio types are generated from the same protos, so the request is
+                // converted through its wire format instead of duplicating the transformation.
+                let request: GrpcRequest = match protobuf::Message::parse_from_bytes(
+                    &prost::Message::encode_to_vec(&request),
+                ) {
+                    Ok(request) => request,
+                    Err(err) => {
+                        return Box::pin(futures::future::ready(Err(crate::Error::from(
+                            grpcio::Error::Codec(Box::new(err)),
+                        )
+                        .into())))
+                    }
+                };
+
+                let mut call_options = CallOption::default();
+                if let Some(headers) = headers.clone() {
+                    let mut metadata_builder: MetadataBuilder = MetadataBuilder::new();
+
+                    for (key, value) in headers {
+                        let _ = metadata_builder.add_str(key.as_str(), value.as_str());
+                    }
+
+                    call_options = call_options.headers(metadata_builder.build());
+                }
+
+                let client = client.clone();
+                let (timeout, retry_config) = (*timeout, *retry_config);
+                Box::pin(async move {
+                    retry_with_backoff(
+                        retry_config,
+                        timeout,
+                        crate::exporter::grpcio::classify_error,
+                        |remaining| {
+                            let receiver = client.export_async_opt(
+                                &request,
+                                call_options.clone().timeout(remaining),
+                            );
+                            async move { receiver?.await }
+                        },
+                    )
+                    .await
+                    .map_err::<crate::Error, _>(Into::into)?;
+                    Ok(())
+                })
+            }
+            #[cfg(feature = "http-proto")]
+            MetricsClient::Http {
+                client,
+                collector_endpoint,
+                headers,
+                compression,
+                timeout,
+                retry_config,
+            } => {
+                let client = Arc::clone(client);
+                let (timeout, retry_config) = (*timeout, *retry_config);
+                match http_build_request(
+                    request,
+                    headers.clone(),
+                    collector_endpoint.clone(),
+                    *compression,
+                ) {
+                    Ok(request) => Box::pin(async move {
+                        send_with_retry(client, request, retry_config, timeout).await?;
+                        Ok(())
+                    }),
+                    Err(err) => Box::pin(futures::future::ready(Err(err.into()))),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "http-proto")]
+fn http_build_request(
+    request: ExportMetricsServiceRequest,
+    headers: Option<HashMap<String, String>>,
+    collector_endpoint: Uri,
+    compression: Option<Compression>,
+) -> std::result::Result<http::Request<Vec<u8>>, crate::Error> {
+    let (buf, content_encoding) = compress_body(request.encode_to_vec(), compression)?;
+
+    let mut builder = http::Request::builder()
+        .method(Method::POST)
+        .uri(collector_endpoint)
+        .header(CONTENT_TYPE, "application/x-protobuf");
+    if let Some(content_encoding) = content_encoding {
+        builder = builder.header(CONTENT_ENCODING, content_encoding);
+    }
+
+    let mut request = builder.body(buf)?;
+
+    if let Some(headers) = headers {
+        for (k, val) in headers {
+            let value = HeaderValue::from_str(val.as_ref())?;
+            let key = HeaderName::try_from(&k)?;
+            request.headers_mut().insert(key, value);
+        }
+    }
+
+    Ok(request)
 }
 
 /// Export metrics in OTEL format.
 pub struct MetricsExporter {
-    #[cfg(feature = "tokio")]
-    sender: Mutex<tokio::sync::mpsc::Sender<ExportMsg>>,
+    sender: Mutex<mpsc::Sender<ExportMsg>>,
     temporality_selector: Box<dyn TemporalitySelector + Send + Sync>,
-    metadata: Option<tonic::metadata::MetadataMap>,
 }
 
 impl Debug for MetricsExporter {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        #[cfg(feature = "grpc-tonic")]
-        f.debug_struct("OTLP Metric Exporter")
-            .field("grpc_client", &"tonic")
-            .finish()
+        f.debug_struct("OTLP Metric Exporter").finish()
     }
 }
 
@@ -231,63 +517,220 @@ impl TemporalitySelector for MetricsExporter {
     }
 }
 
+/// The metrics endpoint, from `OTEL_EXPORTER_OTLP_METRICS_ENDPOINT` or the `/v1/metrics` path of
+/// the configured endpoint.
+fn metrics_endpoint(config: &ExportConfig) -> String {
+    match std::env::var(OTEL_EXPORTER_OTLP_METRICS_ENDPOINT) {
+        Ok(val) => val,
+        Err(_) => format!("{}{}", config.endpoint, "/v1/metrics"),
+    }
+}
+
+/// The export timeout, from `OTEL_EXPORTER_OTLP_METRICS_TIMEOUT` or the configured timeout.
+fn metrics_timeout(config: &ExportConfig) -> Duration {
+    match std::env::var(OTEL_EXPORTER_OTLP_METRICS_TIMEOUT) {
+        Ok(val) => match u64::from_str(&val) {
+            Ok(seconds) => Duration::from_secs(seconds),
+            Err(_) => config.timeout,
+        },
+        Err(_) => config.timeout,
+    }
+}
+
 impl MetricsExporter {
-    /// Create a new OTLP metrics exporter.
+    /// Create a new OTLP metrics exporter using tonic as grpc layer.
     #[cfg(feature = "grpc-tonic")]
-    pub fn new(
+    pub fn new<RT: Runtime>(
         config: ExportConfig,
-        mut tonic_config: TonicConfig,
+        tonic_config: TonicConfig,
         temporality_selector: Box<dyn TemporalitySelector + Send + Sync>,
+        export_queue_config: ExportQueueConfig,
+        rt: RT,
     ) -> Result<MetricsExporter> {
-        let endpoint = match std::env::var(OTEL_EXPORTER_OTLP_METRICS_ENDPOINT) {
-            Ok(val) => val,
-            Err(_) => format!("{}{}", config.endpoint, "/v1/metrics"),
-        };
-
-        let _timeout = match std::env::var(OTEL_EXPORTER_OTLP_METRICS_TIMEOUT) {
-            Ok(val) => match u64::from_str(&val) {
-                Ok(seconds) => Duration::from_secs(seconds),
-                Err(_) => config.timeout,
-            },
-            Err(_) => config.timeout,
-        };
-
-        let endpoint = Channel::from_shared(endpoint).map_err::<crate::Error, _>(Into::into)?;
+        let endpoint = Channel::from_shared(metrics_endpoint(&config))
+            .map_err::<crate::Error, _>(Into::into)?;
+        let timeout = metrics_timeout(&config);
 
-        #[cfg(all(feature = "tls"))]
+        #[cfg(feature = "tls")]
         let channel = match tonic_config.tls_config {
             Some(tls_config) => endpoint
                 .tls_config(tls_config)
                 .map_err::<crate::Error, _>(Into::into)?,
             None => endpoint,
         }
-        .timeout(_timeout)
+        .timeout(timeout)
         .connect_lazy();
 
         #[cfg(not(feature = "tls"))]
-        let channel = endpoint.timeout(config.timeout).connect_lazy();
+        let channel = endpoint.timeout(timeout).connect_lazy();
 
-        let client = MetricsServiceClient::new(channel);
+        let mut client = MetricsServiceClient::new(channel);
+        if let Some(compression) = resolve_compression(
+            tonic_config.compression,
+            OTEL_EXPORTER_OTLP_METRICS_COMPRESSION,
+        )? {
+            client = client.send_compressed(compression.try_into()?);
+        }
+
+        Ok(MetricsExporter::spawn(
+            MetricsClient::Tonic {
+                client,
+                metadata: tonic_config.metadata,
+                timeout,
+                retry_config: tonic_config.retry_config,
+            },
+            temporality_selector,
+            export_queue_config,
+            rt,
+        ))
+    }
+
+    /// Create a new OTLP metrics exporter using grpcio as grpc layer.
+    #[cfg(feature = "grpc-sys")]
+    pub fn new_grpcio<RT: Runtime>(
+        config: ExportConfig,
+        grpcio_config: GrpcioConfig,
+        temporality_selector: Box<dyn TemporalitySelector + Send + Sync>,
+        export_queue_config: ExportQueueConfig,
+        rt: RT,
+    ) -> Result<MetricsExporter> {
+        let mut builder: ChannelBuilder = ChannelBuilder::new(Arc::new(Environment::new(
+            grpcio_config.completion_queue_count,
+        )));
+
+        if let Some(compression) = resolve_compression(
+            grpcio_config.compression,
+            OTEL_EXPORTER_OTLP_METRICS_COMPRESSION,
+        )? {
+            builder = builder.default_compression_algorithm(compression.into());
+        }
+
+        let endpoint = config.endpoint.as_str();
+        let channel: GrpcChannel = match (grpcio_config.credentials, grpcio_config.use_tls) {
+            (None, Some(true)) => {
+                builder.secure_connect(endpoint, ChannelCredentialsBuilder::new().build())
+            }
+            (None, _) => builder.connect(endpoint),
+            (Some(credentials), _) => builder.secure_connect(
+                endpoint,
+                ChannelCredentialsBuilder::new()
+                    .cert(credentials.cert.into(), credentials.key.into())
+                    .build(),
+            ),
+        };
+
+        Ok(MetricsExporter::spawn(
+            MetricsClient::Grpcio {
+                client: GrpcioMetricsServiceClient::new(channel),
+                headers: grpcio_config.headers,
+                timeout: metrics_timeout(&config),
+                retry_config: grpcio_config.retry_config,
+            },
+            temporality_selector,
+            export_queue_config,
+            rt,
+        ))
+    }
 
-        let (sender, mut receiver) = tokio::sync::mpsc::channel::<ExportMsg>(2);
-        tokio::spawn(Box::pin(async move {
-            while let Some(msg) = receiver.recv().await {
+    /// Create a new OTLP metrics exporter sending binary protobuf over HTTP.
+    #[cfg(feature = "http-proto")]
+    pub fn new_http<RT: Runtime>(
+        config: ExportConfig,
+        http_config: HttpConfig,
+        temporality_selector: Box<dyn TemporalitySelector + Send + Sync>,
+        export_queue_config: ExportQueueConfig,
+        rt: RT,
+    ) -> Result<MetricsExporter> {
+        // A `grpc` protocol set with `OTEL_EXPORTER_OTLP_PROTOCOL` doesn't override the choice of
+        // the HTTP exporter.
+        if config.protocol == Protocol::HttpJson {
+            return Err(crate::Error::UnsupportedProtocol(config.protocol).into());
+        }
+
+        let collector_endpoint: Uri = metrics_endpoint(&config)
+            .parse()
+            .map_err::<crate::Error, _>(Into::into)?;
+        let client = http_config.client.ok_or(crate::Error::NoHttpClient)?;
+
+        let compression = resolve_compression(
+            http_config.compression,
+            OTEL_EXPORTER_OTLP_METRICS_COMPRESSION,
+        )?;
+        #[cfg(not(feature = "gzip-http"))]
+        if let Some(compression) = compression {
+            return Err(crate::Error::FeatureRequiredForCompressionAlgorithm(
+                "gzip-http",
+                compression,
+            )
+            .into());
+        }
+
+        Ok(MetricsExporter::spawn(
+            MetricsClient::Http {
+                client,
+                collector_endpoint,
+                headers: http_config.headers,
+                compression,
+                timeout: metrics_timeout(&config),
+                retry_config: http_config.retry_config,
+            },
+            temporality_selector,
+            export_queue_config,
+            rt,
+        ))
+    }
+
+    /// Send the exported metrics from a background task, so that collection isn't blocked by the
+    /// network.
+    ///
+    /// The task sends the queued metrics in order and stops once it is asked to shut down or the
+    /// exporter is dropped.
+    fn spawn<RT: Runtime>(
+        client: MetricsClient,
+        temporality_selector: Box<dyn TemporalitySelector + Send + Sync>,
+        export_queue_config: ExportQueueConfig,
+        rt: RT,
+    ) -> MetricsExporter {
+        // A bounded channel holds its buffer plus one message per sender. Exports go through the
+        // single sender held by the exporter, so the queue holds exactly `max_queue_size` of them.
+        let (sender, mut receiver) =
+            mpsc::channel::<ExportMsg>(export_queue_config.max_queue_size.saturating_sub(1));
+        rt.spawn(Box::pin(async move {
+            while let Some(msg) = receiver.next().await {
                 match msg {
-                    ExportMsg::Shutdown => {
+                    ExportMsg::Shutdown(done) => {
+                        let _ = done.send(());
                         break;
                     }
-                    ExportMsg::Export(req) => {
-                        let _ = client.to_owned().export(req).await;
+                    ExportMsg::Export(request) => {
+                        if let Err(err) = client.export(request).await {
+                            global::handle_error(err);
+                        }
                     }
                 }
             }
         }));
 
-        Ok(MetricsExporter {
+        MetricsExporter {
             sender: Mutex::new(sender),
             temporality_selector,
-            metadata: tonic_config.metadata.take(),
-        })
+        }
+    }
+
+    fn enqueue(&self, request: ExportMetricsServiceRequest) -> Result<()> {
+        let mut sender = self
+            .sender
+            .lock()
+            .map_err(|_| Error::PoisonedLock("otlp metric exporter's sender"))?;
+        sender.try_send(ExportMsg::Export(request)).map_err(|err| {
+            if err.is_disconnected() {
+                Error::ExporterShutdown
+            } else {
+                Error::ExportQueueFull
+            }
+        })?;
+
+        Ok(())
     }
 }
 
@@ -307,33 +750,191 @@ impl metrics::MetricsExporter for MetricsExporter {
                 Ok(())
             })
         })?;
-        let mut request = Request::new(sink(resource_metrics));
-        if let Some(metadata) = &self.metadata {
-            for key_and_value in metadata.iter() {
-                match key_and_value {
-                    KeyAndValueRef::Ascii(key, value) => {
-                        request.metadata_mut().append(key, value.to_owned())
-                    }
-                    KeyAndValueRef::Binary(key, value) => {
-                        request.metadata_mut().append_bin(key, value.to_owned())
-                    }
-                };
-            }
-        }
-        self.sender
+        self.enqueue(sink(resource_metrics))
+    }
+
+    fn shutdown(&self) -> BoxFuture<'static, Result<()>> {
+        let sender = self
+            .sender
             .lock()
-            .map(|sender| {
-                let _ = sender.try_send(ExportMsg::Export(request));
-            })
-            .map_err(|_| Error::PoisonedLock("otlp metric exporter's tonic sender"))?;
-        Ok(())
+            .map(|sender| sender.clone())
+            .map_err(|_| Error::PoisonedLock("otlp metric exporter's sender"));
+        Box::pin(async move {
+            let mut sender = sender?;
+            let (done, wait) = oneshot::channel();
+            // The queue is processed in order, so the background task acknowledges the shutdown
+            // once all the metrics exported before have been sent. If it is already gone, there
+            // is nothing left to wait for.
+            if sender.send(ExportMsg::Shutdown(done)).await.is_ok() {
+                let _ = wait.await;
+            }
+            Ok(())
+        })
     }
 }
 
-impl Drop for MetricsExporter {
-    fn drop(&mut self) {
-        let _sender_lock_guard = self.sender.lock().map(|sender| {
-            let _ = sender.try_send(ExportMsg::Shutdown);
-        });
+#[cfg(all(test, feature = "http-proto"))]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use futures::FutureExt;
+    use opentelemetry::metrics::MetricsError;
+    use opentelemetry::sdk::export::metrics::{
+        aggregation::cumulative_temporality_selector, MetricsExporter as _,
+    };
+    use opentelemetry_http::{Bytes, HttpError, Response};
+
+    #[derive(Debug)]
+    struct RecordingClient(mpsc::UnboundedSender<http::Request<Vec<u8>>>);
+
+    #[async_trait]
+    impl HttpClient for RecordingClient {
+        async fn send(
+            &self,
+            request: http::Request<Vec<u8>>,
+        ) -> std::result::Result<Response<Bytes>, HttpError> {
+            futures_timer::Delay::new(Duration::from_millis(10)).await;
+            let _ = self.0.unbounded_send(request);
+            Ok(Response::builder().status(200).body(Bytes::new())?)
+        }
+    }
+
+    fn http_exporter(
+        client: RecordingClient,
+        export_queue_config: ExportQueueConfig,
+    ) -> MetricsExporter {
+        MetricsExporter::new_http(
+            ExportConfig {
+                endpoint: "http://localhost:4318".into(),
+                protocol: Protocol::HttpBinary,
+                timeout: Duration::from_secs(1),
+            },
+            HttpConfig {
+                client: Some(Arc::new(client)),
+                headers: Some(HashMap::from([("x-tenant".into(), "test".into())])),
+                compression: None,
+                retry_config: RetryConfig::disabled(),
+            },
+            Box::new(cumulative_temporality_selector()),
+            export_queue_config,
+            opentelemetry::runtime::Tokio,
+        )
+        .expect("exporter should build")
+    }
+
+    /// An exporter whose queue is never consumed.
+    fn stalled_exporter() -> (MetricsExporter, mpsc::Receiver<ExportMsg>) {
+        let (sender, receiver) = mpsc::channel(0);
+        let exporter = MetricsExporter {
+            sender: Mutex::new(sender),
+            temporality_selector: Box::new(cumulative_temporality_selector()),
+        };
+        (exporter, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_http_exporter_posts_to_metrics_endpoint() {
+        let (client, mut requests) = mpsc::unbounded();
+        let exporter = http_exporter(RecordingClient(client), ExportQueueConfig::default());
+
+        exporter
+            .enqueue(ExportMetricsServiceRequest::default())
+            .unwrap();
+
+        let request = requests.next().await.expect("a request should be sent");
+        assert_eq!(request.method(), Method::POST);
+        assert_eq!(request.uri(), "http://localhost:4318/v1/metrics");
+        assert_eq!(
+            request.headers().get(CONTENT_TYPE).unwrap(),
+            "application/x-protobuf"
+        );
+        assert_eq!(request.headers().get("x-tenant").unwrap(), "test");
+    }
+
+    #[test]
+    fn test_http_exporter_rejects_json_protocol() {
+        let result = MetricsExporter::new_http(
+            ExportConfig {
+                protocol: Protocol::HttpJson,
+                ..ExportConfig::default()
+            },
+            HttpConfig::default(),
+            Box::new(cumulative_temporality_selector()),
+            ExportQueueConfig::default(),
+            opentelemetry::runtime::Tokio,
+        );
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_exporter_ignores_grpc_protocol() {
+        let (client, _requests) = mpsc::unbounded();
+        let result = MetricsExporter::new_http(
+            ExportConfig {
+                protocol: Protocol::Grpc,
+                ..ExportConfig::default()
+            },
+            HttpConfig {
+                client: Some(Arc::new(RecordingClient(client))),
+                ..HttpConfig::default()
+            },
+            Box::new(cumulative_temporality_selector()),
+            ExportQueueConfig::default(),
+            opentelemetry::runtime::Tokio,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_queued_exports() {
+        let (client, mut requests) = mpsc::unbounded();
+        let exporter = http_exporter(
+            RecordingClient(client),
+            ExportQueueConfig { max_queue_size: 4 },
+        );
+
+        for _ in 0..3 {
+            exporter
+                .enqueue(ExportMetricsServiceRequest::default())
+                .unwrap();
+        }
+        exporter.shutdown().await.unwrap();
+
+        for _ in 0..3 {
+            // already sent, no need to wait
+            assert!(requests.next().now_or_never().flatten().is_some());
+        }
+        let err = exporter
+            .enqueue(ExportMetricsServiceRequest::default())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            MetricsError::from(Error::ExporterShutdown).to_string()
+        );
+    }
+
+    #[test]
+    fn test_full_queue_drops_metrics() {
+        let (exporter, receiver) = stalled_exporter();
+
+        assert!(exporter
+            .enqueue(ExportMetricsServiceRequest::default())
+            .is_ok());
+        let err = exporter
+            .enqueue(ExportMetricsServiceRequest::default())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            MetricsError::from(Error::ExportQueueFull).to_string()
+        );
+
+        drop(receiver);
+        let err = exporter
+            .enqueue(ExportMetricsServiceRequest::default())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            MetricsError::from(Error::ExporterShutdown).to_string()
+        );
     }
 }
@@ -36,9 +36,9 @@ use {
 
 #[cfg(feature = "http-proto")]
 use {
-    crate::exporter::http::{HttpConfig, HttpExporterBuilder},
+    crate::exporter::http::{compress_body, send_with_retry, HttpConfig, HttpExporterBuilder},
     http::{
-        header::{HeaderName, HeaderValue, CONTENT_TYPE},
+        header::{HeaderName, HeaderValue, CONTENT_ENCODING, CONTENT_TYPE},
         Method, Uri,
     },
     opentelemetry_http::HttpClient,
@@ -47,11 +47,16 @@ use {
     std::convert::TryFrom,
 };
 
+#[cfg(feature = "http-proto")]
+use crate::{Compression, Protocol};
+
 #[cfg(any(feature = "grpc-sys", feature = "http-proto"))]
 use {std::collections::HashMap, std::sync::Arc};
 
-use crate::exporter::ExportConfig;
-use crate::OtlpPipeline;
+#[cfg(any(feature = "grpc-sys", feature = "grpc-tonic"))]
+use crate::exporter::retry::retry_with_backoff;
+use crate::exporter::{resolve_compression, ExportConfig};
+use crate::{OtlpPipeline, RetryConfig};
 
 use opentelemetry::{
     global,
@@ -71,6 +76,8 @@ use async_trait::async_trait;
 pub const OTEL_EXPORTER_OTLP_TRACES_ENDPOINT: &str = "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT";
 /// Max waiting time for the backend to process each spans batch, defaults to 10s.
 pub const OTEL_EXPORTER_OTLP_TRACES_TIMEOUT: &str = "OTEL_EXPORTER_OTLP_TRACES_TIMEOUT";
+/// Compression algorithm to use for spans, overrides `OTEL_EXPORTER_OTLP_COMPRESSION`.
+pub const OTEL_EXPORTER_OTLP_TRACES_COMPRESSION: &str = "OTEL_EXPORTER_OTLP_TRACES_COMPRESSION";
 
 impl OtlpPipeline {
     /// Create a OTLP tracing pipeline.
@@ -214,7 +221,7 @@ impl SpanExporterBuilder {
             SpanExporterBuilder::Grpcio(builder) => Ok(SpanExporter::new_grpcio(
                 builder.exporter_config,
                 builder.grpcio_config,
-            )),
+            )?),
             #[cfg(feature = "http-proto")]
             SpanExporterBuilder::Http(builder) => Ok(SpanExporter::new_http(
                 builder.exporter_config,
@@ -256,6 +263,8 @@ pub enum SpanExporter {
         metadata: Option<MetadataMap>,
         /// The Grpc trace exporter
         trace_exporter: TonicTraceServiceClient<TonicChannel>,
+        /// The retry policy of failed exports.
+        retry_config: RetryConfig,
     },
     #[cfg(feature = "grpc-sys")]
     /// Trace Exporter using grpcio as grpc layer
@@ -266,6 +275,8 @@ pub enum SpanExporter {
         headers: Option<HashMap<String, String>>,
         /// The Grpc trace exporter
         trace_exporter: GrpcioTraceServiceClient,
+        /// The retry policy of failed exports.
+        retry_config: RetryConfig,
     },
     #[cfg(feature = "http-proto")]
     /// Trace Exporter using HTTP transport
@@ -276,8 +287,14 @@ pub enum SpanExporter {
         headers: Option<HashMap<String, String>>,
         /// The Collector URL
         collector_endpoint: Uri,
+        /// The encoding of the request body, either binary protobuf or JSON
+        protocol: Protocol,
+        /// The compression algorithm of the request body
+        compression: Option<Compression>,
         /// The HTTP trace exporter
         trace_exporter: Option<Arc<dyn HttpClient>>,
+        /// The retry policy of failed exports.
+        retry_config: RetryConfig,
     },
 }
 
@@ -304,11 +321,15 @@ impl Debug for SpanExporter {
                 .finish(),
             #[cfg(feature = "http-proto")]
             SpanExporter::Http {
-                headers, timeout, ..
+                headers,
+                timeout,
+                protocol,
+                ..
             } => f
                 .debug_struct("Exporter")
                 .field("headers", &headers)
                 .field("timeout", &timeout)
+                .field("protocol", &protocol)
                 .field("trace_exporter", &"TraceServiceClient")
                 .finish(),
         }
@@ -362,21 +383,36 @@ impl SpanExporter {
         tonic_config: TonicConfig,
         channel: tonic::transport::Channel,
     ) -> Result<Self, crate::Error> {
+        let mut trace_exporter = TonicTraceServiceClient::new(channel);
+        if let Some(compression) = resolve_compression(
+            tonic_config.compression,
+            OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+        )? {
+            trace_exporter = trace_exporter.send_compressed(compression.try_into()?);
+        }
+
         Ok(SpanExporter::Tonic {
             timeout: config.timeout,
             metadata: tonic_config.metadata,
-            trace_exporter: TonicTraceServiceClient::new(channel),
+            trace_exporter,
+            retry_config: tonic_config.retry_config,
         })
     }
 
     /// Builds a new span exporter with the given configuration
     #[cfg(feature = "grpc-sys")]
-    pub fn new_grpcio(config: ExportConfig, grpcio_config: GrpcioConfig) -> Self {
+    pub fn new_grpcio(
+        config: ExportConfig,
+        grpcio_config: GrpcioConfig,
+    ) -> Result<Self, crate::Error> {
         let mut builder: ChannelBuilder = ChannelBuilder::new(Arc::new(Environment::new(
             grpcio_config.completion_queue_count,
         )));
 
-        if let Some(compression) = grpcio_config.compression {
+        if let Some(compression) = resolve_compression(
+            grpcio_config.compression,
+            OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+        )? {
             builder = builder.default_compression_algorithm(compression.into());
         }
 
@@ -394,11 +430,12 @@ impl SpanExporter {
             ),
         };
 
-        SpanExporter::Grpcio {
+        Ok(SpanExporter::Grpcio {
             trace_exporter: GrpcioTraceServiceClient::new(channel),
             timeout: config.timeout,
             headers: grpcio_config.headers,
-        }
+            retry_config: grpcio_config.retry_config,
+        })
     }
 
     /// Builds a new span exporter with the given configuration
@@ -409,11 +446,36 @@ impl SpanExporter {
             .parse()
             .map_err::<crate::Error, _>(Into::into)?;
 
+        // Picking the HTTP exporter takes precedence over a `grpc` protocol set with
+        // `OTEL_EXPORTER_OTLP_PROTOCOL`, binary protobuf is used instead.
+        let protocol = match config.protocol {
+            Protocol::Grpc | Protocol::HttpBinary => Protocol::HttpBinary,
+            #[cfg(feature = "http-json")]
+            Protocol::HttpJson => Protocol::HttpJson,
+            #[cfg(not(feature = "http-json"))]
+            Protocol::HttpJson => return Err(crate::Error::UnsupportedProtocol(config.protocol)),
+        };
+
+        let compression = resolve_compression(
+            http_config.compression,
+            OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+        )?;
+        #[cfg(not(feature = "gzip-http"))]
+        if let Some(compression) = compression {
+            return Err(crate::Error::FeatureRequiredForCompressionAlgorithm(
+                "gzip-http",
+                compression,
+            ));
+        }
+
         Ok(SpanExporter::Http {
             trace_exporter: http_config.client,
             timeout: config.timeout,
             collector_endpoint: url,
+            protocol,
+            compression,
             headers: http_config.headers,
+            retry_config: http_config.retry_config,
         })
     }
 }
@@ -423,51 +485,101 @@ async fn grpcio_send_request(
     trace_exporter: GrpcioTraceServiceClient,
     request: GrpcRequest,
     call_options: CallOption,
+    timeout: Duration,
+    retry_config: RetryConfig,
 ) -> ExportResult {
-    let receiver = trace_exporter
-        .export_async_opt(&request, call_options)
-        .map_err::<crate::Error, _>(Into::into)?;
-    receiver.await.map_err::<crate::Error, _>(Into::into)?;
+    retry_with_backoff(
+        retry_config,
+        timeout,
+        crate::exporter::grpcio::classify_error,
+        |remaining| {
+            let receiver =
+                trace_exporter.export_async_opt(&request, call_options.clone().timeout(remaining));
+            async move { receiver?.await }
+        },
+    )
+    .await
+    .map_err::<crate::Error, _>(Into::into)?;
     Ok(())
 }
 
 #[cfg(feature = "tonic")]
 async fn tonic_send_request(
     trace_exporter: TonicTraceServiceClient<TonicChannel>,
-    request: Request<TonicRequest>,
+    request: TonicRequest,
+    metadata: Option<MetadataMap>,
+    timeout: Duration,
+    retry_config: RetryConfig,
 ) -> ExportResult {
-    trace_exporter
-        .to_owned()
-        .export(request)
-        .await
-        .map_err::<crate::Error, _>(Into::into)?;
+    retry_with_backoff(
+        retry_config,
+        timeout,
+        crate::exporter::tonic::classify_status,
+        |remaining| {
+            let mut request = Request::new(request.clone());
+            request.set_timeout(remaining);
+
+            if let Some(metadata) = &metadata {
+                for key_and_value in metadata.iter() {
+                    match key_and_value {
+                        KeyAndValueRef::Ascii(key, value) => {
+                            request.metadata_mut().append(key, value.to_owned())
+                        }
+                        KeyAndValueRef::Binary(key, value) => {
+                            request.metadata_mut().append_bin(key, value.to_owned())
+                        }
+                    };
+                }
+            }
+
+            let mut trace_exporter = trace_exporter.to_owned();
+            async move { trace_exporter.export(request).await }
+        },
+    )
+    .await
+    .map_err::<crate::Error, _>(Into::into)?;
 
     Ok(())
 }
 
 #[cfg(feature = "http-proto")]
-async fn http_send_request(
+fn http_build_request(
     batch: Vec<SpanData>,
-    client: std::sync::Arc<dyn HttpClient>,
     headers: Option<HashMap<String, String>>,
     collector_endpoint: Uri,
-) -> ExportResult {
-    use opentelemetry_http::ResponseExt;
-
+    protocol: Protocol,
+    compression: Option<Compression>,
+) -> Result<http::Request<Vec<u8>>, crate::Error> {
     let req = ProstRequest {
         resource_spans: batch.into_iter().map(Into::into).collect(),
     };
 
-    let mut buf = vec![];
-    req.encode(&mut buf)
-        .map_err::<crate::Error, _>(Into::into)?;
+    let (buf, content_type) = match protocol {
+        #[cfg(feature = "http-json")]
+        Protocol::HttpJson => {
+            let json = crate::transform::json::trace_request_to_json(&req);
+            let buf = serde_json::to_vec(&json).map_err::<crate::Error, _>(Into::into)?;
+            (buf, "application/json")
+        }
+        _ => {
+            let mut buf = vec![];
+            req.encode(&mut buf)
+                .map_err::<crate::Error, _>(Into::into)?;
+            (buf, "application/x-protobuf")
+        }
+    };
+
+    let (buf, content_encoding) = compress_body(buf, compression)?;
 
-    let mut request = http::Request::builder()
+    let mut builder = http::Request::builder()
         .method(Method::POST)
         .uri(collector_endpoint)
-        .header(CONTENT_TYPE, "application/x-protobuf")
-        .body(buf)
-        .map_err::<crate::Error, _>(Into::into)?;
+        .header(CONTENT_TYPE, content_type);
+    if let Some(content_encoding) = content_encoding {
+        builder = builder.header(CONTENT_ENCODING, content_encoding);
+    }
+
+    let mut request = builder.body(buf).map_err::<crate::Error, _>(Into::into)?;
 
     if let Some(headers) = headers {
         for (k, val) in headers {
@@ -478,8 +590,7 @@ async fn http_send_request(
         }
     }
 
-    client.send(request).await?.error_for_status()?;
-    Ok(())
+    Ok(request)
 }
 
 #[async_trait]
@@ -494,6 +605,7 @@ impl opentelemetry::sdk::export::trace::SpanExporter for SpanExporter {
                 timeout,
                 headers,
                 trace_exporter,
+                retry_config,
             } => {
                 let request = GrpcRequest {
                     resource_spans: protobuf::RepeatedField::from_vec(
@@ -503,7 +615,7 @@ impl opentelemetry::sdk::export::trace::SpanExporter for SpanExporter {
                     cached_size: Default::default(),
                 };
 
-                let mut call_options = CallOption::default().timeout(*timeout);
+                let mut call_options = CallOption::default();
 
                 if let Some(headers) = headers.clone() {
                     let mut metadata_builder: MetadataBuilder = MetadataBuilder::new();
@@ -519,50 +631,58 @@ impl opentelemetry::sdk::export::trace::SpanExporter for SpanExporter {
                     trace_exporter.clone(),
                     request,
                     call_options,
+                    *timeout,
+                    *retry_config,
                 ))
             }
 
             #[cfg(feature = "grpc-tonic")]
             SpanExporter::Tonic {
+                timeout,
                 trace_exporter,
                 metadata,
-                ..
+                retry_config,
             } => {
-                let mut request = Request::new(TonicRequest {
+                let request = TonicRequest {
                     resource_spans: batch.into_iter().map(Into::into).collect(),
-                });
-
-                if let Some(metadata) = metadata {
-                    for key_and_value in metadata.iter() {
-                        match key_and_value {
-                            KeyAndValueRef::Ascii(key, value) => {
-                                request.metadata_mut().append(key, value.to_owned())
-                            }
-                            KeyAndValueRef::Binary(key, value) => {
-                                request.metadata_mut().append_bin(key, value.to_owned())
-                            }
-                        };
-                    }
-                }
+                };
 
-                Box::pin(tonic_send_request(trace_exporter.to_owned(), request))
+                Box::pin(tonic_send_request(
+                    trace_exporter.to_owned(),
+                    request,
+                    metadata.clone(),
+                    *timeout,
+                    *retry_config,
+                ))
             }
 
             #[cfg(feature = "http-proto")]
             SpanExporter::Http {
+                timeout,
                 trace_exporter,
                 collector_endpoint,
                 headers,
-                ..
+                protocol,
+                compression,
+                retry_config,
             } => {
                 if let Some(ref client) = trace_exporter {
                     let client = Arc::clone(client);
-                    Box::pin(http_send_request(
+                    let (timeout, retry_config) = (*timeout, *retry_config);
+                    match http_build_request(
                         batch,
-                        client,
                         headers.clone(),
                         collector_endpoint.clone(),
-                    ))
+                        *protocol,
+                        *compression,
+                    ) {
+                        Ok(request) => Box::pin(async move {
+                            send_with_retry(client, request, retry_config, timeout)
+                                .await
+                                .map_err(Into::into)
+                        }),
+                        Err(err) => Box::pin(std::future::ready(Err(err.into()))),
+                    }
                 } else {
                     Box::pin(std::future::ready(Err(crate::Error::NoHttpClient.into())))
                 }
@@ -570,3 +690,27 @@ impl opentelemetry::sdk::export::trace::SpanExporter for SpanExporter {
         }
     }
 }
+
+#[cfg(all(test, feature = "http-proto"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_exporter_ignores_grpc_protocol() {
+        let exporter = SpanExporter::new_http(
+            ExportConfig {
+                protocol: Protocol::Grpc,
+                ..ExportConfig::default()
+            },
+            HttpConfig::default(),
+        )
+        .expect("the HTTP exporter should build");
+        assert!(matches!(
+            exporter,
+            SpanExporter::Http {
+                protocol: Protocol::HttpBinary,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+//! OTLP/JSON encoding of the generated OTLP protobuf types.
+//!
+//! The generated prost types don't implement `serde`, so the requests are
+//! converted by hand following the [OTLP/JSON] rules, which build on the
+//! [proto3 JSON mapping] with a few differences:
+//!
+//! - field names are lowerCamelCase,
+//! - trace and span ids are hex encoded rather than base64 encoded,
+//! - enum values are encoded as integers,
+//! - 64 bit integers are encoded as decimal strings.
+//!
+//! [OTLP/JSON]: https://github.com/open-telemetry/opentelemetry-specification/blob/v1.13.0/specification/protocol/otlp.md#json-protobuf-encoding
+//! [proto3 JSON mapping]: https://developers.google.com/protocol-buffers/docs/proto3#json
+use opentelemetry_proto::tonic::{
+    common::v1::{any_value, AnyValue, InstrumentationLibrary, KeyValue},
+    resource::v1::Resource,
+};
+use serde_json::{json, Map, Value};
+
+#[cfg(feature = "trace")]
+use opentelemetry_proto::tonic::{
+    collector::trace::v1::ExportTraceServiceRequest,
+    trace::v1::{span, InstrumentationLibrarySpans, ResourceSpans, Span, Status},
+};
+
+/// Encode an `ExportTraceServiceRequest` as an OTLP/JSON document.
+#[cfg(feature = "trace")]
+pub(crate) fn trace_request_to_json(request: &ExportTraceServiceRequest) -> Value {
+    json!({
+        "resourceSpans": request.resource_spans.iter().map(resource_spans).collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(feature = "trace")]
+fn resource_spans(resource_spans: &ResourceSpans) -> Value {
+    let mut value = Map::new();
+    if let Some(res) = resource_spans.resource.as_ref() {
+        value.insert("resource".into(), resource(res));
+    }
+    value.insert(
+        "instrumentationLibrarySpans".into(),
+        resource_spans
+            .instrumentation_library_spans
+            .iter()
+            .map(instrumentation_library_spans)
+            .collect(),
+    );
+    value.insert("schemaUrl".into(), resource_spans.schema_url.clone().into());
+    Value::Object(value)
+}
+
+#[cfg(feature = "trace")]
+fn instrumentation_library_spans(library_spans: &InstrumentationLibrarySpans) -> Value {
+    let mut value = Map::new();
+    if let Some(library) = library_spans.instrumentation_library.as_ref() {
+        value.insert(
+            "instrumentationLibrary".into(),
+            instrumentation_library(library),
+        );
+    }
+    value.insert(
+        "spans".into(),
+        library_spans.spans.iter().map(span).collect(),
+    );
+    value.insert("schemaUrl".into(), library_spans.schema_url.clone().into());
+    Value::Object(value)
+}
+
+#[cfg(feature = "trace")]
+fn span(span: &Span) -> Value {
+    let mut value = json!({
+        "traceId": hex(&span.trace_id),
+        "spanId": hex(&span.span_id),
+        "traceState": span.trace_state,
+        "parentSpanId": hex(&span.parent_span_id),
+        "name": span.name,
+        "kind": span.kind,
+        "startTimeUnixNano": span.start_time_unix_nano.to_string(),
+        "endTimeUnixNano": span.end_time_unix_nano.to_string(),
+        "attributes": key_values(&span.attributes),
+        "droppedAttributesCount": span.dropped_attributes_count,
+        "events": span.events.iter().map(event).collect::<Vec<_>>(),
+        "droppedEventsCount": span.dropped_events_count,
+        "links": span.links.iter().map(link).collect::<Vec<_>>(),
+        "droppedLinksCount": span.dropped_links_count,
+    });
+    if let Some(status) = span.status.as_ref() {
+        value["status"] = self::status(status);
+    }
+    value
+}
+
+#[cfg(feature = "trace")]
+fn event(event: &span::Event) -> Value {
+    json!({
+        "timeUnixNano": event.time_unix_nano.to_string(),
+        "name": event.name,
+        "attributes": key_values(&event.attributes),
+        "droppedAttributesCount": event.dropped_attributes_count,
+    })
+}
+
+#[cfg(feature = "trace")]
+fn link(link: &span::Link) -> Value {
+    json!({
+        "traceId": hex(&link.trace_id),
+        "spanId": hex(&link.span_id),
+        "traceState": link.trace_state,
+        "attributes": key_values(&link.attributes),
+        "droppedAttributesCount": link.dropped_attributes_count,
+    })
+}
+
+#[cfg(feature = "trace")]
+fn status(status: &Status) -> Value {
+    json!({
+        "message": status.message,
+        "code": status.code,
+    })
+}
+
+pub(crate) fn resource(resource: &Resource) -> Value {
+    json!({
+        "attributes": key_values(&resource.attributes),
+        "droppedAttributesCount": resource.dropped_attributes_count,
+    })
+}
+
+pub(crate) fn instrumentation_library(library: &InstrumentationLibrary) -> Value {
+    json!({
+        "name": library.name,
+        "version": library.version,
+    })
+}
+
+pub(crate) fn key_values(key_values: &[KeyValue]) -> Value {
+    key_values.iter().map(key_value).collect()
+}
+
+fn key_value(key_value: &KeyValue) -> Value {
+    let mut value = Map::new();
+    value.insert("key".into(), key_value.key.clone().into());
+    if let Some(any) = key_value.value.as_ref() {
+        value.insert("value".into(), any_value(any));
+    }
+    Value::Object(value)
+}
+
+pub(crate) fn any_value(value: &AnyValue) -> Value {
+    match value.value.as_ref() {
+        Some(any_value::Value::StringValue(val)) => json!({ "stringValue": val }),
+        Some(any_value::Value::BoolValue(val)) => json!({ "boolValue": val }),
+        Some(any_value::Value::IntValue(val)) => json!({ "intValue": val.to_string() }),
+        Some(any_value::Value::DoubleValue(val)) => json!({ "doubleValue": double(*val) }),
+        Some(any_value::Value::ArrayValue(array)) => json!({
+            "arrayValue": {
+                "values": array.values.iter().map(any_value).collect::<Vec<_>>(),
+            }
+        }),
+        Some(any_value::Value::KvlistValue(list)) => json!({
+            "kvlistValue": {
+                "values": key_values(&list.values),
+            }
+        }),
+        Some(any_value::Value::BytesValue(bytes)) => json!({ "bytesValue": base64::encode(bytes) }),
+        None => json!({}),
+    }
+}
+
+/// Doubles that are not finite are encoded as strings by the proto3 JSON mapping.
+pub(crate) fn double(val: f64) -> Value {
+    if val.is_nan() {
+        "NaN".into()
+    } else if val.is_infinite() {
+        if val.is_sign_positive() {
+            "Infinity".into()
+        } else {
+            "-Infinity".into()
+        }
+    } else {
+        val.into()
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_proto::tonic::common::v1::ArrayValue;
+
+    #[test]
+    fn test_bytes_value() {
+        let value = AnyValue {
+            value: Some(any_value::Value::BytesValue(b"foobar".to_vec())),
+        };
+
+        assert_eq!(any_value(&value), json!({ "bytesValue": "Zm9vYmFy" }));
+    }
+
+    #[test]
+    fn test_any_value() {
+        let value = AnyValue {
+            value: Some(any_value::Value::ArrayValue(ArrayValue {
+                values: vec![
+                    AnyValue {
+                        value: Some(any_value::Value::IntValue(i64::MAX)),
+                    },
+                    AnyValue {
+                        value: Some(any_value::Value::DoubleValue(f64::NAN)),
+                    },
+                ],
+            })),
+        };
+
+        assert_eq!(
+            any_value(&value),
+            json!({
+                "arrayValue": {
+                    "values": [
+                        { "intValue": "9223372036854775807" },
+                        { "doubleValue": "NaN" },
+                    ]
+                }
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "trace")]
+    fn test_trace_request_to_json() {
+        let request = ExportTraceServiceRequest {
+            resource_spans: vec![ResourceSpans {
+                resource: None,
+                instrumentation_library_spans: vec![InstrumentationLibrarySpans {
+                    instrumentation_library: Some(InstrumentationLibrary {
+                        name: "lib".into(),
+                        version: "1.0".into(),
+                    }),
+                    spans: vec![Span {
+                        trace_id: vec![0; 15].into_iter().chain(Some(1)).collect(),
+                        span_id: vec![0, 0, 0, 0, 0, 0, 0, 0xab],
+                        trace_state: "".into(),
+                        parent_span_id: vec![],
+                        name: "span".into(),
+                        kind: span::SpanKind::Client as i32,
+                        start_time_unix_nano: 1_000_000_000_000_000_000,
+                        end_time_unix_nano: 1_000_000_000_000_000_001,
+                        attributes: vec![KeyValue {
+                            key: "key".into(),
+                            value: Some(AnyValue {
+                                value: Some(any_value::Value::StringValue("value".into())),
+                            }),
+                        }],
+                        dropped_attributes_count: 0,
+                        events: vec![],
+                        dropped_events_count: 0,
+                        links: vec![],
+                        dropped_links_count: 0,
+                        status: Some(Status {
+                            message: "".into(),
+                            code: 2,
+                        }),
+                    }],
+                    schema_url: "".into(),
+                }],
+                schema_url: "".into(),
+            }],
+        };
+
+        assert_eq!(
+            trace_request_to_json(&request),
+            json!({
+                "resourceSpans": [{
+                    "instrumentationLibrarySpans": [{
+                        "instrumentationLibrary": { "name": "lib", "version": "1.0" },
+                        "spans": [{
+                            "traceId": "00000000000000000000000000000001",
+                            "spanId": "00000000000000ab",
+                            "traceState": "",
+                            "parentSpanId": "",
+                            "name": "span",
+                            "kind": 3,
+                            "startTimeUnixNano": "1000000000000000000",
+                            "endTimeUnixNano": "1000000000000000001",
+                            "attributes": [{ "key": "key", "value": { "stringValue": "value" } }],
+                            "droppedAttributesCount": 0,
+                            "events": [],
+                            "droppedEventsCount": 0,
+                            "links": [],
+                            "droppedLinksCount": 0,
+                            "status": { "message": "", "code": 2 },
+                        }],
+                        "schemaUrl": "",
+                    }],
+                    "schemaUrl": "",
+                }]
+            })
+        );
+    }
+}
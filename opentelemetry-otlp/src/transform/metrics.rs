@@ -1,4 +1,3 @@
-#[cfg(feature = "grpc-tonic")]
 // The prost currently will generate a non optional deprecated field for labels.
 // We cannot assign value to it otherwise clippy will complain.
 // We cannot ignore it as it's not an optional field.
@@ -8,13 +7,16 @@ pub(crate) mod tonic {
     use opentelemetry::metrics::MetricsError;
     use opentelemetry::sdk::export::metrics::{
         aggregation::{
-            Count, Histogram as SdkHistogram, LastValue, Sum as SdkSum, TemporalitySelector,
+            Count, ExponentialBuckets, ExponentialHistogram as SdkExponentialHistogram,
+            Histogram as SdkHistogram, LastValue, Sum as SdkSum, TemporalitySelector,
         },
         Record,
     };
     use opentelemetry::sdk::metrics::aggregators::{
-        HistogramAggregator, LastValueAggregator, SumAggregator,
+        ExponentialHistogramAggregator, HistogramAggregator, LastValueAggregator, SumAggregator,
     };
+    use opentelemetry::sdk::metrics::exemplar::Exemplar as SdkExemplar;
+    use opentelemetry::sdk::metrics::sdk_api::NumberKind;
     use opentelemetry::sdk::InstrumentationLibrary;
     use opentelemetry_proto::tonic::metrics::v1::DataPointFlags;
     use opentelemetry_proto::tonic::FromNumber;
@@ -22,9 +24,10 @@ pub(crate) mod tonic {
         collector::metrics::v1::ExportMetricsServiceRequest,
         common::v1::KeyValue,
         metrics::v1::{
-            metric::Data, number_data_point, AggregationTemporality, Gauge, Histogram,
-            HistogramDataPoint, InstrumentationLibraryMetrics, Metric, NumberDataPoint,
-            ResourceMetrics, Sum,
+            exemplar, exponential_histogram_data_point, metric::Data, number_data_point,
+            AggregationTemporality, Exemplar, ExponentialHistogram, ExponentialHistogramDataPoint,
+            Gauge, Histogram, HistogramDataPoint, InstrumentationLibraryMetrics, Metric,
+            NumberDataPoint, ResourceMetrics, Sum,
         },
     };
 
@@ -47,6 +50,11 @@ pub(crate) mod tonic {
             .temporality_for(descriptor, aggregator.aggregation().kind())
             .into();
         let kind = descriptor.number_kind();
+        let exemplars = record
+            .exemplars()
+            .iter()
+            .map(|exemplar| to_exemplar(exemplar, kind))
+            .collect::<Vec<Exemplar>>();
         Ok(Metric {
             name: descriptor.name().to_string(),
             description: descriptor.description().cloned().unwrap_or_default(),
@@ -63,7 +71,7 @@ pub(crate) mod tonic {
                                 start_time_unix_nano: to_nanos(*record.start_time()),
                                 time_unix_nano: to_nanos(sample_time),
                                 value: Some(number_data_point::Value::from_number(val, kind)),
-                                exemplars: Vec::default(),
+                                exemplars,
                             }],
                         })
                     })
@@ -77,7 +85,7 @@ pub(crate) mod tonic {
                                 start_time_unix_nano: to_nanos(*record.start_time()),
                                 time_unix_nano: to_nanos(*record.end_time()),
                                 value: Some(number_data_point::Value::from_number(val, kind)),
-                                exemplars: Vec::default(),
+                                exemplars,
                             }],
                             aggregation_temporality: temporality as i32,
                             is_monotonic: descriptor.instrument_kind().monotonic(),
@@ -104,7 +112,30 @@ pub(crate) mod tonic {
                                     .map(|c| c as u64)
                                     .collect(),
                                 explicit_bounds: buckets.boundaries().clone(),
-                                exemplars: Vec::default(),
+                                exemplars,
+                            }],
+                            aggregation_temporality: temporality as i32,
+                        })
+                    })
+                } else if let Some(histogram) = aggregator
+                    .as_any()
+                    .downcast_ref::<ExponentialHistogramAggregator>()
+                {
+                    Some({
+                        let (sum, count) = (histogram.sum()?, histogram.count()?);
+                        Data::ExponentialHistogram(ExponentialHistogram {
+                            data_points: vec![ExponentialHistogramDataPoint {
+                                flags: DataPointFlags::FlagNone as u32,
+                                attributes,
+                                start_time_unix_nano: to_nanos(*record.start_time()),
+                                time_unix_nano: to_nanos(*record.end_time()),
+                                count,
+                                sum: sum.to_f64(kind),
+                                scale: histogram.scale()?.into(),
+                                zero_count: histogram.zero_count()?,
+                                positive: Some(exponential_buckets(histogram.positive()?)),
+                                negative: Some(exponential_buckets(histogram.negative()?)),
+                                exemplars,
                             }],
                             aggregation_temporality: temporality as i32,
                         })
@@ -116,6 +147,35 @@ pub(crate) mod tonic {
         })
     }
 
+    fn to_exemplar(exemplar: &SdkExemplar, kind: &NumberKind) -> Exemplar {
+        Exemplar {
+            filtered_attributes: exemplar
+                .filtered_attributes()
+                .iter()
+                .map(|kv| (&kv.key, &kv.value).into())
+                .collect(),
+            time_unix_nano: to_nanos(*exemplar.time()),
+            span_id: exemplar
+                .span_id()
+                .map(|id| id.to_bytes().to_vec())
+                .unwrap_or_default(),
+            trace_id: exemplar
+                .trace_id()
+                .map(|id| id.to_bytes().to_vec())
+                .unwrap_or_default(),
+            value: Some(exemplar::Value::from_number(exemplar.value().clone(), kind)),
+        }
+    }
+
+    fn exponential_buckets(
+        buckets: ExponentialBuckets,
+    ) -> exponential_histogram_data_point::Buckets {
+        exponential_histogram_data_point::Buckets {
+            offset: buckets.offset(),
+            bucket_counts: buckets.counts().clone(),
+        }
+    }
+
     // Group metrics with resources and instrumentation libraries with resources first,
     // then instrumentation libraries.
     #[allow(clippy::map_entry)] // caused by https://github.com/rust-lang/rust-clippy/issues/4674
@@ -224,6 +284,7 @@ pub(crate) mod tonic {
             Data::Sum => Data::Sum;
             Data::Gauge => Data::Sum, Data::Gauge;
             Data::Histogram => Data::Histogram;
+            Data::ExponentialHistogram => Data::ExponentialHistogram;
             Data::Summary => Data::Summary
         );
     }
@@ -232,7 +293,6 @@ pub(crate) mod tonic {
 #[cfg(test)]
 #[allow(deprecated)]
 mod tests {
-    #[cfg(feature = "grpc-tonic")]
     mod tonic {
         use crate::transform::metrics::tonic::merge;
         use crate::transform::{record_to_metric, sink, ResourceWrapper};
@@ -241,7 +301,7 @@ mod tests {
         use opentelemetry::sdk::export::metrics::aggregation::cumulative_temporality_selector;
         use opentelemetry::sdk::export::metrics::record;
         use opentelemetry::sdk::metrics::aggregators::{
-            histogram, last_value, Aggregator, SumAggregator,
+            exponential_histogram, histogram, last_value, Aggregator, SumAggregator,
         };
         use opentelemetry::sdk::metrics::sdk_api::{
             Descriptor, InstrumentKind, Number, NumberKind,
@@ -252,8 +312,10 @@ mod tests {
         use opentelemetry_proto::tonic::{
             common::v1::{any_value, AnyValue, KeyValue},
             metrics::v1::{
-                metric::Data, number_data_point, Gauge, Histogram, HistogramDataPoint,
-                InstrumentationLibraryMetrics, Metric, NumberDataPoint, ResourceMetrics, Sum,
+                exponential_histogram_data_point, metric::Data, number_data_point,
+                ExponentialHistogram, ExponentialHistogramDataPoint, Gauge, Histogram,
+                HistogramDataPoint, InstrumentationLibraryMetrics, Metric, NumberDataPoint,
+                ResourceMetrics, Sum,
             },
             Attributes, FromNumber,
         };
@@ -569,7 +631,7 @@ mod tests {
                     data: Some(Data::Histogram(Histogram {
                         data_points: vec![HistogramDataPoint {
                             flags: DataPointFlags::FlagNone as u32,
-                            attributes: str_kv_attributes,
+                            attributes: str_kv_attributes.clone(),
                             start_time_unix_nano: 1608891000000000000,
                             time_unix_nano: 1608891030000000000,
                             count: 3,
@@ -585,6 +647,121 @@ mod tests {
                 assert_eq!(expect, metric);
             }
 
+            // Exponential Histogram
+            {
+                let descriptor = Descriptor::new(
+                    "test".to_string(),
+                    InstrumentKind::Histogram,
+                    NumberKind::I64,
+                    None,
+                    None,
+                );
+                let aggregator = exponential_histogram(160, 0);
+                let vals = [0i64.into(), 2i64.into(), 3i64.into(), (-4i64).into()];
+                for val in vals.iter() {
+                    aggregator.update(&cx, val, &descriptor)?;
+                }
+                let wrapped_aggregator: Arc<dyn Aggregator + Send + Sync> = Arc::new(aggregator);
+                let record = record(
+                    &descriptor,
+                    &attribute_set,
+                    Some(&wrapped_aggregator),
+                    start_time.into(),
+                    end_time.into(),
+                );
+                let metric = record_to_metric(&record, &cumulative_temporality_selector())?;
+
+                let expect = Metric {
+                    name: "test".to_string(),
+                    description: "".to_string(),
+                    unit: "".to_string(),
+                    data: Some(Data::ExponentialHistogram(ExponentialHistogram {
+                        data_points: vec![ExponentialHistogramDataPoint {
+                            flags: DataPointFlags::FlagNone as u32,
+                            attributes: str_kv_attributes,
+                            start_time_unix_nano: 1608891000000000000,
+                            time_unix_nano: 1608891030000000000,
+                            count: 4,
+                            sum: 1f64,
+                            scale: 0,
+                            zero_count: 1,
+                            positive: Some(exponential_histogram_data_point::Buckets {
+                                offset: 0,
+                                bucket_counts: vec![1, 1],
+                            }),
+                            negative: Some(exponential_histogram_data_point::Buckets {
+                                offset: 1,
+                                bucket_counts: vec![1],
+                            }),
+                            exemplars: vec![],
+                        }],
+                        aggregation_temporality: 2,
+                    })),
+                };
+
+                assert_eq!(expect, metric);
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_record_to_metric_with_exemplars() -> Result<(), MetricsError> {
+            use opentelemetry::sdk::metrics::exemplar::Exemplar as SdkExemplar;
+            use opentelemetry::trace::{
+                SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+            };
+            use opentelemetry_proto::tonic::metrics::v1::{exemplar, Exemplar};
+
+            let cx = Context::new().with_remote_span_context(SpanContext::new(
+                TraceId::from_u128(1),
+                SpanId::from_u64(2),
+                TraceFlags::SAMPLED,
+                true,
+                TraceState::default(),
+            ));
+            let descriptor = Descriptor::new(
+                "test".to_string(),
+                InstrumentKind::Counter,
+                NumberKind::I64,
+                None,
+                None,
+            );
+            let aggregator = SumAggregator::default();
+            aggregator.update(&cx, &Number::from(12_i64), &descriptor)?;
+            let wrapped_aggregator: Arc<dyn Aggregator + Send + Sync> = Arc::new(aggregator);
+            let exemplars = [SdkExemplar::new(
+                &cx,
+                Number::from(12_i64),
+                datetime!(2020-12-25 10:10:10 UTC).into(),
+                vec![opentelemetry::KeyValue::new("user", "alice")],
+            )];
+            let attribute_set = AttributeSet::default();
+            let record = record(
+                &descriptor,
+                &attribute_set,
+                Some(&wrapped_aggregator),
+                datetime!(2020-12-25 10:10:0 UTC).into(),
+                datetime!(2020-12-25 10:10:30 UTC).into(),
+            )
+            .with_exemplars(&exemplars);
+            let metric = record_to_metric(&record, &cumulative_temporality_selector())?;
+
+            let data_point = match metric.data {
+                Some(Data::Sum(sum)) => sum.data_points.into_iter().next().unwrap(),
+                other => panic!("expected a sum, got {:?}", other),
+            };
+            assert_eq!(
+                data_point.exemplars,
+                vec![Exemplar {
+                    filtered_attributes: vec![key_value("user", "alice")],
+                    time_unix_nano: 1608891010000000000,
+                    span_id: vec![0, 0, 0, 0, 0, 0, 0, 2],
+                    trace_id: vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+                    value: Some(exemplar::Value::AsInt(12)),
+                }]
+            );
+
             Ok(())
         }
 
@@ -1,20 +1,22 @@
+#[cfg(feature = "http-json")]
+pub(crate) mod json;
 #[cfg(feature = "metrics")]
 mod metrics;
 mod resource;
 
-#[cfg(all(feature = "grpc-tonic", feature = "metrics"))]
+#[cfg(feature = "metrics")]
 pub(crate) use metrics::tonic::record_to_metric;
-#[cfg(all(feature = "grpc-tonic", feature = "metrics"))]
+#[cfg(feature = "metrics")]
 pub(crate) use metrics::tonic::sink;
-#[cfg(all(feature = "grpc-tonic", feature = "metrics"))]
+#[cfg(feature = "metrics")]
 pub(crate) use resource::ResourceWrapper;
 
-#[cfg(all(feature = "grpc-tonic", feature = "metrics"))]
+#[cfg(feature = "metrics")]
 use opentelemetry::sdk::InstrumentationLibrary;
 
 // Metrics in OTEL proto format checked from checkpoint with information of resource and instrumentation
 // library.
-#[cfg(all(feature = "grpc-tonic", feature = "metrics"))]
+#[cfg(feature = "metrics")]
 pub(crate) type CheckpointedMetrics = (
     ResourceWrapper,
     InstrumentationLibrary,
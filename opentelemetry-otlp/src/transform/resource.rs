@@ -1,4 +1,4 @@
-#[cfg(feature = "grpc-tonic")]
+#[cfg(any(feature = "grpc-tonic", feature = "metrics"))]
 use opentelemetry_proto::tonic::{common::v1::KeyValue, resource::v1::Resource};
 use std::cmp::Ordering;
 
@@ -26,14 +26,14 @@ impl PartialOrd for ResourceWrapper {
 }
 
 impl ResourceWrapper {
-    #[cfg(all(feature = "grpc-tonic", feature = "metrics"))]
+    #[cfg(feature = "metrics")]
     // it's currently only used by metrics. Trace set this in opentelemtry-proto
     pub(crate) fn schema_url(&self) -> Option<&str> {
         self.0.schema_url()
     }
 }
 
-#[cfg(feature = "grpc-tonic")]
+#[cfg(any(feature = "grpc-tonic", feature = "metrics"))]
 impl From<ResourceWrapper> for Resource {
     fn from(resource: ResourceWrapper) -> Self {
         Resource {
@@ -105,8 +105,12 @@ use opentelemetry::{attributes, metrics::MetricsError, Context, Key, Value};
 use opentelemetry::{global, InstrumentationLibrary, StringValue};
 use std::sync::{Arc, Mutex};
 
+mod open_metrics;
 mod sanitize;
 
+pub use open_metrics::OpenMetricsEncoder;
+
+use open_metrics::{Exemplar, ExemplarKey, Exemplars};
 use sanitize::sanitize;
 
 /// Monotonic Sum metric points MUST have _total added as a suffix to the metric name
@@ -178,8 +182,10 @@ impl ExporterBuilder {
         let registry = self.registry.unwrap_or_else(prometheus::Registry::new);
 
         let controller = Arc::new(Mutex::new(self.controller));
-        let collector =
-            Collector::with_controller(controller.clone()).with_scope_info(config.with_scope_info);
+        let exemplars = Exemplars::default();
+        let collector = Collector::with_controller(controller.clone())
+            .with_scope_info(config.with_scope_info)
+            .with_exemplars(exemplars.clone());
         registry
             .register(Box::new(collector))
             .map_err(|e| MetricsError::Other(e.to_string()))?;
@@ -187,6 +193,7 @@ impl ExporterBuilder {
         let exporter = PrometheusExporter {
             registry,
             controller,
+            exemplars,
         };
         global::set_meter_provider(exporter.meter_provider()?);
 
@@ -236,6 +243,7 @@ impl ExporterConfig {
 pub struct PrometheusExporter {
     registry: prometheus::Registry,
     controller: Arc<Mutex<BasicController>>,
+    exemplars: Exemplars,
 }
 
 impl PrometheusExporter {
@@ -251,12 +259,19 @@ impl PrometheusExporter {
             .map_err(Into::into)
             .map(|locked| locked.clone())
     }
+
+    /// Returns an encoder of the OpenMetrics text format that includes the
+    /// exemplars of the metrics gathered from this exporter's registry.
+    pub fn open_metrics_encoder(&self) -> OpenMetricsEncoder {
+        OpenMetricsEncoder::new(self.exemplars.clone())
+    }
 }
 
 #[derive(Debug)]
 struct Collector {
     controller: Arc<Mutex<BasicController>>,
     with_scope_info: bool,
+    exemplars: Exemplars,
 }
 
 impl TemporalitySelector for Collector {
@@ -270,12 +285,17 @@ impl Collector {
         Collector {
             controller,
             with_scope_info: true,
+            exemplars: Exemplars::default(),
         }
     }
     fn with_scope_info(mut self, with_scope_info: bool) -> Self {
         self.with_scope_info = with_scope_info;
         self
     }
+    fn with_exemplars(mut self, exemplars: Exemplars) -> Self {
+        self.exemplars = exemplars;
+        self
+    }
 }
 
 impl prometheus::core::Collector for Collector {
@@ -288,6 +308,11 @@ impl prometheus::core::Collector for Collector {
     fn collect(&self) -> Vec<prometheus::proto::MetricFamily> {
         if let Ok(controller) = self.controller.lock() {
             let mut metrics = Vec::new();
+            let mut exemplars = match self.exemplars.lock() {
+                Ok(exemplars) => exemplars,
+                Err(_) => return metrics,
+            };
+            exemplars.clear();
 
             if let Err(err) = controller.collect(&Context::current()) {
                 global::handle_error(err);
@@ -310,9 +335,35 @@ impl prometheus::core::Collector for Collector {
                         get_metric_labels(record, controller.resource(), &mut scope_labels.clone());
 
                     if let Some(hist) = agg.as_any().downcast_ref::<HistogramAggregator>() {
+                        let boundaries = hist.histogram()?.boundaries().clone();
+                        for exemplar in record.exemplars() {
+                            let value = exemplar.value().to_f64(number_kind);
+                            let upper_bound = boundaries
+                                .iter()
+                                .find(|boundary| value < **boundary)
+                                .copied()
+                                .unwrap_or(f64::INFINITY);
+                            exemplars.insert(
+                                ExemplarKey::new(&desc.name, &labels, Some(upper_bound)),
+                                Exemplar::new(exemplar, number_kind),
+                            );
+                        }
                         metrics.push(build_histogram(hist, number_kind, desc, labels)?);
                     } else if let Some(sum) = agg.as_any().downcast_ref::<SumAggregator>() {
                         let counter = if instrument_kind.monotonic() {
+                            // Counters keep the most recent exemplar.
+                            if let Some(exemplar) =
+                                record.exemplars().iter().max_by_key(|e| *e.time())
+                            {
+                                exemplars.insert(
+                                    ExemplarKey::new(
+                                        &(desc.name.clone() + MONOTONIC_COUNTER_SUFFIX),
+                                        &labels,
+                                        None,
+                                    ),
+                                    Exemplar::new(exemplar, number_kind),
+                                );
+                            }
                             build_monotonic_counter(sum, number_kind, desc, labels)?
                         } else {
                             build_non_monotonic_counter(sum, number_kind, desc, labels)?
@@ -0,0 +1,376 @@
+//! OpenMetrics text encoding of the collected metrics, including the exemplars
+//! sampled by the SDK.
+//!
+//! The prometheus data model has no room for exemplars, so the collector keeps
+//! the exemplars of the last collection aside, keyed by the series and bucket
+//! they belong to, and the encoder appends them to the matching samples.
+use opentelemetry::sdk::metrics::exemplar::Exemplar as SdkExemplar;
+use opentelemetry::sdk::metrics::sdk_api::NumberKind;
+use prometheus::proto::{LabelPair, MetricFamily, MetricType};
+use prometheus::Encoder;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use crate::sanitize::sanitize;
+
+/// The OpenMetrics limit of the combined length of the names and values of the
+/// labels of an exemplar.
+const MAX_EXEMPLAR_LABELS_LENGTH: usize = 128;
+
+/// The exemplars of the last collection.
+pub(crate) type Exemplars = Arc<Mutex<HashMap<ExemplarKey, Exemplar>>>;
+
+/// Identifies the sample an exemplar is attached to.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub(crate) struct ExemplarKey {
+    name: String,
+    labels: Vec<(String, String)>,
+    /// Bits of the upper bound of the histogram bucket, if any.
+    upper_bound: Option<u64>,
+}
+
+impl ExemplarKey {
+    pub(crate) fn new(name: &str, labels: &[LabelPair], upper_bound: Option<f64>) -> Self {
+        let mut labels: Vec<(String, String)> = labels
+            .iter()
+            .map(|label| (label.get_name().to_string(), label.get_value().to_string()))
+            .collect();
+        labels.sort();
+
+        ExemplarKey {
+            name: name.to_string(),
+            labels,
+            upper_bound: upper_bound.map(f64::to_bits),
+        }
+    }
+}
+
+/// An exemplar as written in the OpenMetrics exposition.
+#[derive(Clone, Debug)]
+pub(crate) struct Exemplar {
+    labels: Vec<(String, String)>,
+    value: f64,
+    timestamp: Option<f64>,
+}
+
+impl Exemplar {
+    pub(crate) fn new(exemplar: &SdkExemplar, kind: &NumberKind) -> Self {
+        let mut labels = Vec::new();
+        if let (Some(trace_id), Some(span_id)) = (exemplar.trace_id(), exemplar.span_id()) {
+            labels.push(("trace_id".to_string(), trace_id.to_string()));
+            labels.push(("span_id".to_string(), span_id.to_string()));
+        }
+        // Filtered attributes are only kept as long as the labels stay within
+        // the OpenMetrics limit.
+        let mut length: usize = labels.iter().map(|(k, v)| k.len() + v.len()).sum();
+        for kv in exemplar.filtered_attributes() {
+            let (key, value) = (sanitize(kv.key.as_str()), kv.value.to_string());
+            length += key.chars().count() + value.chars().count();
+            if length > MAX_EXEMPLAR_LABELS_LENGTH {
+                break;
+            }
+            labels.push((key, value));
+        }
+
+        Exemplar {
+            labels,
+            value: exemplar.value().to_f64(kind),
+            timestamp: exemplar
+                .time()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|since_epoch| since_epoch.as_secs_f64()),
+        }
+    }
+}
+
+/// Encodes metric families in the OpenMetrics text format, with the exemplars
+/// of the exporter they were gathered from.
+///
+/// Create it with [`PrometheusExporter::open_metrics_encoder`] and use it on
+/// the families gathered from the exporter's registry.
+///
+/// [`PrometheusExporter::open_metrics_encoder`]: crate::PrometheusExporter::open_metrics_encoder
+#[derive(Clone, Debug)]
+pub struct OpenMetricsEncoder {
+    exemplars: Exemplars,
+}
+
+impl OpenMetricsEncoder {
+    pub(crate) fn new(exemplars: Exemplars) -> Self {
+        OpenMetricsEncoder { exemplars }
+    }
+}
+
+impl Encoder for OpenMetricsEncoder {
+    fn encode<W: Write>(&self, mfs: &[MetricFamily], writer: &mut W) -> prometheus::Result<()> {
+        let exemplars = self
+            .exemplars
+            .lock()
+            .map_err(|err| prometheus::Error::Msg(err.to_string()))?;
+
+        for mf in mfs {
+            let name = mf.get_name();
+            let (family, metric_type) = match mf.get_field_type() {
+                MetricType::COUNTER => (name.strip_suffix("_total").unwrap_or(name), "counter"),
+                MetricType::GAUGE => (name, "gauge"),
+                MetricType::HISTOGRAM => (name, "histogram"),
+                MetricType::SUMMARY => (name, "summary"),
+                MetricType::UNTYPED => (name, "unknown"),
+            };
+            writeln!(writer, "# TYPE {} {}", family, metric_type)?;
+            if !mf.get_help().is_empty() {
+                writeln!(writer, "# HELP {} {}", family, escape_help(mf.get_help()))?;
+            }
+
+            for metric in mf.get_metric() {
+                let labels = metric.get_label();
+                match mf.get_field_type() {
+                    MetricType::COUNTER => {
+                        let exemplar = exemplars.get(&ExemplarKey::new(name, labels, None));
+                        write_sample(
+                            writer,
+                            &format!("{}_total", family),
+                            labels,
+                            None,
+                            metric.get_counter().get_value(),
+                            exemplar,
+                        )?;
+                    }
+                    MetricType::GAUGE => write_sample(
+                        writer,
+                        name,
+                        labels,
+                        None,
+                        metric.get_gauge().get_value(),
+                        None,
+                    )?,
+                    MetricType::UNTYPED => write_sample(
+                        writer,
+                        name,
+                        labels,
+                        None,
+                        metric.get_untyped().get_value(),
+                        None,
+                    )?,
+                    MetricType::HISTOGRAM => {
+                        let histogram = metric.get_histogram();
+                        let bucket_name = format!("{}_bucket", name);
+                        let mut buckets: Vec<(f64, f64)> = histogram
+                            .get_bucket()
+                            .iter()
+                            .map(|b| (b.get_upper_bound(), b.get_cumulative_count() as f64))
+                            .collect();
+                        if buckets.last().map_or(true, |(bound, _)| bound.is_finite()) {
+                            buckets.push((f64::INFINITY, histogram.get_sample_count() as f64));
+                        }
+                        for (upper_bound, count) in buckets {
+                            let exemplar =
+                                exemplars.get(&ExemplarKey::new(name, labels, Some(upper_bound)));
+                            write_sample(
+                                writer,
+                                &bucket_name,
+                                labels,
+                                Some(("le", upper_bound)),
+                                count,
+                                exemplar,
+                            )?;
+                        }
+                        write_sample(
+                            writer,
+                            &format!("{}_count", name),
+                            labels,
+                            None,
+                            histogram.get_sample_count() as f64,
+                            None,
+                        )?;
+                        write_sample(
+                            writer,
+                            &format!("{}_sum", name),
+                            labels,
+                            None,
+                            histogram.get_sample_sum(),
+                            None,
+                        )?;
+                    }
+                    MetricType::SUMMARY => {
+                        let summary = metric.get_summary();
+                        for quantile in summary.get_quantile() {
+                            write_sample(
+                                writer,
+                                name,
+                                labels,
+                                Some(("quantile", quantile.get_quantile())),
+                                quantile.get_value(),
+                                None,
+                            )?;
+                        }
+                        write_sample(
+                            writer,
+                            &format!("{}_count", name),
+                            labels,
+                            None,
+                            summary.get_sample_count() as f64,
+                            None,
+                        )?;
+                        write_sample(
+                            writer,
+                            &format!("{}_sum", name),
+                            labels,
+                            None,
+                            summary.get_sample_sum(),
+                            None,
+                        )?;
+                    }
+                }
+            }
+        }
+
+        writeln!(writer, "# EOF")?;
+
+        Ok(())
+    }
+
+    fn format_type(&self) -> &str {
+        "application/openmetrics-text; version=1.0.0; charset=utf-8"
+    }
+}
+
+fn write_sample<W: Write>(
+    writer: &mut W,
+    name: &str,
+    labels: &[LabelPair],
+    additional_label: Option<(&str, f64)>,
+    value: f64,
+    exemplar: Option<&Exemplar>,
+) -> prometheus::Result<()> {
+    writer.write_all(name.as_bytes())?;
+    let mut labels: Vec<(&str, String)> = labels
+        .iter()
+        .map(|label| (label.get_name(), label.get_value().to_string()))
+        .collect();
+    if let Some((name, value)) = additional_label {
+        labels.push((name, format_float(value)));
+    }
+    write_labels(writer, labels.iter().map(|(k, v)| (*k, v.as_str())))?;
+    write!(writer, " {}", format_float(value))?;
+
+    if let Some(exemplar) = exemplar {
+        writer.write_all(b" # ")?;
+        write_labels(
+            writer,
+            exemplar
+                .labels
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str())),
+        )?;
+        if exemplar.labels.is_empty() {
+            writer.write_all(b"{}")?;
+        }
+        write!(writer, " {}", format_float(exemplar.value))?;
+        if let Some(timestamp) = exemplar.timestamp {
+            write!(writer, " {}", timestamp)?;
+        }
+    }
+
+    writer.write_all(b"\n")?;
+
+    Ok(())
+}
+
+fn write_labels<'a, W: Write>(
+    writer: &mut W,
+    labels: impl Iterator<Item = (&'a str, &'a str)>,
+) -> prometheus::Result<()> {
+    let mut separator = "{";
+    for (name, value) in labels {
+        write!(
+            writer,
+            "{}{}=\"{}\"",
+            separator,
+            name,
+            escape_label_value(value)
+        )?;
+        separator = ",";
+    }
+    if separator == "," {
+        writer.write_all(b"}")?;
+    }
+
+    Ok(())
+}
+
+fn format_float(value: f64) -> String {
+    if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else if value.is_nan() {
+        "NaN".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', r"\\").replace('\n', r"\n")
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(name: &str, value: &str) -> LabelPair {
+        let mut label = LabelPair::new();
+        label.set_name(name.to_string());
+        label.set_value(value.to_string());
+        label
+    }
+
+    #[test]
+    fn test_encode_counter_with_exemplar() {
+        let mut counter = prometheus::proto::Counter::new();
+        counter.set_value(3.0);
+        let mut metric = prometheus::proto::Metric::new();
+        metric.set_label(protobuf::RepeatedField::from_vec(vec![label(
+            "path", "/\"a\"",
+        )]));
+        metric.set_counter(counter);
+        let mut mf = MetricFamily::new();
+        mf.set_name("requests_total".to_string());
+        mf.set_help("Counts\nrequests".to_string());
+        mf.set_field_type(MetricType::COUNTER);
+        mf.set_metric(protobuf::RepeatedField::from_vec(vec![metric]));
+
+        let exemplars: Exemplars = Default::default();
+        exemplars.lock().unwrap().insert(
+            ExemplarKey::new("requests_total", &[label("path", "/\"a\"")], None),
+            Exemplar {
+                labels: vec![("trace_id".to_string(), "01".to_string())],
+                value: 1.0,
+                timestamp: Some(1.5),
+            },
+        );
+
+        let mut output = Vec::new();
+        OpenMetricsEncoder::new(exemplars)
+            .encode(&[mf], &mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "# TYPE requests counter\n\
+             # HELP requests Counts\\nrequests\n\
+             requests_total{path=\"/\\\"a\\\"\"} 3 # {trace_id=\"01\"} 1 1.5\n\
+             # EOF\n"
+        );
+    }
+}
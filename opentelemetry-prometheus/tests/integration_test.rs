@@ -1,6 +1,7 @@
 use opentelemetry::sdk::export::metrics::aggregation;
-use opentelemetry::sdk::metrics::{controllers, processors, selectors};
+use opentelemetry::sdk::metrics::{controllers, exemplar::ExemplarFilter, processors, selectors};
 use opentelemetry::sdk::Resource;
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
 use opentelemetry::Context;
 use opentelemetry::{metrics::MeterProvider, KeyValue};
 use opentelemetry_prometheus::{ExporterConfig, PrometheusExporter};
@@ -199,6 +200,80 @@ fn test_scope_info() {
     compare_export(&exporter, expected)
 }
 
+#[test]
+fn test_exemplars() {
+    let cx = Context::new().with_remote_span_context(SpanContext::new(
+        TraceId::from_u128(1),
+        SpanId::from_u64(2),
+        TraceFlags::SAMPLED,
+        false,
+        TraceState::default(),
+    ));
+    let controller = controllers::basic(processors::factory(
+        selectors::simple::histogram(vec![1.0]),
+        aggregation::cumulative_temporality_selector(),
+    ))
+    .with_resource(Resource::new(vec![KeyValue::new("R", "V")]))
+    .with_exemplar_filter(ExemplarFilter::TraceBased)
+    .build();
+    let exporter = opentelemetry_prometheus::exporter(controller)
+        .with_config(ExporterConfig::default().with_scope_info(false))
+        .init();
+    let meter = exporter
+        .meter_provider()
+        .unwrap()
+        .versioned_meter("test", None, None);
+
+    let counter = meter.u64_counter("counter").init();
+    let histogram = meter.f64_histogram("my.histogram").init();
+
+    counter.add(&cx, 3, &[]);
+    counter.add(&Context::new(), 4, &[]);
+    histogram.record(&cx, 0.5, &[]);
+    histogram.record(&cx, 5.0, &[]);
+
+    let mut output = Vec::new();
+    let encoder = exporter.open_metrics_encoder();
+    encoder
+        .encode(&exporter.registry().gather(), &mut output)
+        .unwrap();
+    let output_string = String::from_utf8(output).unwrap();
+
+    // Exemplar timestamps vary, compare the samples up to the exemplar value.
+    let exemplar_labels =
+        r#"{trace_id="00000000000000000000000000000001",span_id="0000000000000002"}"#;
+    let expected = vec![
+        "# EOF".to_string(),
+        format!(r#"counter_total{{R="V"}} 7 # {} 3"#, exemplar_labels),
+        format!(
+            r#"my_histogram_bucket{{R="V",le="+Inf"}} 2 # {} 5"#,
+            exemplar_labels
+        ),
+        format!(
+            r#"my_histogram_bucket{{R="V",le="1"}} 1 # {} 0.5"#,
+            exemplar_labels
+        ),
+        r#"my_histogram_count{R="V"} 2"#.to_string(),
+        r#"my_histogram_sum{R="V"} 5.5"#.to_string(),
+    ];
+    let mut samples = output_string
+        .split_terminator('\n')
+        .filter(|line| !line.starts_with("# TYPE") && !line.starts_with("# HELP"))
+        .map(|line| match line.split_once(" # {") {
+            Some(_) => line.rsplit_once(' ').unwrap().0.to_string(),
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>();
+    samples.sort_unstable();
+
+    assert_eq!(expected, samples);
+    assert!(output_string.contains("# TYPE counter counter"));
+    assert_eq!(
+        encoder.format_type(),
+        "application/openmetrics-text; version=1.0.0; charset=utf-8"
+    );
+}
+
 fn compare_export(exporter: &PrometheusExporter, mut expected: Vec<&'static str>) {
     let mut output = Vec::new();
     let encoder = TextEncoder::new();
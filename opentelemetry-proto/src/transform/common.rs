@@ -1,7 +1,7 @@
-#[cfg(feature = "traces")]
+#[cfg(any(feature = "traces", feature = "logs"))]
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-#[cfg(feature = "traces")]
+#[cfg(any(feature = "traces", feature = "logs"))]
 pub(crate) fn to_nanos(time: SystemTime) -> u64 {
     time.duration_since(UNIX_EPOCH)
         .unwrap_or_else(|_| Duration::from_secs(0))
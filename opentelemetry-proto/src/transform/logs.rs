@@ -0,0 +1,153 @@
+use crate::transform::common::to_nanos;
+use opentelemetry::logs::{AnyValue, Severity};
+use opentelemetry::sdk::{self, export::logs::LogData};
+
+#[cfg(feature = "gen-tonic")]
+pub mod tonic {
+    use super::*;
+    use crate::proto::tonic::{
+        common::v1::{
+            any_value::Value, AnyValue as ProtoAnyValue, ArrayValue, KeyValue, KeyValueList,
+        },
+        logs::v1::{InstrumentationLibraryLogs, LogRecord, ResourceLogs, SeverityNumber},
+        resource::v1::Resource,
+    };
+    use crate::transform::common::tonic::Attributes;
+
+    impl From<AnyValue> for ProtoAnyValue {
+        fn from(value: AnyValue) -> Self {
+            ProtoAnyValue {
+                value: Some(match value {
+                    AnyValue::Int(val) => Value::IntValue(val),
+                    AnyValue::Double(val) => Value::DoubleValue(val),
+                    AnyValue::String(val) => Value::StringValue(val.into()),
+                    AnyValue::Boolean(val) => Value::BoolValue(val),
+                    AnyValue::Bytes(val) => Value::BytesValue(val),
+                    AnyValue::ListAny(vals) => Value::ArrayValue(ArrayValue {
+                        values: vals.into_iter().map(Into::into).collect(),
+                    }),
+                    AnyValue::Map(map) => Value::KvlistValue(KeyValueList {
+                        values: map
+                            .into_iter()
+                            .map(|(key, value)| KeyValue {
+                                key: key.into(),
+                                value: Some(value.into()),
+                            })
+                            .collect(),
+                    }),
+                }),
+            }
+        }
+    }
+
+    impl From<Severity> for SeverityNumber {
+        fn from(severity: Severity) -> Self {
+            match severity {
+                Severity::Trace => SeverityNumber::Trace,
+                Severity::Trace2 => SeverityNumber::Trace2,
+                Severity::Trace3 => SeverityNumber::Trace3,
+                Severity::Trace4 => SeverityNumber::Trace4,
+                Severity::Debug => SeverityNumber::Debug,
+                Severity::Debug2 => SeverityNumber::Debug2,
+                Severity::Debug3 => SeverityNumber::Debug3,
+                Severity::Debug4 => SeverityNumber::Debug4,
+                Severity::Info => SeverityNumber::Info,
+                Severity::Info2 => SeverityNumber::Info2,
+                Severity::Info3 => SeverityNumber::Info3,
+                Severity::Info4 => SeverityNumber::Info4,
+                Severity::Warn => SeverityNumber::Warn,
+                Severity::Warn2 => SeverityNumber::Warn2,
+                Severity::Warn3 => SeverityNumber::Warn3,
+                Severity::Warn4 => SeverityNumber::Warn4,
+                Severity::Error => SeverityNumber::Error,
+                Severity::Error2 => SeverityNumber::Error2,
+                Severity::Error3 => SeverityNumber::Error3,
+                Severity::Error4 => SeverityNumber::Error4,
+                Severity::Fatal => SeverityNumber::Fatal,
+                Severity::Fatal2 => SeverityNumber::Fatal2,
+                Severity::Fatal3 => SeverityNumber::Fatal3,
+                Severity::Fatal4 => SeverityNumber::Fatal4,
+            }
+        }
+    }
+
+    impl From<opentelemetry::logs::LogRecord> for LogRecord {
+        fn from(log_record: opentelemetry::logs::LogRecord) -> Self {
+            let trace_context = log_record.trace_context.as_ref();
+            let severity_number = log_record
+                .severity_number
+                .map(SeverityNumber::from)
+                .unwrap_or(SeverityNumber::Unspecified);
+
+            #[allow(deprecated)]
+            LogRecord {
+                time_unix_nano: log_record.timestamp.map(to_nanos).unwrap_or_default(),
+                observed_time_unix_nano: log_record
+                    .observed_timestamp
+                    .map(to_nanos)
+                    .unwrap_or_default(),
+                severity_number: severity_number as i32,
+                severity_text: log_record.severity_text.map(Into::into).unwrap_or_default(),
+                name: Default::default(),
+                body: log_record.body.map(Into::into),
+                attributes: log_record
+                    .attributes
+                    .map(|attributes| {
+                        attributes
+                            .into_iter()
+                            .map(|(key, value)| KeyValue {
+                                key: key.into(),
+                                value: Some(value.into()),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                dropped_attributes_count: 0,
+                flags: trace_context
+                    .and_then(|ctx| ctx.trace_flags)
+                    .map(|flags| flags.to_u8() as u32)
+                    .unwrap_or_default(),
+                trace_id: trace_context
+                    .map(|ctx| ctx.trace_id.to_bytes().to_vec())
+                    .unwrap_or_default(),
+                span_id: trace_context
+                    .map(|ctx| ctx.span_id.to_bytes().to_vec())
+                    .unwrap_or_default(),
+            }
+        }
+    }
+
+    impl From<LogData> for ResourceLogs {
+        fn from(log_data: LogData) -> Self {
+            ResourceLogs {
+                resource: Some(Resource {
+                    attributes: resource_attributes(&log_data.resource).0,
+                    dropped_attributes_count: 0,
+                }),
+                schema_url: log_data
+                    .resource
+                    .schema_url()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                instrumentation_library_logs: vec![InstrumentationLibraryLogs {
+                    schema_url: log_data
+                        .instrumentation
+                        .schema_url
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                    instrumentation_library: Some(log_data.instrumentation.into()),
+                    log_records: vec![log_data.record.into()],
+                }],
+            }
+        }
+    }
+
+    fn resource_attributes(resource: &sdk::Resource) -> Attributes {
+        resource
+            .iter()
+            .map(|(k, v)| opentelemetry::KeyValue::new(k.clone(), v.clone()))
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
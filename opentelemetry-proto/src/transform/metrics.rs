@@ -7,7 +7,7 @@
 pub mod tonic {
     use crate::proto::tonic::{
         common::v1::KeyValue,
-        metrics::v1::{number_data_point, AggregationTemporality},
+        metrics::v1::{exemplar, number_data_point, AggregationTemporality},
     };
     use opentelemetry::{
         metrics::MetricsError,
@@ -36,6 +36,17 @@ pub mod tonic {
         }
     }
 
+    impl FromNumber for exemplar::Value {
+        fn from_number(number: Number, number_kind: &NumberKind) -> Self {
+            match &number_kind {
+                NumberKind::I64 | NumberKind::U64 => {
+                    exemplar::Value::AsInt(number.to_i64(number_kind))
+                }
+                NumberKind::F64 => exemplar::Value::AsDouble(number.to_f64(number_kind)),
+            }
+        }
+    }
+
     impl From<(&Key, &Value)> for KeyValue {
         fn from(kv: (&Key, &Value)) -> Self {
             KeyValue {
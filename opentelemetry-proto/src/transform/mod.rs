@@ -1,5 +1,8 @@
 pub mod common;
 
+#[cfg(feature = "logs")]
+pub mod logs;
+
 #[cfg(feature = "metrics")]
 pub mod metrics;
 
@@ -0,0 +1,58 @@
+//! Log exporters
+use crate::Resource;
+use futures_util::future::BoxFuture;
+use opentelemetry_api::logs::{LogError, LogRecord};
+use opentelemetry_api::InstrumentationLibrary;
+use std::borrow::Cow;
+use std::fmt::Debug;
+
+/// Describes the result of an export.
+pub type ExportResult = Result<(), LogError>;
+
+/// `LogExporter` defines the interface that log exporters should implement.
+///
+/// It mirrors [`SpanExporter`]: protocol exporters are expected to be simple
+/// encoders and transmitters of the log data handed to them by a
+/// [`LogProcessor`].
+///
+/// [`SpanExporter`]: crate::export::trace::SpanExporter
+/// [`LogProcessor`]: crate::logs::LogProcessor
+pub trait LogExporter: Send + Debug {
+    /// Exports a batch of [`LogData`]. Protocol exporters that will implement
+    /// this function are typically expected to serialize and transmit the
+    /// data to the destination.
+    ///
+    /// This function will never be called concurrently for the same exporter
+    /// instance. It can be called again only after the current call returns.
+    ///
+    /// This function must not block indefinitely, there must be a reasonable
+    /// upper limit after which the call must time out with an error result.
+    fn export(&mut self, batch: Vec<LogData>) -> BoxFuture<'static, ExportResult>;
+
+    /// Shuts down the exporter. Called when SDK is shut down. This is an
+    /// opportunity for exporter to do any cleanup required.
+    ///
+    /// After the call to `shutdown`, subsequent calls to `export` are not
+    /// allowed and should return an error.
+    fn shutdown(&mut self) {}
+
+    /// This is a hint to ensure that the export of any log records the
+    /// exporter has received prior to the call to this function SHOULD be
+    /// completed as soon as possible, preferably before returning from this
+    /// method.
+    fn force_flush(&mut self) -> BoxFuture<'static, ExportResult> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// `LogData` associates a [`LogRecord`] with a [`Resource`] and
+/// [`InstrumentationLibrary`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogData {
+    /// Log record
+    pub record: LogRecord,
+    /// Resource for the emitter who produced this `LogData`.
+    pub resource: Cow<'static, Resource>,
+    /// Instrumentation details for the emitter who produced this `LogData`.
+    pub instrumentation: InstrumentationLibrary,
+}
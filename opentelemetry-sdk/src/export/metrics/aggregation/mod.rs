@@ -72,12 +72,68 @@ impl Buckets {
     }
 }
 
+/// MinMax returns the minimum and maximum of the values that were aggregated.
+pub trait MinMax: Aggregation {
+    /// The minimum of the currently aggregated metrics
+    fn min(&self) -> Result<Number>;
+
+    /// The maximum of the currently aggregated metrics
+    fn max(&self) -> Result<Number>;
+}
+
 /// Histogram returns the count of events in pre-determined buckets.
 pub trait Histogram: Sum + Count + Aggregation {
     /// Buckets for this histogram.
     fn histogram(&self) -> Result<Buckets>;
 }
 
+/// Buckets of an exponential histogram covering a contiguous range of bucket
+/// indices.
+///
+/// At scale `s` the bucket with index `i` counts the values in
+/// `(2^(i * 2^-s), 2^((i + 1) * 2^-s)]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExponentialBuckets {
+    /// Index of the first bucket.
+    offset: i32,
+
+    /// Counts of the consecutive buckets starting at `offset`.
+    counts: Vec<u64>,
+}
+
+impl ExponentialBuckets {
+    /// Create new exponential buckets
+    pub fn new(offset: i32, counts: Vec<u64>) -> Self {
+        ExponentialBuckets { offset, counts }
+    }
+
+    /// Index of the first bucket
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    /// Counts of the buckets, starting at the bucket with index `offset`
+    pub fn counts(&self) -> &Vec<u64> {
+        &self.counts
+    }
+}
+
+/// ExponentialHistogram returns the count of events in buckets whose
+/// boundaries grow exponentially, with a resolution given by the scale.
+pub trait ExponentialHistogram: Sum + Count + Aggregation {
+    /// The scale of the histogram, the base of the buckets is `2^(2^-scale)`.
+    fn scale(&self) -> Result<i8>;
+
+    /// Count of the values that are exactly zero.
+    fn zero_count(&self) -> Result<u64>;
+
+    /// Buckets of the positive values.
+    fn positive(&self) -> Result<ExponentialBuckets>;
+
+    /// Buckets of the negative values, indexed by their absolute value.
+    fn negative(&self) -> Result<ExponentialBuckets>;
+}
+
 /// A short name for the [`Aggregator`] that produces an [`Aggregation`].
 ///
 /// Kind is a string to allow user-defined Aggregators.
@@ -100,6 +156,9 @@ impl AggregationKind {
     /// Aggregations that return a distribution
     pub const HISTOGRAM: Self = AggregationKind("HISTOGRAM");
 
+    /// Aggregations that return a distribution in exponential buckets
+    pub const EXPONENTIAL_HISTOGRAM: Self = AggregationKind("EXPONENTIAL_HISTOGRAM");
+
     /// Aggregations that return only the latest value.
     pub const LAST_VALUE: AggregationKind = AggregationKind("LAST_VALUE");
 
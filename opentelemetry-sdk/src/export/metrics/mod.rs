@@ -3,14 +3,15 @@
 use core::fmt;
 use std::{sync::Arc, time::SystemTime};
 
+use futures_util::future::{self, BoxFuture};
 use opentelemetry_api::{attributes, metrics::Result, Context, InstrumentationLibrary};
 
 use crate::{
-    metrics::{aggregators::Aggregator, sdk_api::Descriptor},
+    metrics::{aggregators::Aggregator, exemplar::Exemplar, sdk_api::Descriptor},
     Resource,
 };
 
-use self::aggregation::TemporalitySelector;
+use self::aggregation::{AggregationKind, TemporalitySelector};
 
 pub mod aggregation;
 mod stdout;
@@ -80,6 +81,8 @@ pub fn accumulation<'a>(
 pub struct Accumulation<'a> {
     metadata: Metadata<'a>,
     aggregator: &'a Arc<dyn Aggregator + Send + Sync>,
+    aggregator_selector: Option<&'a dyn AggregatorSelector>,
+    exemplars: &'a [Exemplar],
 }
 
 impl<'a> Accumulation<'a> {
@@ -92,9 +95,28 @@ impl<'a> Accumulation<'a> {
         Accumulation {
             metadata: Metadata::new(descriptor, attributes),
             aggregator,
+            aggregator_selector: None,
+            exemplars: &[],
         }
     }
 
+    /// Set the selector allocating the aggregators of this metric, when it
+    /// differs from the processor's, e.g. because a view overrides the
+    /// aggregation of the instrument.
+    pub fn with_aggregator_selector(
+        mut self,
+        aggregator_selector: &'a dyn AggregatorSelector,
+    ) -> Self {
+        self.aggregator_selector = Some(aggregator_selector);
+        self
+    }
+
+    /// Set the exemplars sampled during the collection interval.
+    pub fn with_exemplars(mut self, exemplars: &'a [Exemplar]) -> Self {
+        self.exemplars = exemplars;
+        self
+    }
+
     /// A description of the metric instrument being exported.
     pub fn descriptor(&self) -> &Descriptor {
         self.metadata.descriptor
@@ -109,6 +131,17 @@ impl<'a> Accumulation<'a> {
     pub fn aggregator(&self) -> &Arc<dyn Aggregator + Send + Sync> {
         self.aggregator
     }
+
+    /// The selector to allocate the aggregators of this metric with, if it
+    /// differs from the processor's.
+    pub fn aggregator_selector(&self) -> Option<&dyn AggregatorSelector> {
+        self.aggregator_selector
+    }
+
+    /// The exemplars sampled during the collection interval.
+    pub fn exemplars(&self) -> &[Exemplar] {
+        self.exemplars
+    }
 }
 
 impl<'a> fmt::Debug for Accumulation<'a> {
@@ -146,9 +179,16 @@ pub struct Record<'a> {
     aggregator: Option<&'a Arc<dyn Aggregator + Send + Sync>>,
     start: SystemTime,
     end: SystemTime,
+    exemplars: &'a [Exemplar],
 }
 
-impl Record<'_> {
+impl<'a> Record<'a> {
+    /// Set the exemplars sampled for this aggregation.
+    pub fn with_exemplars(mut self, exemplars: &'a [Exemplar]) -> Self {
+        self.exemplars = exemplars;
+        self
+    }
+
     /// A description of the metric instrument being exported.
     pub fn descriptor(&self) -> &Descriptor {
         self.metadata.descriptor
@@ -173,6 +213,11 @@ impl Record<'_> {
     pub fn end_time(&self) -> &SystemTime {
         &self.end
     }
+
+    /// The exemplars sampled for this aggregation.
+    pub fn exemplars(&self) -> &[Exemplar] {
+        self.exemplars
+    }
 }
 
 /// Exporter handles presentation of the checkpoint of aggregate
@@ -193,6 +238,17 @@ pub trait MetricsExporter: TemporalitySelector {
         res: &Resource,
         reader: &dyn InstrumentationLibraryReader,
     ) -> Result<()>;
+
+    /// Shuts down the exporter, returning a future that completes once the
+    /// data passed to previous `export` calls has been sent.
+    ///
+    /// Exporters that send data in the background should override this so
+    /// that no data is lost when the controller is stopped. The controller
+    /// bounds the time it waits for the returned future with its push
+    /// timeout.
+    fn shutdown(&self) -> BoxFuture<'static, Result<()>> {
+        Box::pin(future::ready(Ok(())))
+    }
 }
 
 /// InstrumentationLibraryReader is an interface for exporters to iterate
@@ -299,6 +355,7 @@ pub fn record<'a>(
         aggregator,
         start,
         end,
+        exemplars: &[],
     }
 }
 
@@ -352,4 +409,15 @@ pub trait Processor: AsDynProcessor {
     /// require an Aggregator. This does not provide a way to
     /// disable metrics with active records.
     fn aggregator_selector(&self) -> &dyn AggregatorSelector;
+
+    /// Whether the processor keeps the state of every attribute set of a
+    /// stream across collections, even once the attribute set is no longer
+    /// recorded. Defaults to `false`.
+    ///
+    /// The accumulator keeps the records of such streams, so that they count
+    /// against the cardinality limit for as long as the processor remembers
+    /// them.
+    fn memory_required(&self, _descriptor: &Descriptor, _kind: &AggregationKind) -> bool {
+        false
+    }
 }
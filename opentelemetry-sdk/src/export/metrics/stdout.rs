@@ -1,10 +1,12 @@
 //! Stdout Metrics Exporter
 use crate::{
     export::metrics::{
-        aggregation::{stateless_temporality_selector, LastValue, Sum, TemporalitySelector},
+        aggregation::{
+            stateless_temporality_selector, Count, LastValue, MinMax, Sum, TemporalitySelector,
+        },
         InstrumentationLibraryReader, MetricsExporter,
     },
-    metrics::aggregators::{LastValueAggregator, SumAggregator},
+    metrics::aggregators::{HistogramAggregator, LastValueAggregator, SumAggregator},
     Resource,
 };
 use opentelemetry_api::{
@@ -53,6 +55,15 @@ pub struct ExportLine {
     /// populated if using last value aggregator
     pub last_value: Option<ExportNumeric>,
 
+    /// populated if using histogram aggregator
+    pub count: Option<u64>,
+
+    /// populated if using histogram aggregator and values were recorded
+    pub min: Option<ExportNumeric>,
+
+    /// populated if using histogram aggregator and values were recorded
+    pub max: Option<ExportNumeric>,
+
     /// metric timestamp
     pub timestamp: Option<SystemTime>,
 }
@@ -127,6 +138,13 @@ where
                     if self.timestamps {
                         expose.timestamp = Some(timestamp);
                     }
+                } else if let Some(histogram) = agg.as_any().downcast_ref::<HistogramAggregator>() {
+                    expose.sum = Some(ExportNumeric(histogram.sum()?.to_debug(kind)));
+                    expose.count = Some(histogram.count()?);
+                    if let (Ok(min), Ok(max)) = (histogram.min(), histogram.max()) {
+                        expose.min = Some(ExportNumeric(min.to_debug(kind)));
+                        expose.max = Some(ExportNumeric(max.to_debug(kind)));
+                    }
                 }
 
                 let mut encoded_attributes = String::new();
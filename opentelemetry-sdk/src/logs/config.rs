@@ -0,0 +1,22 @@
+use crate::Resource;
+use std::borrow::Cow;
+
+/// Default log configuration
+pub fn config() -> Config {
+    Config::default()
+}
+
+/// Log emitter configuration.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Contains attributes representing an entity that produces telemetry.
+    pub resource: Cow<'static, Resource>,
+}
+
+impl Config {
+    /// Specify the attributes representing the entity that produces telemetry
+    pub fn with_resource(mut self, resource: Resource) -> Self {
+        self.resource = Cow::Owned(resource);
+        self
+    }
+}
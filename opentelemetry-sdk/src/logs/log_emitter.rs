@@ -0,0 +1,313 @@
+//! # Logger Provider SDK
+//!
+//! New [`Logger`] instances are always created through a [`LoggerProvider`].
+//! All configuration objects and extension points (log processors, resource)
+//! are provided by the [`LoggerProvider`] and shared by the [`Logger`]s it
+//! creates.
+use super::{BatchLogProcessor, Config, LogProcessor, LogRuntime, SimpleLogProcessor};
+use crate::export::logs::{LogData, LogExporter};
+use crate::{InstrumentationLibrary, Resource};
+use once_cell::sync::OnceCell;
+use opentelemetry_api::{
+    global,
+    logs::{LogRecord, LogResult, TraceContext},
+    Context,
+};
+use std::{
+    borrow::Cow,
+    sync::{Arc, Weak},
+};
+
+/// Default logger name if empty string is provided.
+const DEFAULT_COMPONENT_NAME: &str = "rust.opentelemetry.io/sdk/logger";
+static PROVIDER_RESOURCE: OnceCell<Resource> = OnceCell::new();
+
+/// LoggerProvider inner type
+#[derive(Debug)]
+struct LoggerProviderInner {
+    processors: Vec<Box<dyn LogProcessor>>,
+    config: Config,
+}
+
+impl Drop for LoggerProviderInner {
+    fn drop(&mut self) {
+        for processor in &mut self.processors {
+            if let Err(err) = processor.shutdown() {
+                global::handle_error(err);
+            }
+        }
+    }
+}
+
+/// Creator and registry of named [`Logger`] instances.
+#[derive(Clone, Debug)]
+pub struct LoggerProvider {
+    inner: Arc<LoggerProviderInner>,
+}
+
+impl Default for LoggerProvider {
+    fn default() -> Self {
+        LoggerProvider::builder().build()
+    }
+}
+
+impl LoggerProvider {
+    /// Create a new [`LoggerProvider`] builder.
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// Config associated with this provider.
+    pub fn config(&self) -> &Config {
+        &self.inner.config
+    }
+
+    /// Log processors associated with this provider.
+    pub fn log_processors(&self) -> &Vec<Box<dyn LogProcessor>> {
+        &self.inner.processors
+    }
+
+    /// Force flush all remaining logs in log processors and return results.
+    pub fn force_flush(&self) -> Vec<LogResult<()>> {
+        self.log_processors()
+            .iter()
+            .map(|processor| processor.force_flush())
+            .collect()
+    }
+}
+
+impl opentelemetry_api::logs::LoggerProvider for LoggerProvider {
+    /// This implementation of `LoggerProvider` produces `Logger` instances.
+    type Logger = Logger;
+
+    /// Create a new versioned `Logger` instance.
+    fn versioned_logger(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        version: Option<&'static str>,
+        schema_url: Option<&'static str>,
+        include_trace_context: bool,
+    ) -> Self::Logger {
+        let name = name.into();
+        // Use default value if name is invalid empty string
+        let component_name = if name.is_empty() {
+            Cow::Borrowed(DEFAULT_COMPONENT_NAME)
+        } else {
+            name
+        };
+        let instrumentation_lib = InstrumentationLibrary::new(
+            component_name,
+            version.map(Into::into),
+            schema_url.map(Into::into),
+        );
+
+        Logger {
+            include_trace_context,
+            instrumentation_lib,
+            provider: Arc::downgrade(&self.inner),
+        }
+    }
+}
+
+/// Builder for provider attributes.
+#[derive(Debug, Default)]
+pub struct Builder {
+    processors: Vec<Box<dyn LogProcessor>>,
+    config: Config,
+}
+
+impl Builder {
+    /// The `LogExporter` that this provider should use.
+    pub fn with_simple_exporter<T: LogExporter + 'static>(self, exporter: T) -> Self {
+        self.with_log_processor(SimpleLogProcessor::new(Box::new(exporter)))
+    }
+
+    /// The [`LogExporter`] setup using a default [`BatchLogProcessor`] that this provider should use.
+    pub fn with_batch_exporter<T: LogExporter + 'static, R: LogRuntime>(
+        self,
+        exporter: T,
+        runtime: R,
+    ) -> Self {
+        let batch = BatchLogProcessor::builder(exporter, runtime).build();
+        self.with_log_processor(batch)
+    }
+
+    /// The [`LogProcessor`] that this provider should use.
+    pub fn with_log_processor<T: LogProcessor + 'static>(self, processor: T) -> Self {
+        let mut processors = self.processors;
+        processors.push(Box::new(processor));
+
+        Builder { processors, ..self }
+    }
+
+    /// The SDK [`Config`] that this provider will use.
+    pub fn with_config(self, config: Config) -> Self {
+        Builder { config, ..self }
+    }
+
+    /// Create a new provider from this configuration.
+    pub fn build(self) -> LoggerProvider {
+        let mut config = self.config;
+
+        // Like the tracer provider, share a static reference to the resource
+        // in the common case to avoid cloning it for every emitted record.
+        if matches!(config.resource, Cow::Owned(_)) {
+            config.resource = match PROVIDER_RESOURCE.try_insert(config.resource.into_owned()) {
+                Ok(static_resource) => Cow::Borrowed(static_resource),
+                Err((prev, new)) => {
+                    if prev == &new {
+                        Cow::Borrowed(prev)
+                    } else {
+                        Cow::Owned(new)
+                    }
+                }
+            }
+        }
+
+        LoggerProvider {
+            inner: Arc::new(LoggerProviderInner {
+                processors: self.processors,
+                config,
+            }),
+        }
+    }
+}
+
+/// The object for emitting [`LogRecord`]s.
+///
+/// Records are handed to every [`LogProcessor`] registered on the
+/// [`LoggerProvider`] that created this logger.
+#[derive(Debug)]
+pub struct Logger {
+    include_trace_context: bool,
+    instrumentation_lib: InstrumentationLibrary,
+    provider: Weak<LoggerProviderInner>,
+}
+
+impl Logger {
+    /// Instrumentation library information of this logger.
+    pub fn instrumentation_library(&self) -> &InstrumentationLibrary {
+        &self.instrumentation_lib
+    }
+}
+
+impl opentelemetry_api::logs::Logger for Logger {
+    /// Emit a `LogRecord`.
+    fn emit(&self, record: LogRecord) {
+        let provider = match self.provider.upgrade() {
+            Some(provider) => provider,
+            None => return,
+        };
+
+        let mut record = record;
+        if self.include_trace_context && record.trace_context.is_none() {
+            record.trace_context = TraceContext::from_context(&Context::current());
+        }
+        if record.observed_timestamp.is_none() {
+            record.observed_timestamp = Some(opentelemetry_api::time::now());
+        }
+
+        let data = LogData {
+            record,
+            resource: provider.config.resource.clone(),
+            instrumentation: self.instrumentation_lib.clone(),
+        };
+
+        for processor in provider.processors.iter() {
+            processor.emit(data.clone());
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::LoggerProvider;
+    use crate::export::logs::LogData;
+    use crate::logs::LogProcessor;
+    use opentelemetry_api::logs::{LogRecord, LogResult, Logger, LoggerProvider as _};
+    use opentelemetry_api::testing::trace::TestSpan;
+    use opentelemetry_api::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+    };
+    use opentelemetry_api::Context;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, Default)]
+    struct RecordingProcessor {
+        logs: Arc<Mutex<Vec<LogData>>>,
+    }
+
+    impl LogProcessor for RecordingProcessor {
+        fn emit(&self, data: LogData) {
+            self.logs.lock().unwrap().push(data);
+        }
+
+        fn force_flush(&self) -> LogResult<()> {
+            Ok(())
+        }
+
+        fn shutdown(&mut self) -> LogResult<()> {
+            Ok(())
+        }
+    }
+
+    fn active_span_context() -> SpanContext {
+        SpanContext::new(
+            TraceId::from_u128(42),
+            SpanId::from_u64(7),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::default(),
+        )
+    }
+
+    #[test]
+    fn logger_correlates_records_with_active_span() {
+        let processor = RecordingProcessor::default();
+        let provider = LoggerProvider::builder()
+            .with_log_processor(processor.clone())
+            .build();
+        let logger = provider.logger("test");
+
+        let _guard = Context::current_with_span(TestSpan(active_span_context())).attach();
+        logger.emit(LogRecord::builder().with_body("hello".into()).build());
+
+        let logs = processor.logs.lock().unwrap();
+        assert_eq!(logs.len(), 1);
+        let trace_context = logs[0].record.trace_context.as_ref().unwrap();
+        assert_eq!(trace_context.trace_id, TraceId::from_u128(42));
+        assert_eq!(trace_context.span_id, SpanId::from_u64(7));
+        assert!(logs[0].record.observed_timestamp.is_some());
+        assert_eq!(logs[0].instrumentation.name, "test");
+    }
+
+    #[test]
+    fn logger_without_trace_context() {
+        let processor = RecordingProcessor::default();
+        let provider = LoggerProvider::builder()
+            .with_log_processor(processor.clone())
+            .build();
+        let logger = provider.versioned_logger("test", None, None, false);
+
+        let _guard = Context::current_with_span(TestSpan(active_span_context())).attach();
+        logger.emit(LogRecord::builder().build());
+
+        let logs = processor.logs.lock().unwrap();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].record.trace_context.is_none());
+    }
+
+    #[test]
+    fn logger_is_noop_after_provider_dropped() {
+        let processor = RecordingProcessor::default();
+        let provider = LoggerProvider::builder()
+            .with_log_processor(processor.clone())
+            .build();
+        let logger = provider.logger("test");
+        drop(provider);
+
+        logger.emit(LogRecord::builder().build());
+
+        assert!(processor.logs.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,512 @@
+//! # OpenTelemetry Log Processor Interface
+//!
+//! Log processors are invoked by SDK [`Logger`]s each time a log record is
+//! emitted. Built-in log processors are responsible for batching and passing
+//! log records to a [`LogExporter`], mirroring their span counterparts.
+//!
+//! Log processors are registered on the SDK [`LoggerProvider`] and invoked
+//! in the same order as they were registered.
+//!
+//! [`Logger`]: crate::logs::Logger
+//! [`LoggerProvider`]: crate::logs::LoggerProvider
+//! [`LogExporter`]: crate::export::logs::LogExporter
+
+use crate::export::logs::{ExportResult, LogData, LogExporter};
+use crate::logs::runtime::{LogRuntime, TrySend};
+use futures_channel::oneshot;
+use futures_util::{
+    future::{self, Either},
+    stream, StreamExt as _,
+};
+use opentelemetry_api::{
+    global,
+    logs::{LogError, LogResult},
+};
+use std::{env, fmt, str::FromStr, thread, time::Duration};
+
+/// Delay interval between two consecutive exports.
+const OTEL_BLRP_SCHEDULE_DELAY: &str = "OTEL_BLRP_SCHEDULE_DELAY";
+/// Default delay interval between two consecutive exports.
+const OTEL_BLRP_SCHEDULE_DELAY_DEFAULT: u64 = 1_000;
+/// Maximum allowed time to export data.
+const OTEL_BLRP_EXPORT_TIMEOUT: &str = "OTEL_BLRP_EXPORT_TIMEOUT";
+/// Default maximum allowed time to export data.
+const OTEL_BLRP_EXPORT_TIMEOUT_DEFAULT: u64 = 30_000;
+/// Maximum queue size.
+const OTEL_BLRP_MAX_QUEUE_SIZE: &str = "OTEL_BLRP_MAX_QUEUE_SIZE";
+/// Default maximum queue size.
+const OTEL_BLRP_MAX_QUEUE_SIZE_DEFAULT: usize = 2_048;
+/// Maximum batch size, must be less than or equal to OTEL_BLRP_MAX_QUEUE_SIZE.
+const OTEL_BLRP_MAX_EXPORT_BATCH_SIZE: &str = "OTEL_BLRP_MAX_EXPORT_BATCH_SIZE";
+/// Default maximum batch size.
+const OTEL_BLRP_MAX_EXPORT_BATCH_SIZE_DEFAULT: usize = 512;
+
+/// `LogProcessor` is an interface which allows hooks for log record
+/// emission.
+pub trait LogProcessor: Send + Sync + fmt::Debug {
+    /// Called when a log record is ready to be processed and exported. This
+    /// method is called synchronously on the thread that emitted the record,
+    /// therefore it should not block or throw exceptions.
+    fn emit(&self, data: LogData);
+    /// Force the logs lying in the cache to be exported.
+    fn force_flush(&self) -> LogResult<()>;
+    /// Shuts down the processor. Called when SDK is shut down. This is an
+    /// opportunity for processors to do any cleanup required.
+    fn shutdown(&mut self) -> LogResult<()>;
+}
+
+/// A [`LogProcessor`] that exports synchronously when logs are emitted.
+///
+/// Note that the simple processor exports every time a log record is emitted.
+/// If you find this limiting, consider the batch processor instead.
+#[derive(Debug)]
+pub struct SimpleLogProcessor {
+    sender: crossbeam_channel::Sender<Option<LogData>>,
+    shutdown: crossbeam_channel::Receiver<()>,
+}
+
+impl SimpleLogProcessor {
+    pub(crate) fn new(mut exporter: Box<dyn LogExporter>) -> Self {
+        let (log_tx, log_rx) = crossbeam_channel::unbounded();
+        let (shutdown_tx, shutdown_rx) = crossbeam_channel::bounded(0);
+
+        let _ = thread::Builder::new()
+            .name("opentelemetry-log-exporter".to_string())
+            .spawn(move || {
+                while let Ok(Some(log)) = log_rx.recv() {
+                    if let Err(err) = futures_executor::block_on(exporter.export(vec![log])) {
+                        global::handle_error(err);
+                    }
+                }
+
+                exporter.shutdown();
+
+                if let Err(err) = shutdown_tx.send(()) {
+                    global::handle_error(LogError::from(format!(
+                        "could not send shutdown: {:?}",
+                        err
+                    )));
+                }
+            });
+
+        SimpleLogProcessor {
+            sender: log_tx,
+            shutdown: shutdown_rx,
+        }
+    }
+}
+
+impl LogProcessor for SimpleLogProcessor {
+    fn emit(&self, data: LogData) {
+        if let Err(err) = self.sender.send(Some(data)) {
+            global::handle_error(LogError::from(format!("error processing log {:?}", err)));
+        }
+    }
+
+    fn force_flush(&self) -> LogResult<()> {
+        // Ignored since all logs in Simple Processor will be exported as they are emitted.
+        Ok(())
+    }
+
+    fn shutdown(&mut self) -> LogResult<()> {
+        if self.sender.send(None).is_ok() {
+            if let Err(err) = self.shutdown.recv() {
+                global::handle_error(LogError::from(format!(
+                    "error shutting down log processor: {:?}",
+                    err
+                )))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A [`LogProcessor`] that asynchronously buffers log records and reports
+/// them at a preconfigured interval.
+///
+/// Like the [`BatchSpanProcessor`], it runs a background task on the given
+/// runtime to collect and send log records.
+///
+/// [`BatchSpanProcessor`]: crate::trace::BatchSpanProcessor
+pub struct BatchLogProcessor<R: LogRuntime> {
+    message_sender: R::Sender,
+}
+
+impl<R: LogRuntime> fmt::Debug for BatchLogProcessor<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchLogProcessor")
+            .field("message_sender", &self.message_sender)
+            .finish()
+    }
+}
+
+impl<R: LogRuntime> LogProcessor for BatchLogProcessor<R> {
+    fn emit(&self, data: LogData) {
+        let result = self.message_sender.try_send(BatchMessage::ExportLog(data));
+
+        if let Err(err) = result {
+            global::handle_error(err);
+        }
+    }
+
+    fn force_flush(&self) -> LogResult<()> {
+        let (res_sender, res_receiver) = oneshot::channel();
+        self.message_sender
+            .try_send(BatchMessage::Flush(Some(res_sender)))?;
+
+        futures_executor::block_on(res_receiver)
+            .map_err(|err| LogError::Other(err.into()))
+            .and_then(std::convert::identity)
+    }
+
+    fn shutdown(&mut self) -> LogResult<()> {
+        let (res_sender, res_receiver) = oneshot::channel();
+        self.message_sender
+            .try_send(BatchMessage::Shutdown(res_sender))?;
+
+        futures_executor::block_on(res_receiver)
+            .map_err(|err| LogError::Other(err.into()))
+            .and_then(std::convert::identity)
+    }
+}
+
+impl<R: LogRuntime> BatchLogProcessor<R> {
+    pub(crate) fn new(mut exporter: Box<dyn LogExporter>, config: BatchConfig, runtime: R) -> Self {
+        let (message_sender, message_receiver) =
+            runtime.batch_message_channel(config.max_queue_size);
+        let ticker = runtime
+            .interval(config.scheduled_delay)
+            .map(|_| BatchMessage::Flush(None));
+        let timeout_runtime = runtime.clone();
+
+        // Spawn worker process via user-defined spawn function.
+        runtime.spawn(Box::pin(async move {
+            let mut logs = Vec::new();
+            let mut messages = Box::pin(stream::select(message_receiver, ticker));
+
+            while let Some(message) = messages.next().await {
+                match message {
+                    // Log has finished, add to buffer of pending logs.
+                    BatchMessage::ExportLog(log) => {
+                        logs.push(log);
+
+                        if logs.len() == config.max_export_batch_size {
+                            let result = export_with_timeout(
+                                config.max_export_timeout,
+                                exporter.as_mut(),
+                                &timeout_runtime,
+                                logs.split_off(0),
+                            )
+                            .await;
+
+                            if let Err(err) = result {
+                                global::handle_error(err);
+                            }
+                        }
+                    }
+                    // Log batch interval time reached or a force flush has been invoked, export current logs.
+                    BatchMessage::Flush(res_channel) => {
+                        let result = export_with_timeout(
+                            config.max_export_timeout,
+                            exporter.as_mut(),
+                            &timeout_runtime,
+                            logs.split_off(0),
+                        )
+                        .await;
+
+                        if let Some(channel) = res_channel {
+                            if let Err(result) = channel.send(result) {
+                                global::handle_error(LogError::from(format!(
+                                    "failed to send flush result: {:?}",
+                                    result
+                                )));
+                            }
+                        } else if let Err(err) = result {
+                            global::handle_error(err);
+                        }
+                    }
+                    // Stream has terminated or processor is shutdown, return to finish execution.
+                    BatchMessage::Shutdown(ch) => {
+                        let result = export_with_timeout(
+                            config.max_export_timeout,
+                            exporter.as_mut(),
+                            &timeout_runtime,
+                            logs.split_off(0),
+                        )
+                        .await;
+
+                        exporter.shutdown();
+
+                        if let Err(result) = ch.send(result) {
+                            global::handle_error(LogError::from(format!(
+                                "failed to send batch processor shutdown result: {:?}",
+                                result
+                            )));
+                        }
+
+                        break;
+                    }
+                }
+            }
+        }));
+
+        // Return batch processor with link to worker
+        BatchLogProcessor { message_sender }
+    }
+
+    /// Create a new batch processor builder
+    pub fn builder<E>(exporter: E, runtime: R) -> BatchLogProcessorBuilder<E, R>
+    where
+        E: LogExporter,
+    {
+        BatchLogProcessorBuilder {
+            exporter,
+            config: BatchConfig::default(),
+            runtime,
+        }
+    }
+}
+
+async fn export_with_timeout<R, E>(
+    time_out: Duration,
+    exporter: &mut E,
+    runtime: &R,
+    batch: Vec<LogData>,
+) -> ExportResult
+where
+    R: LogRuntime,
+    E: LogExporter + ?Sized,
+{
+    if batch.is_empty() {
+        return Ok(());
+    }
+
+    let export = exporter.export(batch);
+    let timeout = runtime.delay(time_out);
+    futures_util::pin_mut!(export);
+    futures_util::pin_mut!(timeout);
+    match future::select(export, timeout).await {
+        Either::Left((export_res, _)) => export_res,
+        Either::Right((_, _)) => ExportResult::Err(LogError::ExportTimedOut(time_out)),
+    }
+}
+
+/// Batch log processor configuration
+#[derive(Debug)]
+pub struct BatchConfig {
+    /// The maximum queue size to buffer logs for delayed processing. If the
+    /// queue gets full it drops the logs. The default value of is 2048.
+    max_queue_size: usize,
+
+    /// The delay interval in milliseconds between two consecutive processing
+    /// of batches. The default value is 1 second.
+    scheduled_delay: Duration,
+
+    /// The maximum number of logs to process in a single batch. If there are
+    /// more than one batch worth of logs then it processes multiple batches
+    /// of logs one batch after the other without any delay. The default value
+    /// is 512.
+    max_export_batch_size: usize,
+
+    /// The maximum duration to export a batch of data.
+    max_export_timeout: Duration,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        let mut config = BatchConfig {
+            max_queue_size: OTEL_BLRP_MAX_QUEUE_SIZE_DEFAULT,
+            scheduled_delay: Duration::from_millis(OTEL_BLRP_SCHEDULE_DELAY_DEFAULT),
+            max_export_batch_size: OTEL_BLRP_MAX_EXPORT_BATCH_SIZE_DEFAULT,
+            max_export_timeout: Duration::from_millis(OTEL_BLRP_EXPORT_TIMEOUT_DEFAULT),
+        };
+
+        if let Some(max_queue_size) = env::var(OTEL_BLRP_MAX_QUEUE_SIZE)
+            .ok()
+            .and_then(|queue_size| usize::from_str(&queue_size).ok())
+        {
+            config.max_queue_size = max_queue_size;
+        }
+
+        if let Some(scheduled_delay) = env::var(OTEL_BLRP_SCHEDULE_DELAY)
+            .ok()
+            .and_then(|delay| u64::from_str(&delay).ok())
+        {
+            config.scheduled_delay = Duration::from_millis(scheduled_delay);
+        }
+
+        if let Some(max_export_batch_size) = env::var(OTEL_BLRP_MAX_EXPORT_BATCH_SIZE)
+            .ok()
+            .and_then(|batch_size| usize::from_str(&batch_size).ok())
+        {
+            config.max_export_batch_size = max_export_batch_size;
+        }
+
+        // max export batch size must be less or equal to max queue size.
+        // we set max export batch size to max queue size if it's larger than max queue size.
+        if config.max_export_batch_size > config.max_queue_size {
+            config.max_export_batch_size = config.max_queue_size;
+        }
+
+        if let Some(max_export_timeout) = env::var(OTEL_BLRP_EXPORT_TIMEOUT)
+            .ok()
+            .and_then(|timeout| u64::from_str(&timeout).ok())
+        {
+            config.max_export_timeout = Duration::from_millis(max_export_timeout);
+        }
+
+        config
+    }
+}
+
+/// A builder for creating [`BatchLogProcessor`] instances.
+///
+#[derive(Debug)]
+pub struct BatchLogProcessorBuilder<E, R> {
+    exporter: E,
+    config: BatchConfig,
+    runtime: R,
+}
+
+impl<E, R> BatchLogProcessorBuilder<E, R>
+where
+    E: LogExporter + 'static,
+    R: LogRuntime,
+{
+    /// Set max queue size for batches
+    pub fn with_max_queue_size(self, size: usize) -> Self {
+        let mut config = self.config;
+        config.max_queue_size = size;
+
+        BatchLogProcessorBuilder { config, ..self }
+    }
+
+    /// Set scheduled delay for batches
+    pub fn with_scheduled_delay(self, delay: Duration) -> Self {
+        let mut config = self.config;
+        config.scheduled_delay = delay;
+
+        BatchLogProcessorBuilder { config, ..self }
+    }
+
+    /// Set max timeout for exporting.
+    pub fn with_max_timeout(self, timeout: Duration) -> Self {
+        let mut config = self.config;
+        config.max_export_timeout = timeout;
+
+        BatchLogProcessorBuilder { config, ..self }
+    }
+
+    /// Set max export size for batches, should always less than or equals to max queue size.
+    ///
+    /// If input is larger than max queue size, will lower it to be equal to max queue size
+    pub fn with_max_export_batch_size(self, size: usize) -> Self {
+        let mut config = self.config;
+        if size > config.max_queue_size {
+            config.max_export_batch_size = config.max_queue_size;
+        } else {
+            config.max_export_batch_size = size;
+        }
+
+        BatchLogProcessorBuilder { config, ..self }
+    }
+
+    /// Build a batch processor
+    pub fn build(self) -> BatchLogProcessor<R> {
+        BatchLogProcessor::new(Box::new(self.exporter), self.config, self.runtime)
+    }
+}
+
+/// Messages sent between application thread and batch log processor's work thread.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum BatchMessage {
+    /// Export logs, usually called when the log is emitted.
+    ExportLog(LogData),
+    /// Flush the current buffer to the backend, it can be triggered by
+    /// pre configured interval or a call to `force_push` function.
+    Flush(Option<oneshot::Sender<ExportResult>>),
+    /// Shut down the worker thread, push all logs in buffer to the backend.
+    Shutdown(oneshot::Sender<ExportResult>),
+}
+
+#[cfg(all(test, feature = "testing", feature = "logs"))]
+mod tests {
+    use super::{
+        BatchLogProcessor, LogProcessor, SimpleLogProcessor, OTEL_BLRP_MAX_QUEUE_SIZE_DEFAULT,
+        OTEL_BLRP_SCHEDULE_DELAY_DEFAULT,
+    };
+    use crate::export::logs::{ExportResult, LogData, LogExporter};
+    use crate::runtime;
+    use crate::{InstrumentationLibrary, Resource};
+    use futures_util::future::BoxFuture;
+    use opentelemetry_api::logs::LogRecord;
+    use std::borrow::Cow;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Default)]
+    struct InMemoryLogExporter {
+        logs: Arc<Mutex<Vec<LogData>>>,
+        shutdown_called: Arc<Mutex<bool>>,
+    }
+
+    impl LogExporter for InMemoryLogExporter {
+        fn export(&mut self, batch: Vec<LogData>) -> BoxFuture<'static, ExportResult> {
+            self.logs.lock().unwrap().extend(batch);
+            Box::pin(std::future::ready(Ok(())))
+        }
+
+        fn shutdown(&mut self) {
+            *self.shutdown_called.lock().unwrap() = true;
+        }
+    }
+
+    fn new_test_log_data(body: &'static str) -> LogData {
+        LogData {
+            record: LogRecord::builder().with_body(body.into()).build(),
+            resource: Cow::Owned(Resource::empty()),
+            instrumentation: InstrumentationLibrary::default(),
+        }
+    }
+
+    #[test]
+    fn test_default_batch_config() {
+        let config = super::BatchConfig::default();
+        assert_eq!(config.max_queue_size, OTEL_BLRP_MAX_QUEUE_SIZE_DEFAULT);
+        assert_eq!(
+            config.scheduled_delay,
+            Duration::from_millis(OTEL_BLRP_SCHEDULE_DELAY_DEFAULT)
+        );
+    }
+
+    #[test]
+    fn simple_log_processor_emit_calls_export() {
+        let exporter = InMemoryLogExporter::default();
+        let mut processor = SimpleLogProcessor::new(Box::new(exporter.clone()));
+
+        processor.emit(new_test_log_data("first"));
+        processor.shutdown().unwrap();
+
+        assert_eq!(exporter.logs.lock().unwrap().len(), 1);
+        assert!(*exporter.shutdown_called.lock().unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn batch_log_processor_exports_on_flush_and_shutdown() {
+        let exporter = InMemoryLogExporter::default();
+        let mut processor = BatchLogProcessor::builder(exporter.clone(), runtime::Tokio)
+            .with_scheduled_delay(Duration::from_secs(60 * 60))
+            .build();
+
+        processor.emit(new_test_log_data("first"));
+        processor.force_flush().unwrap();
+        assert_eq!(exporter.logs.lock().unwrap().len(), 1);
+
+        processor.emit(new_test_log_data("second"));
+        processor.shutdown().unwrap();
+        assert_eq!(exporter.logs.lock().unwrap().len(), 2);
+        assert!(*exporter.shutdown_called.lock().unwrap());
+    }
+}
@@ -0,0 +1,20 @@
+//! # OpenTelemetry Log SDK
+//!
+//! The log SDK provides [`LoggerProvider`] and [`Logger`] implementations of
+//! the logs API that hand emitted records to registered [`LogProcessor`]s,
+//! which in turn export them through a [`LogExporter`].
+//!
+//! [`LogExporter`]: crate::export::logs::LogExporter
+
+mod config;
+mod log_emitter;
+mod log_processor;
+mod runtime;
+
+pub use config::{config, Config};
+pub use log_emitter::{Builder, Logger, LoggerProvider};
+pub use log_processor::{
+    BatchConfig, BatchLogProcessor, BatchLogProcessorBuilder, BatchMessage, LogProcessor,
+    SimpleLogProcessor,
+};
+pub use runtime::{LogRuntime, TrySend};
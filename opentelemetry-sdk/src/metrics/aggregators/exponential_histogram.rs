@@ -0,0 +1,511 @@
+use crate::export::metrics::aggregation::{
+    Aggregation, AggregationKind, Count, ExponentialBuckets, ExponentialHistogram, Sum,
+};
+use crate::metrics::{
+    aggregators::Aggregator,
+    sdk_api::{AtomicNumber, Descriptor, Number, NumberKind},
+};
+use opentelemetry_api::metrics::{MetricsError, Result};
+use opentelemetry_api::Context;
+use std::mem;
+use std::sync::{Arc, RwLock};
+
+/// The default maximum number of buckets for the positive and the negative
+/// values.
+pub const DEFAULT_MAX_SIZE: u32 = 160;
+
+/// The default scale the histogram starts recording at.
+pub const DEFAULT_MAX_SCALE: i8 = 20;
+
+/// The highest supported scale, higher scales are clamped to it.
+const MAX_SCALE: i8 = 20;
+
+/// The lowest supported scale, all the finite `f64` values fit in a few buckets
+/// at this scale.
+const MIN_SCALE: i8 = -10;
+
+/// Create a new exponential histogram keeping at most `max_size` buckets for
+/// the positive and the negative values, starting at scale `max_scale`.
+///
+/// `max_size` is raised to 2 and `max_scale` is clamped to `[-10, 20]`.
+pub fn exponential_histogram(max_size: u32, max_scale: i8) -> ExponentialHistogramAggregator {
+    let max_scale = max_scale.clamp(MIN_SCALE, MAX_SCALE);
+
+    ExponentialHistogramAggregator {
+        max_size: max_size.max(2),
+        max_scale,
+        state: RwLock::new(State::empty(max_scale)),
+    }
+}
+
+/// This aggregator observes events and counts them in base-2 exponential
+/// buckets. It also calculates the sum and count of all events.
+///
+/// The histogram starts at its maximum scale and lowers it whenever the
+/// recorded values would need more than the maximum number of buckets, so the
+/// resolution adapts to the range of the values without choosing boundaries up
+/// front.
+#[derive(Debug)]
+pub struct ExponentialHistogramAggregator {
+    max_size: u32,
+    max_scale: i8,
+    state: RwLock<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    count: u64,
+    sum: AtomicNumber,
+    zero_count: u64,
+    scale: i8,
+    positive: Bins,
+    negative: Bins,
+}
+
+impl State {
+    fn empty(scale: i8) -> Self {
+        State {
+            count: 0,
+            sum: NumberKind::U64.zero().to_atomic(),
+            zero_count: 0,
+            scale,
+            positive: Bins::default(),
+            negative: Bins::default(),
+        }
+    }
+
+    fn record(&mut self, value: f64, max_size: u32) {
+        if value == 0.0 {
+            self.zero_count += 1;
+            return;
+        }
+
+        let abs = value.abs();
+        let mut bin = bin_index(abs, self.scale);
+        let bins = if value > 0.0 {
+            &self.positive
+        } else {
+            &self.negative
+        };
+        let change = match bins.range() {
+            Some((low, high)) => scale_change(low.min(bin), high.max(bin), max_size, self.scale),
+            None => 0,
+        };
+        if change > 0 {
+            self.downscale(change);
+            bin = bin_index(abs, self.scale);
+        }
+
+        if value > 0.0 {
+            self.positive.increment(bin, 1);
+        } else {
+            self.negative.increment(bin, 1);
+        }
+    }
+
+    fn downscale(&mut self, change: i8) {
+        self.positive.downscale(change);
+        self.negative.downscale(change);
+        self.scale -= change;
+    }
+
+    fn merge(&mut self, other: &State, kind: &NumberKind, max_size: u32) {
+        self.count += other.count;
+        self.sum.fetch_add(kind, &other.sum.load());
+        self.zero_count += other.zero_count;
+
+        // Use the finest scale at which the buckets of both histograms fit.
+        let mut scale = self.scale.min(other.scale);
+        let change = [
+            (&self.positive, &other.positive),
+            (&self.negative, &other.negative),
+        ]
+        .iter()
+        .map(|(bins, other_bins)| {
+            let ranges = [
+                bins.range_at(self.scale - scale),
+                other_bins.range_at(other.scale - scale),
+            ];
+            let low = ranges.iter().flatten().map(|range| range.0).min();
+            let high = ranges.iter().flatten().map(|range| range.1).max();
+            match low.zip(high) {
+                Some((low, high)) => scale_change(low, high, max_size, scale),
+                None => 0,
+            }
+        })
+        .max()
+        .unwrap_or(0);
+        scale -= change;
+
+        self.downscale(self.scale - scale);
+        let other_change = other.scale - scale;
+        for (bins, other_bins) in [
+            (&mut self.positive, &other.positive),
+            (&mut self.negative, &other.negative),
+        ] {
+            for (bin, count) in other_bins.iter() {
+                bins.increment(bin >> other_change, count);
+            }
+        }
+    }
+}
+
+/// Counts of a contiguous range of buckets.
+#[derive(Debug, Default)]
+struct Bins {
+    start: i32,
+    counts: Vec<u64>,
+}
+
+impl Bins {
+    /// The first and last bucket indices, `None` if no value was recorded.
+    fn range(&self) -> Option<(i32, i32)> {
+        if self.counts.is_empty() {
+            None
+        } else {
+            Some((self.start, self.start + self.counts.len() as i32 - 1))
+        }
+    }
+
+    /// The range the buckets would cover after lowering their scale by `change`.
+    fn range_at(&self, change: i8) -> Option<(i32, i32)> {
+        self.range()
+            .map(|(low, high)| (low >> change, high >> change))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (i32, u64)> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(move |(idx, count)| (self.start + idx as i32, *count))
+    }
+
+    fn increment(&mut self, bin: i32, count: u64) {
+        match self.range() {
+            None => {
+                self.start = bin;
+                self.counts.push(count);
+                return;
+            }
+            Some((low, _)) if bin < low => {
+                self.counts
+                    .splice(0..0, std::iter::repeat(0).take((low - bin) as usize));
+                self.start = bin;
+            }
+            Some((_, high)) if bin > high => {
+                self.counts
+                    .resize(self.counts.len() + (bin - high) as usize, 0);
+            }
+            _ => (),
+        }
+        self.counts[(bin - self.start) as usize] += count;
+    }
+
+    /// Merge the buckets so they cover the ranges of a scale lowered by
+    /// `change`, each bucket is merged with its `2^change - 1` neighbours.
+    fn downscale(&mut self, change: i8) {
+        if change == 0 {
+            return;
+        }
+
+        let counts = mem::take(&mut self.counts);
+        let start = self.start;
+        self.start >>= change;
+        for (idx, count) in counts.into_iter().enumerate() {
+            self.increment((start + idx as i32) >> change, count);
+        }
+    }
+}
+
+/// The number of times the scale must be lowered for the bucket indices
+/// `low..=high` to fit in `max_size` buckets, without going below the minimum
+/// scale.
+fn scale_change(mut low: i32, mut high: i32, max_size: u32, scale: i8) -> i8 {
+    let mut change = 0;
+    while i64::from(high) - i64::from(low) >= i64::from(max_size) && scale - change > MIN_SCALE {
+        low >>= 1;
+        high >>= 1;
+        change += 1;
+    }
+    change
+}
+
+/// The index of the bucket containing the positive finite value `value` at
+/// `scale`. The bucket `i` contains the values in
+/// `(2^(i * 2^-scale), 2^((i + 1) * 2^-scale)]`.
+fn bin_index(value: f64, scale: i8) -> i32 {
+    let (frac, exp) = frexp(value);
+    // Exact powers of two are the upper boundary of their bucket.
+    let exact = frac == 0.5;
+    if scale <= 0 {
+        let exp = if exact { exp - 2 } else { exp - 1 };
+        exp >> -scale
+    } else if exact {
+        ((exp - 1) << scale) - 1
+    } else {
+        let scale_factor = f64::from(1u32 << scale) * std::f64::consts::LOG2_E;
+        (exp << scale) + (frac.ln() * scale_factor).floor() as i32
+    }
+}
+
+/// Split a positive finite value into a fraction in `[0.5, 1)` and a power of
+/// two, such that `value = frac * 2^exp`.
+fn frexp(value: f64) -> (f64, i32) {
+    const EXP_MASK: u64 = 0x7ff << 52;
+
+    let bits = value.to_bits();
+    let exp = ((bits & EXP_MASK) >> 52) as i32;
+    if exp == 0 {
+        // subnormal values are scaled into the normal range first
+        let (frac, exp) = frexp(value * 2f64.powi(64));
+        return (frac, exp - 64);
+    }
+
+    (
+        f64::from_bits((bits & !EXP_MASK) | (1022 << 52)),
+        exp - 1022,
+    )
+}
+
+impl Sum for ExponentialHistogramAggregator {
+    fn sum(&self) -> Result<Number> {
+        self.state
+            .read()
+            .map_err(From::from)
+            .map(|state| state.sum.load())
+    }
+}
+
+impl Count for ExponentialHistogramAggregator {
+    fn count(&self) -> Result<u64> {
+        self.state
+            .read()
+            .map_err(From::from)
+            .map(|state| state.count)
+    }
+}
+
+impl ExponentialHistogram for ExponentialHistogramAggregator {
+    fn scale(&self) -> Result<i8> {
+        self.state
+            .read()
+            .map_err(From::from)
+            .map(|state| state.scale)
+    }
+
+    fn zero_count(&self) -> Result<u64> {
+        self.state
+            .read()
+            .map_err(From::from)
+            .map(|state| state.zero_count)
+    }
+
+    fn positive(&self) -> Result<ExponentialBuckets> {
+        self.state.read().map_err(From::from).map(|state| {
+            ExponentialBuckets::new(state.positive.start, state.positive.counts.clone())
+        })
+    }
+
+    fn negative(&self) -> Result<ExponentialBuckets> {
+        self.state.read().map_err(From::from).map(|state| {
+            ExponentialBuckets::new(state.negative.start, state.negative.counts.clone())
+        })
+    }
+}
+
+impl Aggregation for ExponentialHistogramAggregator {
+    fn kind(&self) -> &AggregationKind {
+        &AggregationKind::EXPONENTIAL_HISTOGRAM
+    }
+}
+
+impl Aggregator for ExponentialHistogramAggregator {
+    fn aggregation(&self) -> &dyn Aggregation {
+        self
+    }
+
+    fn update(&self, _cx: &Context, number: &Number, descriptor: &Descriptor) -> Result<()> {
+        let kind = descriptor.number_kind();
+        let value = number.to_f64(kind);
+        if !value.is_finite() {
+            // infinities have no bucket, NaN is rejected by the range test
+            return Ok(());
+        }
+
+        self.state.write().map_err(From::from).map(|mut state| {
+            state.record(value, self.max_size);
+            state.count += 1;
+            state.sum.fetch_add(kind, number);
+        })
+    }
+
+    fn synchronized_move(
+        &self,
+        other: &Arc<dyn Aggregator + Send + Sync>,
+        _descriptor: &Descriptor,
+    ) -> Result<()> {
+        if let Some(other) = other.as_any().downcast_ref::<Self>() {
+            self.state
+                .write()
+                .map_err(From::from)
+                .and_then(|mut state| {
+                    other.state.write().map_err(From::from).map(|mut other| {
+                        let empty = State::empty(self.max_scale);
+                        *other = mem::replace(&mut *state, empty)
+                    })
+                })
+        } else {
+            Err(MetricsError::InconsistentAggregator(format!(
+                "Expected {:?}, got: {:?}",
+                self, other
+            )))
+        }
+    }
+
+    fn merge(&self, other: &(dyn Aggregator + Send + Sync), desc: &Descriptor) -> Result<()> {
+        if let Some(other) = other.as_any().downcast_ref::<Self>() {
+            self.state
+                .write()
+                .map_err(From::from)
+                .and_then(|mut state| {
+                    other
+                        .state
+                        .read()
+                        .map_err(From::from)
+                        .map(|other| state.merge(&other, desc.number_kind(), self.max_size))
+                })
+        } else {
+            Err(MetricsError::InconsistentAggregator(format!(
+                "Expected {:?}, got: {:?}",
+                self, other
+            )))
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::sdk_api::InstrumentKind;
+
+    fn descriptor() -> Descriptor {
+        Descriptor::new(
+            "test".to_string(),
+            InstrumentKind::Histogram,
+            NumberKind::F64,
+            None,
+            None,
+        )
+    }
+
+    fn record(aggregator: &ExponentialHistogramAggregator, values: &[f64]) {
+        let cx = Context::new();
+        for value in values {
+            aggregator
+                .update(&cx, &Number::from(*value), &descriptor())
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_bin_index() {
+        // buckets include their upper boundary
+        assert_eq!(bin_index(1.0, 0), -1);
+        assert_eq!(bin_index(1.5, 0), 0);
+        assert_eq!(bin_index(2.0, 0), 0);
+        assert_eq!(bin_index(3.0, 0), 1);
+        assert_eq!(bin_index(4.0, -1), 0);
+        assert_eq!(bin_index(5.0, -1), 1);
+        assert_eq!(bin_index(2.0, 2), 3);
+        assert_eq!(bin_index(2.1, 2), 4);
+        assert_eq!(bin_index(0.25, 20), -(2 << 20) - 1);
+        assert_eq!(bin_index(f64::MIN_POSITIVE / 4.0, 0), -1025);
+        assert_eq!(bin_index(f64::MAX, MIN_SCALE), 0);
+    }
+
+    #[test]
+    fn test_downscales_to_max_size() -> Result<()> {
+        let aggregator = exponential_histogram(4, DEFAULT_MAX_SCALE);
+        record(&aggregator, &[1.0, 2.0]);
+        assert_eq!(aggregator.scale()?, 1);
+        assert_eq!(
+            aggregator.positive()?,
+            ExponentialBuckets::new(-1, vec![1, 0, 1])
+        );
+
+        record(&aggregator, &[4.0]);
+        assert_eq!(aggregator.scale()?, 0);
+        assert_eq!(
+            aggregator.positive()?,
+            ExponentialBuckets::new(-1, vec![1, 1, 1])
+        );
+        assert_eq!(aggregator.count()?, 3);
+        assert_eq!(aggregator.sum()?.to_f64(&NumberKind::F64), 7.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zero_and_negative_values() -> Result<()> {
+        let aggregator = exponential_histogram(DEFAULT_MAX_SIZE, 0);
+        record(&aggregator, &[0.0, -1.0, 0.0, -3.0, 3.0]);
+
+        assert_eq!(aggregator.zero_count()?, 2);
+        assert_eq!(
+            aggregator.negative()?,
+            ExponentialBuckets::new(-1, vec![1, 0, 1])
+        );
+        assert_eq!(aggregator.positive()?, ExponentialBuckets::new(1, vec![1]));
+        assert_eq!(aggregator.count()?, 5);
+        assert_eq!(aggregator.sum()?.to_f64(&NumberKind::F64), -1.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge() -> Result<()> {
+        let descriptor = descriptor();
+        let fine = exponential_histogram(4, 2);
+        record(&fine, &[1.0, 1.5]);
+        let coarse = exponential_histogram(4, 2);
+        record(&coarse, &[0.5, 4.0]);
+        assert_eq!(fine.scale()?, 2);
+        assert_eq!(coarse.scale()?, 0);
+
+        fine.merge(&coarse, &descriptor)?;
+        // the range of 0.5 to 4 needs 4 buckets at scale 0
+        assert_eq!(fine.scale()?, 0);
+        assert_eq!(
+            fine.positive()?,
+            ExponentialBuckets::new(-2, vec![1, 1, 1, 1])
+        );
+        assert_eq!(fine.count()?, 4);
+        assert_eq!(fine.sum()?.to_f64(&NumberKind::F64), 7.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_synchronized_move_resets_scale() -> Result<()> {
+        let descriptor = descriptor();
+        let aggregator = exponential_histogram(2, 3);
+        record(&aggregator, &[1.0, 100.0]);
+        assert!(aggregator.scale()? < 3);
+
+        let checkpoint: Arc<dyn Aggregator + Send + Sync> = Arc::new(exponential_histogram(2, 3));
+        aggregator.synchronized_move(&checkpoint, &descriptor)?;
+        let checkpoint = checkpoint
+            .as_any()
+            .downcast_ref::<ExponentialHistogramAggregator>()
+            .unwrap();
+        assert_eq!(checkpoint.count()?, 2);
+        assert_eq!(aggregator.count()?, 0);
+        assert_eq!(aggregator.scale()?, 3);
+
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::export::metrics::aggregation::{
-    Aggregation, AggregationKind, Buckets, Count, Histogram, Sum,
+    Aggregation, AggregationKind, Buckets, Count, Histogram, MinMax, Sum,
 };
 use crate::metrics::{
     aggregators::Aggregator,
@@ -7,6 +7,7 @@ use crate::metrics::{
 };
 use opentelemetry_api::metrics::{MetricsError, Result};
 use opentelemetry_api::Context;
+use std::cmp::Ordering;
 use std::mem;
 use std::sync::{Arc, RwLock};
 
@@ -25,7 +26,7 @@ pub fn histogram(boundaries: &[f64]) -> HistogramAggregator {
 }
 
 /// This aggregator observes events and counts them in pre-determined buckets. It
-/// also calculates the sum and count of all events.
+/// also calculates the sum, count, minimum and maximum of all events.
 #[derive(Debug)]
 pub struct HistogramAggregator {
     inner: RwLock<Inner>,
@@ -42,6 +43,8 @@ struct State {
     bucket_counts: Vec<f64>,
     count: AtomicNumber,
     sum: AtomicNumber,
+    min: Option<Number>,
+    max: Option<Number>,
 }
 
 impl State {
@@ -50,6 +53,25 @@ impl State {
             bucket_counts: vec![0.0; boundaries.len() + 1],
             count: NumberKind::U64.zero().to_atomic(),
             sum: NumberKind::U64.zero().to_atomic(),
+            min: None,
+            max: None,
+        }
+    }
+
+    fn update_min_max(&mut self, kind: &NumberKind, min: &Number, max: &Number) {
+        // NaN compares to nothing, it would never be replaced once recorded
+        if kind == &NumberKind::F64 && (min.is_nan() || max.is_nan()) {
+            return;
+        }
+        if self.min.as_ref().map_or(true, |current| {
+            min.partial_cmp(kind, current) == Some(Ordering::Less)
+        }) {
+            self.min = Some(min.clone());
+        }
+        if self.max.as_ref().map_or(true, |current| {
+            max.partial_cmp(kind, current) == Some(Ordering::Greater)
+        }) {
+            self.max = Some(max.clone());
         }
     }
 }
@@ -81,6 +103,22 @@ impl Histogram for HistogramAggregator {
     }
 }
 
+impl MinMax for HistogramAggregator {
+    fn min(&self) -> Result<Number> {
+        self.inner
+            .read()
+            .map_err(From::from)
+            .and_then(|inner| inner.state.min.clone().ok_or(MetricsError::NoDataCollected))
+    }
+
+    fn max(&self) -> Result<Number> {
+        self.inner
+            .read()
+            .map_err(From::from)
+            .and_then(|inner| inner.state.max.clone().ok_or(MetricsError::NoDataCollected))
+    }
+}
+
 impl Aggregation for HistogramAggregator {
     fn kind(&self) -> &AggregationKind {
         &AggregationKind::HISTOGRAM
@@ -107,6 +145,7 @@ impl Aggregator for HistogramAggregator {
             inner.state.count.fetch_add(&NumberKind::U64, &1u64.into());
             inner.state.sum.fetch_add(kind, number);
             inner.state.bucket_counts[bucket_id] += 1.0;
+            inner.state.update_min_max(kind, number, number);
         })
     }
 
@@ -152,6 +191,10 @@ impl Aggregator for HistogramAggregator {
                         for idx in 0..inner.state.bucket_counts.len() {
                             inner.state.bucket_counts[idx] += other.state.bucket_counts[idx];
                         }
+
+                        if let (Some(min), Some(max)) = (&other.state.min, &other.state.max) {
+                            inner.state.update_min_max(desc.number_kind(), min, max);
+                        }
                     })
                 })
         } else {
@@ -166,3 +209,71 @@ impl Aggregator for HistogramAggregator {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::sdk_api::{Descriptor, InstrumentKind};
+
+    #[test]
+    fn test_min_max() -> Result<()> {
+        let cx = Context::new();
+        let descriptor = Descriptor::new(
+            "test".to_string(),
+            InstrumentKind::Histogram,
+            NumberKind::I64,
+            None,
+            None,
+        );
+        let current = histogram(&[0.0, 10.0]);
+        assert!(matches!(current.min(), Err(MetricsError::NoDataCollected)));
+
+        for value in [3i64, -2, 15] {
+            current.update(&cx, &value.into(), &descriptor)?;
+        }
+        assert_eq!(current.min()?.to_i64(&NumberKind::I64), -2);
+        assert_eq!(current.max()?.to_i64(&NumberKind::I64), 15);
+
+        // moving the state resets it
+        let checkpoint: Arc<dyn Aggregator + Send + Sync> = Arc::new(histogram(&[0.0, 10.0]));
+        current.synchronized_move(&checkpoint, &descriptor)?;
+        assert!(matches!(current.max(), Err(MetricsError::NoDataCollected)));
+
+        // merging keeps the extremes of both histograms
+        current.update(&cx, &20i64.into(), &descriptor)?;
+        current.merge(checkpoint.as_ref(), &descriptor)?;
+        assert_eq!(current.min()?.to_i64(&NumberKind::I64), -2);
+        assert_eq!(current.max()?.to_i64(&NumberKind::I64), 20);
+        assert_eq!(current.count()?, 4);
+
+        // merging an empty histogram changes nothing
+        current.merge(&histogram(&[0.0, 10.0]), &descriptor)?;
+        assert_eq!(current.min()?.to_i64(&NumberKind::I64), -2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_min_max_skips_nan() -> Result<()> {
+        let cx = Context::new();
+        let descriptor = Descriptor::new(
+            "test".to_string(),
+            InstrumentKind::Histogram,
+            NumberKind::F64,
+            None,
+            None,
+        );
+        let current = histogram(&[0.0, 10.0]);
+
+        current.update(&cx, &f64::NAN.into(), &descriptor)?;
+        assert!(matches!(current.min(), Err(MetricsError::NoDataCollected)));
+
+        for value in [3.0, f64::NAN, -2.0, 15.0] {
+            current.update(&cx, &value.into(), &descriptor)?;
+        }
+        assert_eq!(current.min()?.to_f64(&NumberKind::F64), -2.0);
+        assert_eq!(current.max()?.to_f64(&NumberKind::F64), 15.0);
+
+        Ok(())
+    }
+}
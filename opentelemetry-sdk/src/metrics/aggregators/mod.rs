@@ -11,10 +11,14 @@ use opentelemetry_api::{
     Context,
 };
 
+mod exponential_histogram;
 mod histogram;
 mod last_value;
 mod sum;
 
+pub use exponential_histogram::{
+    exponential_histogram, ExponentialHistogramAggregator, DEFAULT_MAX_SCALE, DEFAULT_MAX_SIZE,
+};
 pub use histogram::{histogram, HistogramAggregator};
 pub use last_value::{last_value, LastValueAggregator};
 pub use sum::{sum, SumAggregator};
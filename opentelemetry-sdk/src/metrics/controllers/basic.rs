@@ -1,4 +1,5 @@
 use std::{
+    any::Any,
     collections::HashMap,
     fmt,
     sync::{Arc, Mutex},
@@ -6,11 +7,14 @@ use std::{
 };
 
 use futures_channel::{mpsc, oneshot};
-use futures_util::{stream, StreamExt};
+use futures_util::{
+    future::{self, Either},
+    stream, StreamExt,
+};
 use opentelemetry_api::{
     global,
     metrics::{noop, Meter, MeterProvider, MetricsError, Result},
-    Context, InstrumentationLibrary,
+    Context, InstrumentationLibrary, KeyValue,
 };
 
 use crate::{
@@ -19,12 +23,15 @@ use crate::{
         MetricsExporter, Reader,
     },
     metrics::{
-        accumulator,
+        accumulator_with_views,
+        exemplar::ExemplarFilter,
         registry::{self, UniqueInstrumentMeterCore},
         sdk_api::{
-            wrap_meter_core, AsyncInstrumentCore, Descriptor, MeterCore, SyncInstrumentCore,
+            wrap_meter_core, AsyncInstrumentCore, Descriptor, InstrumentCore, MeterCore, Number,
+            SyncInstrumentCore,
         },
-        Accumulator,
+        view::View,
+        Accumulator, DEFAULT_CARDINALITY_LIMIT,
     },
     runtime::Runtime,
     Resource,
@@ -52,6 +59,10 @@ where
         collect_period: None,
         collect_timeout: None,
         push_timeout: None,
+        views: Vec::new(),
+        exemplar_filter: ExemplarFilter::default(),
+        cardinality_limit: None,
+        readers: Vec::new(),
     }
 }
 
@@ -68,6 +79,10 @@ where
 ///
 /// The controller supports mixing push and pull access to metric data using the
 /// `InstrumentationLibraryReader` interface.
+///
+/// Other controllers can be attached as readers with
+/// [`BasicControllerBuilder::with_reader`], to export the same instruments with
+/// another temporality, exporter or collection period.
 #[derive(Clone)]
 pub struct BasicController(Arc<ControllerInner>);
 
@@ -81,11 +96,15 @@ struct ControllerInner {
     collect_timeout: Duration,
     push_timeout: Duration,
     collected_time: Mutex<Option<SystemTime>>,
+    views: Vec<View>,
+    exemplar_filter: ExemplarFilter,
+    cardinality_limit: usize,
+    readers: Vec<BasicController>,
 }
 
 enum WorkerMessage {
     Tick,
-    Shutdown((Context, oneshot::Sender<()>)),
+    Shutdown((Context, oneshot::Sender<Result<()>>)),
 }
 
 impl BasicController {
@@ -102,7 +121,15 @@ impl BasicController {
     ///
     /// Note that it is not necessary to start a controller when only pulling data;
     /// use the `collect` and `try_for_each` methods directly in this case.
+    ///
+    /// The readers of this controller that have an exporter are started as well.
     pub fn start<T: Runtime>(&self, cx: &Context, rt: T) -> Result<()> {
+        for reader in &self.0.readers {
+            if reader.0.exporter.lock()?.is_some() {
+                reader.start(cx, rt.clone())?;
+            }
+        }
+
         let (message_sender, message_receiver) = mpsc::channel(8);
         let ticker = rt
             .interval(self.0.collect_period)
@@ -117,6 +144,8 @@ impl BasicController {
         let resource = self.resource().clone();
         let reader = self.clone();
         let cx = cx.clone();
+        let push_timeout = self.0.push_timeout;
+        let delay_rt = rt.clone();
         // Spawn worker process via user-defined spawn function.
         rt.spawn(Box::pin(async move {
             let mut messages = Box::pin(stream::select(message_receiver, ticker));
@@ -136,11 +165,30 @@ impl BasicController {
                         };
                     }
                     WorkerMessage::Shutdown((cx, channel)) => {
-                        let _ = reader.checkpoint(&cx);
+                        let mut result = reader.checkpoint(&cx);
                         if let Some(exporter) = &exporter {
-                            let _ = exporter.export(&cx, &resource, &reader);
+                            if result.is_ok() {
+                                result = exporter.export(&cx, &resource, &reader);
+                            }
+                            // Wait for the exports still in flight, even if the last
+                            // one failed.
+                            let shutdown = exporter.shutdown();
+                            let shutdown_result = if push_timeout == Duration::ZERO {
+                                shutdown.await
+                            } else {
+                                match future::select(shutdown, delay_rt.delay(push_timeout))
+                                    .await
+                                {
+                                    Either::Left((res, _)) => res,
+                                    Either::Right(_) => Err(MetricsError::Other(format!(
+                                        "timed out after {:?} waiting for the exporter to shut down",
+                                        push_timeout
+                                    ))),
+                                }
+                            };
+                            result = result.and(shutdown_result);
                         }
-                        let _ = channel.send(());
+                        let _ = channel.send(result);
                         break;
                     }
                 }
@@ -158,8 +206,21 @@ impl BasicController {
     /// The passed context is passed to the final `collect` and subsequently to the
     /// final asynchronous instruments.
     ///
+    /// Once the final export is done, the exporter is shut down and `stop` waits
+    /// up to the push timeout (see [`BasicControllerBuilder::with_push_timeout`])
+    /// for the exports still in flight. An error is returned if the final
+    /// collection or export fails, or if the exporter doesn't shut down in time.
+    ///
+    /// The readers of this controller are stopped as well.
+    ///
     /// Note that `stop` will not cancel an ongoing collection or export.
     pub fn stop(&self, cx: &Context) -> Result<()> {
+        // stop all the readers even if some of them fail
+        let mut readers_result = Ok(());
+        for reader in &self.0.readers {
+            readers_result = readers_result.and(reader.stop(cx));
+        }
+
         self.0
             .worker_channel
             .lock()
@@ -173,6 +234,7 @@ impl BasicController {
                     {
                         futures_executor::block_on(res_receiver)
                             .map_err(|err| MetricsError::Other(err.to_string()))
+                            .and_then(|result| result)
                     } else {
                         Ok(())
                     }
@@ -180,6 +242,7 @@ impl BasicController {
                     Ok(())
                 }
             })
+            .and(readers_result)
     }
 
     /// true if the controller was started via `start`, indicating that the
@@ -300,26 +363,53 @@ impl MeterProvider for BasicController {
         version: Option<&'static str>,
         schema_url: Option<&'static str>,
     ) -> Meter {
-        self.0
-            .meters
-            .lock()
-            .map(|mut meters| {
-                let library = InstrumentationLibrary::new(name, version, schema_url);
-                let meter_core = meters.entry(library.clone()).or_insert_with(|| {
+        let library = InstrumentationLibrary::new(name, version, schema_url);
+        let meter_core = self.meter_core(&library).and_then(|meter_core| {
+            if self.0.readers.is_empty() {
+                return Ok(meter_core as Arc<dyn MeterCore + Send + Sync>);
+            }
+
+            let mut cores = vec![meter_core];
+            for reader in &self.0.readers {
+                cores.push(reader.meter_core(&library)?);
+            }
+            Ok(Arc::new(ReadersMeterCore { cores }) as Arc<dyn MeterCore + Send + Sync>)
+        });
+
+        match meter_core {
+            Ok(meter_core) => wrap_meter_core(meter_core, library),
+            Err(_) => noop::NoopMeterProvider::new().versioned_meter(name, version, schema_url),
+        }
+    }
+}
+
+impl BasicController {
+    /// The meter core of the accumulator of `library` in this controller.
+    fn meter_core(
+        &self,
+        library: &InstrumentationLibrary,
+    ) -> Result<Arc<UniqueInstrumentMeterCore>> {
+        self.0.meters.lock().map_err(Into::into).map(|mut meters| {
+            meters
+                .entry(library.clone())
+                .or_insert_with(|| {
                     let checkpointer = self.0.checkpointer_factory.checkpointer();
                     Arc::new(registry::unique_instrument_meter_core(
                         AccumulatorCheckpointer {
-                            accumulator: accumulator(checkpointer.clone().as_dyn_processor()),
+                            accumulator: accumulator_with_views(
+                                checkpointer.clone().as_dyn_processor(),
+                                library,
+                                &self.0.views,
+                                self.0.exemplar_filter,
+                                self.0.cardinality_limit,
+                            ),
                             checkpointer,
                             library: library.clone(),
                         },
                     ))
-                });
-                wrap_meter_core(meter_core.clone(), library)
-            })
-            .unwrap_or_else(|_| {
-                noop::NoopMeterProvider::new().versioned_meter(name, version, schema_url)
-            })
+                })
+                .clone()
+        })
     }
 }
 
@@ -349,6 +439,104 @@ impl MeterCore for AccumulatorCheckpointer {
     }
 }
 
+/// Creates the instruments of a meter in the accumulators of a controller and
+/// of its readers, so that each measurement is recorded by all of them.
+struct ReadersMeterCore {
+    cores: Vec<Arc<UniqueInstrumentMeterCore>>,
+}
+
+impl MeterCore for ReadersMeterCore {
+    fn new_sync_instrument(
+        &self,
+        descriptor: Descriptor,
+    ) -> Result<Arc<dyn SyncInstrumentCore + Send + Sync>> {
+        let instruments = self
+            .cores
+            .iter()
+            .map(|core| core.new_sync_instrument(descriptor.clone()))
+            .collect::<Result<_>>()?;
+
+        Ok(Arc::new(ReadersSyncInstrument {
+            descriptor,
+            instruments,
+        }))
+    }
+
+    fn new_async_instrument(
+        &self,
+        descriptor: Descriptor,
+    ) -> Result<Arc<dyn AsyncInstrumentCore + Send + Sync>> {
+        let instruments = self
+            .cores
+            .iter()
+            .map(|core| core.new_async_instrument(descriptor.clone()))
+            .collect::<Result<_>>()?;
+
+        Ok(Arc::new(ReadersAsyncInstrument {
+            descriptor,
+            instruments,
+        }))
+    }
+
+    fn register_callback(&self, f: Box<dyn Fn(&Context) + Send + Sync>) -> Result<()> {
+        // Each accumulator runs the callback when it collects, the observations
+        // are only recorded by the collecting accumulator.
+        let f: Arc<dyn Fn(&Context) + Send + Sync> = Arc::from(f);
+        for core in &self.cores {
+            let f = f.clone();
+            core.register_callback(Box::new(move |cx| f(cx)))?;
+        }
+
+        Ok(())
+    }
+}
+
+struct ReadersSyncInstrument {
+    descriptor: Descriptor,
+    instruments: Vec<Arc<dyn SyncInstrumentCore + Send + Sync>>,
+}
+
+impl InstrumentCore for ReadersSyncInstrument {
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl SyncInstrumentCore for ReadersSyncInstrument {
+    fn record_one(&self, cx: &Context, number: Number, attributes: &'_ [KeyValue]) {
+        for instrument in &self.instruments {
+            instrument.record_one(cx, number.clone(), attributes);
+        }
+    }
+}
+
+struct ReadersAsyncInstrument {
+    descriptor: Descriptor,
+    instruments: Vec<Arc<dyn AsyncInstrumentCore + Send + Sync>>,
+}
+
+impl InstrumentCore for ReadersAsyncInstrument {
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl AsyncInstrumentCore for ReadersAsyncInstrument {
+    fn observe_one(&self, cx: &Context, number: Number, attributes: &'_ [KeyValue]) {
+        for instrument in &self.instruments {
+            instrument.observe_one(cx, number.clone(), attributes);
+        }
+    }
+}
+
 impl InstrumentationLibraryReader for BasicController {
     fn try_for_each(
         &self,
@@ -381,6 +569,7 @@ impl fmt::Debug for BasicController {
             .field("collect_timeout", &self.0.collect_timeout)
             .field("push_timeout", &self.0.push_timeout)
             .field("collected_time", &self.0.collect_timeout)
+            .field("readers", &self.0.readers)
             .finish()
     }
 }
@@ -393,6 +582,10 @@ pub struct BasicControllerBuilder {
     collect_period: Option<Duration>,
     collect_timeout: Option<Duration>,
     push_timeout: Option<Duration>,
+    views: Vec<View>,
+    exemplar_filter: ExemplarFilter,
+    cardinality_limit: Option<usize>,
+    readers: Vec<BasicController>,
 }
 
 impl BasicControllerBuilder {
@@ -436,12 +629,60 @@ impl BasicControllerBuilder {
 
     /// Sets push controller timeout when a exporter is configured.
     ///
+    /// This bounds the time [`BasicController::stop`] waits for the exporter to
+    /// send the pending data.
+    ///
     /// Default value is 10s. If zero, no export timeout is applied.
     pub fn with_push_timeout(mut self, push_timeout: Duration) -> Self {
         self.push_timeout = Some(push_timeout);
         self
     }
 
+    /// Adds a [`View`] customizing the metric streams of the instruments it
+    /// matches.
+    pub fn with_view(mut self, view: View) -> Self {
+        self.views.push(view);
+        self
+    }
+
+    /// Sets the filter selecting the measurements sampled as exemplars.
+    ///
+    /// Default value is [`ExemplarFilter::TraceBased`].
+    pub fn with_exemplar_filter(mut self, exemplar_filter: ExemplarFilter) -> Self {
+        self.exemplar_filter = exemplar_filter;
+        self
+    }
+
+    /// Sets the maximum number of attribute sets of each metric stream.
+    ///
+    /// Once a stream reaches the limit, the measurements of new attribute sets
+    /// are aggregated in a single series with the `otel.metric.overflow=true`
+    /// attribute and an error is reported through the global error handler.
+    /// Attribute sets that are no longer recorded free their slot when they are
+    /// removed at collection.
+    ///
+    /// Default value is 2000.
+    pub fn with_cardinality_limit(mut self, cardinality_limit: usize) -> Self {
+        self.cardinality_limit = Some(cardinality_limit);
+        self
+    }
+
+    /// Attaches another controller as a reader of the instruments created by
+    /// the meters of this controller.
+    ///
+    /// Measurements are recorded once by the instruments and aggregated by each
+    /// controller with its own processor, views and exporter, so that a reader
+    /// can export them with another temporality or collection period, or serve
+    /// them to a pull exporter such as Prometheus. Readers with an exporter are
+    /// started and stopped along with this controller.
+    ///
+    /// Only the meters of this controller record to the reader, the readers of
+    /// the reader are not used.
+    pub fn with_reader(mut self, reader: BasicController) -> Self {
+        self.readers.push(reader);
+        self
+    }
+
     /// Creates a new basic controller.
     pub fn build(self) -> BasicController {
         BasicController(Arc::new(ControllerInner {
@@ -454,6 +695,10 @@ impl BasicControllerBuilder {
             collect_timeout: self.collect_timeout.unwrap_or(DEFAULT_PERIOD),
             push_timeout: self.push_timeout.unwrap_or(DEFAULT_PERIOD),
             collected_time: Default::default(),
+            views: self.views,
+            exemplar_filter: self.exemplar_filter,
+            cardinality_limit: self.cardinality_limit.unwrap_or(DEFAULT_CARDINALITY_LIMIT),
+            readers: self.readers,
         }))
     }
 }
@@ -465,6 +710,10 @@ impl fmt::Debug for BasicControllerBuilder {
             .field("collect_period", &self.collect_period)
             .field("collect_timeout", &self.collect_timeout)
             .field("push_timeout", &self.push_timeout)
+            .field("views", &self.views)
+            .field("exemplar_filter", &self.exemplar_filter)
+            .field("cardinality_limit", &self.cardinality_limit)
+            .field("readers", &self.readers)
             .finish()
     }
 }
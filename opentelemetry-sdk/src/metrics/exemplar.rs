@@ -0,0 +1,319 @@
+//! Metric Exemplars
+//!
+//! An [`Exemplar`] is a sample measurement recorded along with the span that
+//! was active when it was made, it links an aggregated data point to a trace
+//! representative of it.
+//!
+//! The [`ExemplarFilter`] of the controller decides which measurements are
+//! offered to the exemplar reservoirs, and the [`ExemplarReservoir`] of each
+//! stream decides which of them are kept until the next collection.
+use crate::export::metrics::aggregation::Histogram;
+use crate::metrics::aggregators::{Aggregator, HistogramAggregator};
+use crate::metrics::sdk_api::{Number, NumberKind};
+use opentelemetry_api::trace::{SpanId, TraceContextExt, TraceId};
+use opentelemetry_api::{Context, KeyValue};
+use rand::Rng;
+use std::time::SystemTime;
+
+/// Selects the measurements that are offered to the exemplar reservoirs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExemplarFilter {
+    /// Offer all the measurements.
+    AlwaysOn,
+    /// Offer no measurement, exemplars are disabled.
+    AlwaysOff,
+    /// Offer the measurements made while a sampled span is active.
+    TraceBased,
+}
+
+impl Default for ExemplarFilter {
+    fn default() -> Self {
+        ExemplarFilter::TraceBased
+    }
+}
+
+impl ExemplarFilter {
+    /// Whether a measurement made in `cx` should be offered to the reservoirs.
+    pub(crate) fn should_sample(&self, cx: &Context) -> bool {
+        match self {
+            ExemplarFilter::AlwaysOn => true,
+            ExemplarFilter::AlwaysOff => false,
+            ExemplarFilter::TraceBased => cx.span().span_context().is_sampled(),
+        }
+    }
+}
+
+/// Decides which of the offered measurements of a stream are kept as
+/// exemplars.
+///
+/// By default, streams aggregated by a [`HistogramAggregator`] use
+/// [`ExemplarReservoir::AlignedHistogram`] and the others use
+/// [`ExemplarReservoir::FixedSize`] keeping a single exemplar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExemplarReservoir {
+    /// Keep a uniformly random sample of at most the given number of
+    /// measurements of each collection interval.
+    FixedSize(usize),
+    /// Keep the last measurement of each histogram bucket. Streams not
+    /// aggregated by a [`HistogramAggregator`] keep a single exemplar.
+    AlignedHistogram,
+}
+
+/// A measurement sampled along with the context it was made in.
+#[derive(Clone, Debug)]
+pub struct Exemplar {
+    value: Number,
+    time: SystemTime,
+    trace_id: Option<TraceId>,
+    span_id: Option<SpanId>,
+    filtered_attributes: Vec<KeyValue>,
+}
+
+impl Exemplar {
+    /// Create a new exemplar of a measurement made in `cx`.
+    pub fn new(
+        cx: &Context,
+        value: Number,
+        time: SystemTime,
+        filtered_attributes: Vec<KeyValue>,
+    ) -> Self {
+        let span = cx.span();
+        let span_context = span.span_context();
+        let (trace_id, span_id) = if span_context.is_valid() {
+            (Some(span_context.trace_id()), Some(span_context.span_id()))
+        } else {
+            (None, None)
+        };
+
+        Exemplar {
+            value,
+            time,
+            trace_id,
+            span_id,
+            filtered_attributes,
+        }
+    }
+
+    /// The measured value.
+    pub fn value(&self) -> &Number {
+        &self.value
+    }
+
+    /// The time of the measurement.
+    pub fn time(&self) -> &SystemTime {
+        &self.time
+    }
+
+    /// The trace id of the span active during the measurement, if any.
+    pub fn trace_id(&self) -> Option<TraceId> {
+        self.trace_id
+    }
+
+    /// The id of the span active during the measurement, if any.
+    pub fn span_id(&self) -> Option<SpanId> {
+        self.span_id
+    }
+
+    /// The attributes of the measurement that were dropped by the view of the
+    /// stream.
+    pub fn filtered_attributes(&self) -> &[KeyValue] {
+        &self.filtered_attributes
+    }
+}
+
+/// The exemplars kept for one record during a collection interval.
+#[derive(Debug)]
+pub(crate) enum Reservoir {
+    FixedSize {
+        size: usize,
+        offered: u64,
+        exemplars: Vec<Exemplar>,
+    },
+    AlignedHistogram {
+        boundaries: Vec<f64>,
+        exemplars: Vec<Option<Exemplar>>,
+    },
+}
+
+impl Reservoir {
+    /// Create the reservoir of a record aggregated by `aggregator`.
+    pub(crate) fn new(config: Option<&ExemplarReservoir>, aggregator: &dyn Aggregator) -> Self {
+        let boundaries = aggregator
+            .as_any()
+            .downcast_ref::<HistogramAggregator>()
+            .and_then(|histogram| histogram.histogram().ok())
+            .map(|buckets| buckets.boundaries().clone());
+
+        match (config, boundaries) {
+            (Some(ExemplarReservoir::FixedSize(size)), _) => Reservoir::fixed_size(*size),
+            (None | Some(ExemplarReservoir::AlignedHistogram), Some(boundaries)) => {
+                Reservoir::AlignedHistogram {
+                    exemplars: vec![None; boundaries.len() + 1],
+                    boundaries,
+                }
+            }
+            (None | Some(ExemplarReservoir::AlignedHistogram), None) => Reservoir::fixed_size(1),
+        }
+    }
+
+    fn fixed_size(size: usize) -> Self {
+        Reservoir::FixedSize {
+            size,
+            offered: 0,
+            exemplars: Vec::with_capacity(size),
+        }
+    }
+
+    /// Offer a measurement of kind `kind`, it replaces a previous exemplar or is
+    /// dropped if the reservoir is full.
+    pub(crate) fn offer(&mut self, exemplar: Exemplar, kind: &NumberKind) {
+        match self {
+            Reservoir::FixedSize {
+                size,
+                offered,
+                exemplars,
+            } => {
+                // Reservoir sampling, every offered measurement has the same
+                // probability to be kept.
+                *offered += 1;
+                if exemplars.len() < *size {
+                    exemplars.push(exemplar);
+                } else {
+                    let idx = rand::thread_rng().gen_range(0..*offered);
+                    if let Some(slot) = exemplars.get_mut(idx as usize) {
+                        *slot = exemplar;
+                    }
+                }
+            }
+            Reservoir::AlignedHistogram {
+                boundaries,
+                exemplars,
+            } => {
+                let value = exemplar.value.to_f64(kind);
+                let bucket = boundaries
+                    .iter()
+                    .position(|boundary| value < *boundary)
+                    .unwrap_or(boundaries.len());
+                exemplars[bucket] = Some(exemplar);
+            }
+        }
+    }
+
+    /// Take the exemplars kept since the last collection.
+    pub(crate) fn collect(&mut self) -> Vec<Exemplar> {
+        match self {
+            Reservoir::FixedSize {
+                offered, exemplars, ..
+            } => {
+                *offered = 0;
+                std::mem::take(exemplars)
+            }
+            Reservoir::AlignedHistogram { exemplars, .. } => {
+                exemplars.iter_mut().filter_map(Option::take).collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::aggregators;
+    use opentelemetry_api::trace::{SpanContext, TraceFlags, TraceState};
+
+    fn exemplar(value: f64) -> Exemplar {
+        Exemplar::new(
+            &Context::new(),
+            value.into(),
+            opentelemetry_api::time::now(),
+            vec![],
+        )
+    }
+
+    fn sampled_context(sampled: bool) -> Context {
+        Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from_u128(1),
+            SpanId::from_u64(2),
+            if sampled {
+                TraceFlags::SAMPLED
+            } else {
+                TraceFlags::default()
+            },
+            false,
+            TraceState::default(),
+        ))
+    }
+
+    #[test]
+    fn test_filter() {
+        assert!(ExemplarFilter::AlwaysOn.should_sample(&Context::new()));
+        assert!(!ExemplarFilter::AlwaysOff.should_sample(&sampled_context(true)));
+        assert!(ExemplarFilter::TraceBased.should_sample(&sampled_context(true)));
+        assert!(!ExemplarFilter::TraceBased.should_sample(&sampled_context(false)));
+        assert!(!ExemplarFilter::TraceBased.should_sample(&Context::new()));
+    }
+
+    #[test]
+    fn test_exemplar_captures_span() {
+        let exemplar = Exemplar::new(
+            &sampled_context(true),
+            Number::from(3u64),
+            opentelemetry_api::time::now(),
+            vec![KeyValue::new("user", "alice")],
+        );
+        assert_eq!(exemplar.trace_id(), Some(TraceId::from_u128(1)));
+        assert_eq!(exemplar.span_id(), Some(SpanId::from_u64(2)));
+        assert_eq!(
+            exemplar.filtered_attributes(),
+            &[KeyValue::new("user", "alice")]
+        );
+
+        let without_span = self::exemplar(1.0);
+        assert_eq!(without_span.trace_id(), None);
+        assert_eq!(without_span.span_id(), None);
+    }
+
+    #[test]
+    fn test_fixed_size_reservoir() {
+        let mut reservoir = Reservoir::new(
+            Some(&ExemplarReservoir::FixedSize(2)),
+            &aggregators::histogram(&[1.0]),
+        );
+        for value in 0..10 {
+            reservoir.offer(exemplar(value as f64), &NumberKind::F64);
+        }
+        let exemplars = reservoir.collect();
+        assert_eq!(exemplars.len(), 2);
+        assert_ne!(
+            exemplars[0].value().to_f64(&NumberKind::F64),
+            exemplars[1].value().to_f64(&NumberKind::F64)
+        );
+        assert!(reservoir.collect().is_empty());
+    }
+
+    #[test]
+    fn test_aligned_histogram_reservoir() {
+        let mut reservoir = Reservoir::new(None, &aggregators::histogram(&[1.0, 10.0]));
+        for value in [0.5, 0.7, 20.0] {
+            reservoir.offer(exemplar(value), &NumberKind::F64);
+        }
+        let values: Vec<f64> = reservoir
+            .collect()
+            .iter()
+            .map(|exemplar| exemplar.value().to_f64(&NumberKind::F64))
+            .collect();
+        // the last measurement of each bucket is kept
+        assert_eq!(values, vec![0.7, 20.0]);
+
+        // other aggregators keep a single exemplar by default
+        let mut reservoir = Reservoir::new(
+            Some(&ExemplarReservoir::AlignedHistogram),
+            &aggregators::sum(),
+        );
+        reservoir.offer(exemplar(1.0), &NumberKind::F64);
+        reservoir.offer(exemplar(2.0), &NumberKind::F64);
+        assert_eq!(reservoir.collect().len(), 1);
+    }
+}
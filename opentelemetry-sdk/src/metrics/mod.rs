@@ -1,6 +1,6 @@
 //! # OpenTelemetry Metrics SDK
 use crate::export;
-use crate::export::metrics::{LockedProcessor, Processor};
+use crate::export::metrics::{AggregatorSelector, LockedProcessor, Processor};
 use crate::metrics::{
     aggregators::Aggregator,
     sdk_api::{
@@ -8,30 +8,73 @@ use crate::metrics::{
         NumberKind, SyncInstrumentCore,
     },
 };
+use dashmap::mapref::entry::Entry;
 use fnv::FnvHasher;
 use opentelemetry_api::{
     attributes::{hash_attributes, AttributeSet},
     global,
-    metrics::Result,
-    Context, KeyValue,
+    metrics::{MetricsError, Result},
+    Context, InstrumentationLibrary, Key, KeyValue,
 };
 use std::{
     any::Any,
     cmp::Ordering,
+    collections::HashSet,
     fmt,
     hash::{Hash, Hasher},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering},
     sync::{Arc, Mutex},
 };
 pub mod aggregators;
 pub mod controllers;
+pub mod exemplar;
 pub mod processors;
 pub mod registry;
 pub mod sdk_api;
 pub mod selectors;
+pub mod view;
+
+use exemplar::{Exemplar, ExemplarFilter, Reservoir};
+use view::{Stream, View};
+
+/// The default maximum number of attribute sets of a metric stream.
+pub const DEFAULT_CARDINALITY_LIMIT: usize = 2000;
+
+/// The attribute of the series aggregating the measurements of the attribute
+/// sets over the cardinality limit of a stream.
+const OVERFLOW_ATTRIBUTE: Key = Key::from_static_str("otel.metric.overflow");
 
 /// Creates a new accumulator builder
 pub fn accumulator(processor: Arc<dyn Processor + Send + Sync>) -> Accumulator {
-    Accumulator(Arc::new(AccumulatorCore::new(processor)))
+    Accumulator(Arc::new(AccumulatorCore::new(
+        processor,
+        Vec::new(),
+        ExemplarFilter::default(),
+        DEFAULT_CARDINALITY_LIMIT,
+    )))
+}
+
+/// Creates a new accumulator for the instruments of `library`, applying the
+/// `views` that match it, sampling the exemplars selected by `exemplar_filter`
+/// and keeping at most `cardinality_limit` attribute sets per stream.
+pub fn accumulator_with_views(
+    processor: Arc<dyn Processor + Send + Sync>,
+    library: &InstrumentationLibrary,
+    views: &[View],
+    exemplar_filter: ExemplarFilter,
+    cardinality_limit: usize,
+) -> Accumulator {
+    let views = views
+        .iter()
+        .filter(|view| view.matches_library(library))
+        .cloned()
+        .collect();
+    Accumulator(Arc::new(AccumulatorCore::new(
+        processor,
+        views,
+        exemplar_filter,
+        cardinality_limit,
+    )))
 }
 
 /// Accumulator implements the OpenTelemetry Meter API. The Accumulator is bound
@@ -57,16 +100,81 @@ impl Accumulator {
     }
 }
 
+impl Accumulator {
+    /// The streams produced by an instrument, one per matching view or the
+    /// default stream if no view matches.
+    ///
+    /// The processor identifies streams by name, if several views produce a
+    /// stream with the same name only the first one is kept.
+    fn streams(&self, descriptor: &Descriptor) -> Vec<BaseInstrument> {
+        let mut matched = false;
+        let mut streams: Vec<Stream> = Vec::new();
+        for view in self.0.views.iter() {
+            if view.matches_instrument(descriptor) {
+                matched = true;
+                if let Some(stream) = view.stream(descriptor) {
+                    if streams
+                        .iter()
+                        .any(|s| s.descriptor.name() == stream.descriptor.name())
+                    {
+                        global::handle_error(MetricsError::Other(format!(
+                            "several views produce a {} stream for the {} instrument, only the first one is kept",
+                            stream.descriptor.name(),
+                            descriptor.name()
+                        )));
+                    } else {
+                        streams.push(stream);
+                    }
+                }
+            }
+        }
+        if !matched {
+            streams.push(Stream::new(descriptor.clone()));
+        }
+
+        streams
+            .into_iter()
+            .map(|stream| {
+                let retained = self.retained_by_processor(&stream);
+                BaseInstrument {
+                    meter: self.clone(),
+                    descriptor: stream.descriptor,
+                    allowed_attribute_keys: stream.allowed_attribute_keys,
+                    aggregation: stream.aggregation,
+                    exemplar_reservoir: stream.exemplar_reservoir,
+                    cardinality: Arc::new(Cardinality::new(retained)),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Accumulator {
+    /// Whether the processor remembers the attribute sets of `stream` once they
+    /// are no longer recorded.
+    fn retained_by_processor(&self, stream: &Stream) -> bool {
+        let aggregator_selector: &dyn AggregatorSelector = match &stream.aggregation {
+            Some(aggregation) => aggregation.as_ref(),
+            None => self.0.processor.aggregator_selector(),
+        };
+        aggregator_selector
+            .aggregator_for(&stream.descriptor)
+            .map_or(false, |aggregator| {
+                self.0
+                    .processor
+                    .memory_required(&stream.descriptor, aggregator.aggregation().kind())
+            })
+    }
+}
+
 impl MeterCore for Accumulator {
     fn new_sync_instrument(
         &self,
         descriptor: Descriptor,
     ) -> Result<Arc<dyn SyncInstrumentCore + Send + Sync>> {
         Ok(Arc::new(SyncInstrument {
-            instrument: Arc::new(BaseInstrument {
-                meter: self.clone(),
-                descriptor,
-            }),
+            streams: self.streams(&descriptor),
+            descriptor,
         }))
     }
 
@@ -75,10 +183,8 @@ impl MeterCore for Accumulator {
         descriptor: Descriptor,
     ) -> Result<Arc<dyn AsyncInstrumentCore + Send + Sync>> {
         Ok(Arc::new(AsyncInstrument {
-            instrument: Arc::new(BaseInstrument {
-                meter: self.clone(),
-                descriptor,
-            }),
+            streams: self.streams(&descriptor),
+            descriptor,
         }))
     }
 
@@ -96,8 +202,10 @@ struct MapKey {
     instrument_hash: u64,
 }
 
+/// Identifies the accumulator running the async instrument callbacks of a
+/// context, by the address of its core.
 #[derive(Debug)]
-struct AsyncContextKey;
+struct AsyncContextKey(usize);
 
 type Callback = Box<dyn Fn(&Context) + Send + Sync>;
 
@@ -113,18 +221,47 @@ struct AccumulatorCore {
 
     /// The configured processor.
     processor: Arc<dyn Processor + Send + Sync>,
+
+    /// The views applying to the instruments of this accumulator.
+    views: Vec<View>,
+
+    /// Selects the measurements offered to the exemplar reservoirs.
+    exemplar_filter: ExemplarFilter,
+
+    /// The maximum number of attribute sets of each stream, the measurements
+    /// of further attribute sets are aggregated in the overflow series.
+    cardinality_limit: usize,
 }
 
 impl AccumulatorCore {
-    fn new(processor: Arc<dyn Processor + Send + Sync>) -> Self {
+    fn new(
+        processor: Arc<dyn Processor + Send + Sync>,
+        views: Vec<View>,
+        exemplar_filter: ExemplarFilter,
+        cardinality_limit: usize,
+    ) -> Self {
         AccumulatorCore {
             current: dashmap::DashMap::new(),
             current_epoch: NumberKind::U64.zero().to_atomic(),
             processor,
             callbacks: Default::default(),
+            views,
+            exemplar_filter,
+            cardinality_limit,
         }
     }
 
+    fn id(&self) -> usize {
+        self as *const AccumulatorCore as usize
+    }
+
+    /// Whether `cx` is the context of the callbacks run by another accumulator,
+    /// whose observations must not be recorded by this one.
+    fn is_collected_by_other(&self, cx: &Context) -> bool {
+        cx.get::<AsyncContextKey>()
+            .map_or(false, |key| key.0 != self.id())
+    }
+
     fn collect(&self, cx: &Context, locked_processor: &mut dyn LockedProcessor) -> usize {
         self.run_async_callbacks(cx);
         let checkpointed = self.collect_instruments(locked_processor);
@@ -136,7 +273,7 @@ impl AccumulatorCore {
     fn run_async_callbacks(&self, cx: &Context) {
         match self.callbacks.lock() {
             Ok(callbacks) => {
-                let cx = cx.with_value(AsyncContextKey);
+                let cx = cx.with_value(AsyncContextKey(self.id()));
                 for f in callbacks.iter() {
                     f(&cx)
                 }
@@ -159,14 +296,19 @@ impl AccumulatorCore {
                 value.collected_count.store(mods);
             } else {
                 // Having no updates since last collection, try to remove if
-                // there are no bound handles
-                if Arc::strong_count(value) == 1 {
+                // there are no bound handles. The records of the attribute sets
+                // the processor remembers are kept, they still count against
+                // the cardinality limit.
+                if Arc::strong_count(value) == 1 && !value.instrument.cardinality.retained {
                     // There's a potential race between loading collected count and
                     // loading the strong count in this function.  Since this is the
                     // last we'll see of this record, checkpoint.
                     if mods.partial_cmp(&NumberKind::U64, coll) != Some(Ordering::Equal) {
                         checkpointed += self.checkpoint_record(value, locked_processor);
                     }
+                    if !value.overflow {
+                        value.instrument.cardinality.release();
+                    }
                     return false;
                 }
             };
@@ -189,11 +331,16 @@ impl AccumulatorCore {
                 return 0;
             }
 
-            let accumulation = export::metrics::accumulation(
+            let exemplars = record.collect_exemplars();
+            let mut accumulation = export::metrics::accumulation(
                 record.instrument.descriptor(),
                 &record.attributes,
                 checkpoint,
-            );
+            )
+            .with_exemplars(&exemplars);
+            if let Some(aggregation) = &record.instrument.aggregation {
+                accumulation = accumulation.with_aggregator_selector(aggregation.as_ref());
+            }
             if let Err(err) = locked_processor.process(accumulation) {
                 global::handle_error(err);
             }
@@ -258,18 +405,23 @@ impl fmt::Debug for AccumulatorCore {
 
 #[derive(Debug, Clone)]
 struct SyncInstrument {
-    instrument: Arc<BaseInstrument>,
+    descriptor: Descriptor,
+    streams: Vec<BaseInstrument>,
 }
 
 impl SyncInstrumentCore for SyncInstrument {
     fn record_one(&self, cx: &Context, number: sdk_api::Number, kvs: &'_ [KeyValue]) {
-        self.instrument.acquire_handle(kvs).capture_one(cx, number)
+        for stream in &self.streams {
+            stream
+                .acquire_handle(kvs)
+                .capture_one(cx, number.clone(), kvs)
+        }
     }
 }
 
 impl sdk_api::InstrumentCore for SyncInstrument {
     fn descriptor(&self) -> &Descriptor {
-        self.instrument.descriptor()
+        &self.descriptor
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -279,18 +431,26 @@ impl sdk_api::InstrumentCore for SyncInstrument {
 
 #[derive(Debug, Clone)]
 struct AsyncInstrument {
-    instrument: Arc<BaseInstrument>,
+    descriptor: Descriptor,
+    streams: Vec<BaseInstrument>,
 }
 
 impl AsyncInstrumentCore for AsyncInstrument {
     fn observe_one(&self, cx: &Context, number: Number, kvs: &'_ [KeyValue]) {
-        self.instrument.acquire_handle(kvs).capture_one(cx, number)
+        for stream in &self.streams {
+            if stream.meter.0.is_collected_by_other(cx) {
+                continue;
+            }
+            stream
+                .acquire_handle(kvs)
+                .capture_one(cx, number.clone(), kvs)
+        }
     }
 }
 
 impl sdk_api::InstrumentCore for AsyncInstrument {
     fn descriptor(&self) -> &Descriptor {
-        self.instrument.descriptor()
+        &self.descriptor
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -298,50 +458,152 @@ impl sdk_api::InstrumentCore for AsyncInstrument {
     }
 }
 
+/// One metric stream of an instrument.
 #[derive(Debug, Clone)]
 struct BaseInstrument {
     meter: Accumulator,
     descriptor: Descriptor,
+    /// Attributes kept by the view producing this stream, all if `None`.
+    allowed_attribute_keys: Option<Arc<HashSet<Key>>>,
+    /// Aggregation overridden by the view producing this stream.
+    aggregation: Option<Arc<view::StreamAggregation>>,
+    /// Exemplar reservoir selected by the view producing this stream.
+    exemplar_reservoir: Option<exemplar::ExemplarReservoir>,
+    /// Number of attribute sets currently recorded by this stream.
+    cardinality: Arc<Cardinality>,
+}
+
+/// Tracks the attribute sets of a stream against the cardinality limit.
+#[derive(Debug)]
+struct Cardinality {
+    /// Number of records of the stream, not counting the overflow series.
+    series: AtomicUsize,
+    /// Whether reaching the limit was already reported.
+    reported: AtomicBool,
+    /// Whether the processor remembers every attribute set of the stream, in
+    /// which case the idle records are not removed.
+    retained: bool,
+}
+
+impl Cardinality {
+    fn new(retained: bool) -> Self {
+        Cardinality {
+            series: AtomicUsize::new(0),
+            reported: AtomicBool::new(false),
+            retained,
+        }
+    }
+
+    /// Reserve a series for a new attribute set, fails if `limit` series are
+    /// already recorded.
+    fn acquire(&self, limit: usize) -> bool {
+        self.series
+            .fetch_update(AtomicOrdering::AcqRel, AtomicOrdering::Acquire, |series| {
+                if series < limit {
+                    Some(series + 1)
+                } else {
+                    None
+                }
+            })
+            .is_ok()
+    }
+
+    /// Release the series of a removed record.
+    fn release(&self) {
+        self.series.fetch_sub(1, AtomicOrdering::AcqRel);
+    }
 }
 
 impl BaseInstrument {
     // acquireHandle gets or creates a `*record` corresponding to `kvs`,
     // the input attributes.
     fn acquire_handle(&self, kvs: &[KeyValue]) -> Arc<Record> {
+        let filtered;
+        let kvs = match &self.allowed_attribute_keys {
+            Some(keys) => {
+                filtered = kvs
+                    .iter()
+                    .filter(|kv| keys.contains(&kv.key))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                filtered.as_slice()
+            }
+            None => kvs,
+        };
+
+        let current = &self.meter.0.current;
+        let map_key = self.map_key(kvs);
+        if let Some(existing_record) = current.get(&map_key) {
+            return existing_record.value().clone();
+        }
+
+        if !self.cardinality.acquire(self.meter.0.cardinality_limit) {
+            return self.acquire_overflow_handle();
+        }
+        match current.entry(map_key) {
+            Entry::Occupied(existing_record) => {
+                // Another thread recorded this attribute set first.
+                self.cardinality.release();
+                existing_record.get().clone()
+            }
+            Entry::Vacant(entry) => entry.insert(self.new_record(kvs, false)).clone(),
+        }
+    }
+
+    /// Gets or creates the record of the overflow series, reporting the first
+    /// time the cardinality limit is reached.
+    fn acquire_overflow_handle(&self) -> Arc<Record> {
+        if !self.cardinality.reported.swap(true, AtomicOrdering::AcqRel) {
+            global::handle_error(MetricsError::CardinalityLimitReached(
+                self.descriptor.name().to_string(),
+                self.meter.0.cardinality_limit,
+            ));
+        }
+
+        let kvs = [KeyValue::new(OVERFLOW_ATTRIBUTE, true)];
+        self.meter
+            .0
+            .current
+            .entry(self.map_key(&kvs))
+            .or_insert_with(|| self.new_record(&kvs, true))
+            .clone()
+    }
+
+    fn map_key(&self, kvs: &[KeyValue]) -> MapKey {
         let mut hasher = FnvHasher::default();
         self.descriptor.attribute_hash().hash(&mut hasher);
 
         hash_attributes(&mut hasher, kvs.iter().map(|kv| (&kv.key, &kv.value)));
 
-        let map_key = MapKey {
+        MapKey {
             instrument_hash: hasher.finish(),
-        };
-        let current = &self.meter.0.current;
-        if let Some(existing_record) = current.get(&map_key) {
-            return existing_record.value().clone();
         }
+    }
 
-        let record = Arc::new(Record {
+    fn new_record(&self, kvs: &[KeyValue], overflow: bool) -> Arc<Record> {
+        let aggregator_selector: &dyn AggregatorSelector = match &self.aggregation {
+            Some(aggregation) => aggregation.as_ref(),
+            None => self.meter.0.processor.aggregator_selector(),
+        };
+        let aggregator = aggregator_selector.aggregator_for(&self.descriptor);
+        let exemplars = match (&aggregator, self.meter.0.exemplar_filter) {
+            (_, ExemplarFilter::AlwaysOff) | (None, _) => None,
+            (Some(aggregator), _) => Some(Mutex::new(Reservoir::new(
+                self.exemplar_reservoir.as_ref(),
+                aggregator.as_ref(),
+            ))),
+        };
+
+        Arc::new(Record {
             update_count: NumberKind::U64.zero().to_atomic(),
             collected_count: NumberKind::U64.zero().to_atomic(),
             attributes: AttributeSet::from_attributes(kvs.iter().cloned()),
             instrument: self.clone(),
-            current: self
-                .meter
-                .0
-                .processor
-                .aggregator_selector()
-                .aggregator_for(&self.descriptor),
-            checkpoint: self
-                .meter
-                .0
-                .processor
-                .aggregator_selector()
-                .aggregator_for(&self.descriptor),
-        });
-        current.insert(map_key, record.clone());
-
-        record
+            current: aggregator,
+            checkpoint: aggregator_selector.aggregator_for(&self.descriptor),
+            exemplars,
+            overflow,
+        })
     }
 }
 
@@ -380,10 +642,17 @@ struct Record {
     /// aggregation. If `None`, the metric was disabled by the exporter.
     current: Option<Arc<dyn Aggregator + Send + Sync>>,
     checkpoint: Option<Arc<dyn Aggregator + Send + Sync>>,
+
+    /// The exemplars sampled since the last collection, `None` if exemplars
+    /// are disabled.
+    exemplars: Option<Mutex<Reservoir>>,
+
+    /// Whether this record is the overflow series of its stream.
+    overflow: bool,
 }
 
 impl Record {
-    fn capture_one(&self, cx: &Context, number: Number) {
+    fn capture_one(&self, cx: &Context, number: Number, kvs: &[KeyValue]) {
         let current = match &self.current {
             Some(current) => current,
             // The instrument is disabled according to the AggregatorSelector.
@@ -396,8 +665,46 @@ impl Record {
             return;
         }
 
+        if let Some(reservoir) = &self.exemplars {
+            if self.instrument.meter.0.exemplar_filter.should_sample(cx) {
+                let filtered_attributes = match &self.instrument.allowed_attribute_keys {
+                    _ if self.overflow => kvs.to_vec(),
+                    Some(keys) => kvs
+                        .iter()
+                        .filter(|kv| !keys.contains(&kv.key))
+                        .cloned()
+                        .collect(),
+                    None => Vec::new(),
+                };
+                let exemplar = Exemplar::new(
+                    cx,
+                    number,
+                    opentelemetry_api::time::now(),
+                    filtered_attributes,
+                );
+                match reservoir.lock() {
+                    Ok(mut reservoir) => {
+                        reservoir.offer(exemplar, self.instrument.descriptor.number_kind())
+                    }
+                    Err(err) => global::handle_error(err),
+                }
+            }
+        }
+
         // Record was modified, inform the collect() that things need
         // to be collected while the record is still mapped.
         self.update_count.fetch_add(&NumberKind::U64, &1u64.into());
     }
+
+    /// Take the exemplars sampled since the last collection.
+    fn collect_exemplars(&self) -> Vec<Exemplar> {
+        match self.exemplars.as_ref().map(Mutex::lock) {
+            Some(Ok(mut reservoir)) => reservoir.collect(),
+            Some(Err(err)) => {
+                global::handle_error(err);
+                Vec::new()
+            }
+            None => Vec::new(),
+        }
+    }
 }
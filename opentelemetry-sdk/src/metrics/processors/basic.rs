@@ -1,11 +1,11 @@
 use crate::{
     export::metrics::{
         self,
-        aggregation::{Temporality, TemporalitySelector},
+        aggregation::{AggregationKind, Temporality, TemporalitySelector},
         Accumulation, AggregatorSelector, Checkpointer, CheckpointerFactory, LockedCheckpointer,
         LockedProcessor, Processor, Reader, Record,
     },
-    metrics::{aggregators::Aggregator, sdk_api::Descriptor},
+    metrics::{aggregators::Aggregator, exemplar::Exemplar, sdk_api::Descriptor},
 };
 use core::fmt;
 use fnv::FnvHasher;
@@ -62,6 +62,12 @@ impl Processor for BasicProcessor {
     fn aggregator_selector(&self) -> &dyn AggregatorSelector {
         self.aggregator_selector.as_ref()
     }
+
+    fn memory_required(&self, descriptor: &Descriptor, kind: &AggregationKind) -> bool {
+        self.temporality_selector
+            .temporality_for(descriptor, kind)
+            .memory_required(descriptor.instrument_kind())
+    }
 }
 
 impl Checkpointer for BasicProcessor {
@@ -105,6 +111,9 @@ impl<'a> LockedProcessor for BasicLockedProcessor<'a> {
         hash_attributes(&mut hasher, accumulation.attributes().into_iter());
         let key = StateKey(hasher.finish());
         let agg = accumulation.aggregator();
+        let aggregator_selector = accumulation
+            .aggregator_selector()
+            .unwrap_or_else(|| self.parent.aggregator_selector.as_ref());
         let finished_collection = self.state.finished_collection;
         if let Some(value) = self.state.values.get_mut(&key) {
             // Advance the update sequence number.
@@ -140,6 +149,16 @@ impl<'a> LockedProcessor for BasicLockedProcessor<'a> {
             // a single Accumulator has been configured with an attribute key
             // filter.
 
+            if same_collection {
+                value
+                    .exemplars
+                    .extend(accumulation.exemplars().iter().cloned());
+            } else if !value.stateful || !accumulation.exemplars().is_empty() {
+                // Cumulative aggregations keep the exemplars of the last
+                // interval that sampled some.
+                value.exemplars = accumulation.exemplars().to_vec();
+            }
+
             if !same_collection {
                 if !value.current_owned {
                     // This is the first Accumulation we've seen for this
@@ -156,7 +175,7 @@ impl<'a> LockedProcessor for BasicLockedProcessor<'a> {
             // before merging below.
             if !value.current_owned {
                 let tmp = value.current.clone();
-                if let Some(current) = self.parent.aggregator_selector.aggregator_for(desc) {
+                if let Some(current) = aggregator_selector.aggregator_for(desc) {
                     value.current = current;
                     value.current_owned = true;
                     tmp.synchronized_move(&value.current, desc)?;
@@ -167,11 +186,7 @@ impl<'a> LockedProcessor for BasicLockedProcessor<'a> {
             return value.current.merge(agg.as_ref(), desc);
         }
 
-        let stateful = self
-            .parent
-            .temporality_selector
-            .temporality_for(desc, agg.aggregation().kind())
-            .memory_required(desc.instrument_kind());
+        let stateful = self.parent.memory_required(desc, agg.aggregation().kind());
 
         let cumulative = if stateful {
             if desc.instrument_kind().precomputed_sum() {
@@ -179,7 +194,7 @@ impl<'a> LockedProcessor for BasicLockedProcessor<'a> {
                 return Err(MetricsError::Other("No cumulative to sum support".into()));
             }
             // Always allocate a cumulative aggregator if stateful
-            self.parent.aggregator_selector.aggregator_for(desc)
+            aggregator_selector.aggregator_for(desc)
         } else {
             None
         };
@@ -194,6 +209,7 @@ impl<'a> LockedProcessor for BasicLockedProcessor<'a> {
                 cumulative,
                 stateful,
                 updated: finished_collection,
+                exemplars: accumulation.exemplars().to_vec(),
             },
         );
 
@@ -346,7 +362,8 @@ impl Reader for BasicProcessorState {
                 agg,
                 start,
                 self.interval_end,
-            ));
+            )
+            .with_exemplars(&value.exemplars));
 
             if let Err(MetricsError::NoDataCollected) = res {
                 Ok(())
@@ -393,4 +410,8 @@ struct StateValue {
     /// If `Some`, refers to an `Aggregator` owned by the processor used to store
     /// the last cumulative value.
     cumulative: Option<Arc<dyn Aggregator + Send + Sync>>,
+
+    /// The exemplars sampled during the last collection interval, or the last
+    /// one that sampled some if stateful.
+    exemplars: Vec<Exemplar>,
 }
@@ -41,3 +41,35 @@ impl AggregatorSelector for HistogramSelector {
         }
     }
 }
+
+/// An aggregator selector that uses exponential histogram aggregators for
+/// `Histogram` instruments.
+///
+/// The histograms keep at most `max_size` buckets for the positive and the
+/// negative values and start at scale `max_scale`, see
+/// [`exponential_histogram`](aggregators::exponential_histogram).
+pub fn exponential_histogram(max_size: u32, max_scale: i8) -> impl AggregatorSelector {
+    ExponentialHistogramSelector {
+        max_size,
+        max_scale,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ExponentialHistogramSelector {
+    max_size: u32,
+    max_scale: i8,
+}
+
+impl AggregatorSelector for ExponentialHistogramSelector {
+    fn aggregator_for(&self, descriptor: &Descriptor) -> Option<Arc<dyn Aggregator + Send + Sync>> {
+        match descriptor.instrument_kind() {
+            InstrumentKind::GaugeObserver => Some(Arc::new(aggregators::last_value())),
+            InstrumentKind::Histogram => Some(Arc::new(aggregators::exponential_histogram(
+                self.max_size,
+                self.max_scale,
+            ))),
+            _ => Some(Arc::new(aggregators::sum())),
+        }
+    }
+}
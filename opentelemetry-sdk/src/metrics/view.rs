@@ -0,0 +1,381 @@
+//! Metric Views
+//!
+//! A [`View`] customizes the metric stream produced by the instruments it
+//! matches. It can rename the stream, change its description, keep only some
+//! of the recorded attributes, override the aggregation picked by the
+//! [`AggregatorSelector`] or choose how exemplars are sampled.
+//!
+//! An instrument matched by several views produces one stream per view. An
+//! instrument that no view matches produces its default stream.
+use crate::export::metrics::AggregatorSelector;
+use crate::metrics::aggregators::{self, Aggregator};
+use crate::metrics::exemplar::ExemplarReservoir;
+use crate::metrics::sdk_api::{Descriptor, InstrumentKind};
+use opentelemetry_api::{
+    metrics::{MetricsError, Result, Unit},
+    InstrumentationLibrary, Key,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// The aggregation a [`View`] applies to the instruments it matches.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum StreamAggregation {
+    /// Drop all the measurements, no stream is produced.
+    Drop,
+    /// Sum the measurements.
+    Sum,
+    /// Keep the last measurement.
+    LastValue,
+    /// Count the measurements in buckets with the given boundaries.
+    Histogram(Vec<f64>),
+    /// Count the measurements in base-2 exponential buckets.
+    ExponentialHistogram {
+        /// Maximum number of buckets for the positive and the negative values.
+        max_size: u32,
+        /// Scale the histogram starts recording at.
+        max_scale: i8,
+    },
+}
+
+impl AggregatorSelector for StreamAggregation {
+    fn aggregator_for(
+        &self,
+        _descriptor: &Descriptor,
+    ) -> Option<Arc<dyn Aggregator + Send + Sync>> {
+        match self {
+            StreamAggregation::Drop => None,
+            StreamAggregation::Sum => Some(Arc::new(aggregators::sum())),
+            StreamAggregation::LastValue => Some(Arc::new(aggregators::last_value())),
+            StreamAggregation::Histogram(boundaries) => {
+                Some(Arc::new(aggregators::histogram(boundaries)))
+            }
+            StreamAggregation::ExponentialHistogram {
+                max_size,
+                max_scale,
+            } => Some(Arc::new(aggregators::exponential_histogram(
+                *max_size, *max_scale,
+            ))),
+        }
+    }
+}
+
+/// Selects instruments and describes the metric stream they produce.
+///
+/// Views are created with [`View::builder`] and registered on the controller,
+/// see [`BasicControllerBuilder::with_view`].
+///
+/// [`BasicControllerBuilder::with_view`]: crate::metrics::controllers::BasicControllerBuilder::with_view
+#[derive(Clone, Debug, Default)]
+pub struct View {
+    instrument_name: Option<String>,
+    instrument_kind: Option<InstrumentKind>,
+    meter_name: Option<String>,
+    meter_version: Option<String>,
+    name: Option<String>,
+    description: Option<String>,
+    allowed_attribute_keys: Option<Arc<HashSet<Key>>>,
+    aggregation: Option<Arc<StreamAggregation>>,
+    exemplar_reservoir: Option<ExemplarReservoir>,
+}
+
+impl View {
+    /// Create a builder for a view matching all instruments.
+    pub fn builder() -> ViewBuilder {
+        ViewBuilder::default()
+    }
+
+    /// Whether the view applies to the instruments created by meters of `library`.
+    pub(crate) fn matches_library(&self, library: &InstrumentationLibrary) -> bool {
+        self.meter_name
+            .as_ref()
+            .map_or(true, |name| name == library.name.as_ref())
+            && self.meter_version.as_ref().map_or(true, |version| {
+                Some(version.as_str()) == library.version.as_deref()
+            })
+    }
+
+    /// Whether the view applies to the instrument described by `descriptor`.
+    pub(crate) fn matches_instrument(&self, descriptor: &Descriptor) -> bool {
+        self.instrument_name
+            .as_ref()
+            .map_or(true, |pattern| wildcard_match(pattern, descriptor.name()))
+            && self
+                .instrument_kind
+                .as_ref()
+                .map_or(true, |kind| kind == descriptor.instrument_kind())
+    }
+
+    /// The stream this view produces for the instrument described by
+    /// `descriptor`, or `None` if the view drops its measurements.
+    pub(crate) fn stream(&self, descriptor: &Descriptor) -> Option<Stream> {
+        if self.aggregation.as_deref() == Some(&StreamAggregation::Drop) {
+            return None;
+        }
+
+        let descriptor = if self.name.is_some() || self.description.is_some() {
+            Descriptor::new(
+                self.name
+                    .clone()
+                    .unwrap_or_else(|| descriptor.name().to_string()),
+                descriptor.instrument_kind().clone(),
+                descriptor.number_kind().clone(),
+                self.description
+                    .clone()
+                    .or_else(|| descriptor.description().cloned()),
+                descriptor.unit().map(|unit| Unit::new(unit.to_string())),
+            )
+        } else {
+            descriptor.clone()
+        };
+
+        Some(Stream {
+            descriptor,
+            allowed_attribute_keys: self.allowed_attribute_keys.clone(),
+            aggregation: self.aggregation.clone(),
+            exemplar_reservoir: self.exemplar_reservoir.clone(),
+        })
+    }
+}
+
+/// Configuration of a [`View`].
+#[derive(Debug, Default)]
+pub struct ViewBuilder {
+    view: View,
+}
+
+impl ViewBuilder {
+    /// Match the instruments by name.
+    ///
+    /// The name can contain wildcards, `*` matches any sequence of characters
+    /// and `?` matches a single character.
+    pub fn with_instrument_name(mut self, name: impl Into<String>) -> Self {
+        self.view.instrument_name = Some(name.into());
+        self
+    }
+
+    /// Match the instruments of the given kind.
+    pub fn with_instrument_kind(mut self, kind: InstrumentKind) -> Self {
+        self.view.instrument_kind = Some(kind);
+        self
+    }
+
+    /// Match the instruments created by meters with the given instrumentation
+    /// library name.
+    pub fn with_meter_name(mut self, name: impl Into<String>) -> Self {
+        self.view.meter_name = Some(name.into());
+        self
+    }
+
+    /// Match the instruments created by meters with the given instrumentation
+    /// library version.
+    pub fn with_meter_version(mut self, version: impl Into<String>) -> Self {
+        self.view.meter_version = Some(version.into());
+        self
+    }
+
+    /// Set the name of the produced stream, defaults to the instrument name.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.view.name = Some(name.into());
+        self
+    }
+
+    /// Set the description of the produced stream, defaults to the instrument
+    /// description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.view.description = Some(description.into());
+        self
+    }
+
+    /// Only keep the attributes with the given keys, the others are dropped
+    /// before the measurements are aggregated.
+    pub fn with_allowed_attribute_keys(mut self, keys: impl IntoIterator<Item = Key>) -> Self {
+        self.view.allowed_attribute_keys = Some(Arc::new(keys.into_iter().collect()));
+        self
+    }
+
+    /// Set the aggregation of the produced stream, defaults to the aggregation
+    /// picked by the processor's [`AggregatorSelector`].
+    pub fn with_aggregation(mut self, aggregation: StreamAggregation) -> Self {
+        self.view.aggregation = Some(Arc::new(aggregation));
+        self
+    }
+
+    /// Set the reservoir sampling the exemplars of the produced stream,
+    /// defaults to a reservoir aligned to the buckets of histograms and keeping
+    /// a single exemplar for the other aggregations.
+    pub fn with_exemplar_reservoir(mut self, reservoir: ExemplarReservoir) -> Self {
+        self.view.exemplar_reservoir = Some(reservoir);
+        self
+    }
+
+    /// Create the view.
+    ///
+    /// Returns an error if the view renames the stream but can match several
+    /// instruments, which would produce conflicting streams.
+    pub fn build(self) -> Result<View> {
+        let selects_single_instrument = self
+            .view
+            .instrument_name
+            .as_ref()
+            .map_or(false, |name| !name.contains(&['*', '?'][..]));
+        if self.view.name.is_some() && !selects_single_instrument {
+            return Err(MetricsError::InvalidInstrumentConfiguration(
+                "a view renaming the stream must select a single instrument name",
+            ));
+        }
+
+        Ok(self.view)
+    }
+}
+
+/// A metric stream produced by an instrument.
+#[derive(Clone, Debug)]
+pub(crate) struct Stream {
+    pub(crate) descriptor: Descriptor,
+    pub(crate) allowed_attribute_keys: Option<Arc<HashSet<Key>>>,
+    pub(crate) aggregation: Option<Arc<StreamAggregation>>,
+    pub(crate) exemplar_reservoir: Option<ExemplarReservoir>,
+}
+
+impl Stream {
+    /// The default stream of an instrument no view applies to.
+    pub(crate) fn new(descriptor: Descriptor) -> Self {
+        Stream {
+            descriptor,
+            allowed_attribute_keys: None,
+            aggregation: None,
+            exemplar_reservoir: None,
+        }
+    }
+}
+
+/// Match `name` against `pattern`, where `*` matches any sequence of
+/// characters and `?` matches any single character.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` in the pattern and of the name character it is
+    // currently matched up to, to backtrack on mismatches.
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::sdk_api::NumberKind;
+
+    fn descriptor(name: &str, kind: InstrumentKind) -> Descriptor {
+        Descriptor::new(
+            name.to_string(),
+            kind,
+            NumberKind::F64,
+            Some("original".to_string()),
+            Some(Unit::new("ms")),
+        )
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match(
+            "http.server.duration",
+            "http.server.duration"
+        ));
+        assert!(wildcard_match("http.*", "http.server.duration"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("*.duration", "http.server.duration"));
+        assert!(wildcard_match("http.*.duration", "http.client.duration"));
+        assert!(wildcard_match("http.?erver.*", "http.server.duration"));
+        assert!(wildcard_match("a*b*c", "aXbYbZc"));
+        assert!(!wildcard_match("http.*", "rpc.server.duration"));
+        assert!(!wildcard_match("http.?", "http.server"));
+        assert!(!wildcard_match("a*b*c", "aXbYbZ"));
+        assert!(!wildcard_match("http", "http.server"));
+    }
+
+    #[test]
+    fn test_view_matching() {
+        let view = View::builder()
+            .with_instrument_name("http.*")
+            .with_instrument_kind(InstrumentKind::Histogram)
+            .with_meter_name("my-crate")
+            .build()
+            .unwrap();
+
+        assert!(view.matches_instrument(&descriptor("http.duration", InstrumentKind::Histogram)));
+        assert!(!view.matches_instrument(&descriptor("http.duration", InstrumentKind::Counter)));
+        assert!(!view.matches_instrument(&descriptor("rpc.duration", InstrumentKind::Histogram)));
+
+        assert!(view.matches_library(&InstrumentationLibrary::new("my-crate", Some("1.0"), None)));
+        assert!(!view.matches_library(&InstrumentationLibrary::new("other", None, None)));
+    }
+
+    #[test]
+    fn test_view_stream() {
+        let original = descriptor("latency", InstrumentKind::Histogram);
+
+        let view = View::builder()
+            .with_instrument_name("latency")
+            .with_name("request.latency")
+            .with_aggregation(StreamAggregation::Histogram(vec![1.0, 10.0]))
+            .build()
+            .unwrap();
+        let stream = view.stream(&original).unwrap();
+        assert_eq!(stream.descriptor.name(), "request.latency");
+        assert_eq!(stream.descriptor.description().unwrap(), "original");
+        assert_eq!(stream.descriptor.unit(), Some("ms"));
+        assert_eq!(
+            stream.aggregation.as_deref(),
+            Some(&StreamAggregation::Histogram(vec![1.0, 10.0]))
+        );
+
+        let unchanged = View::builder().build().unwrap().stream(&original).unwrap();
+        assert_eq!(unchanged.descriptor, original);
+
+        let dropped = View::builder()
+            .with_aggregation(StreamAggregation::Drop)
+            .build()
+            .unwrap();
+        assert!(dropped.stream(&original).is_none());
+    }
+
+    #[test]
+    fn test_renaming_view_must_select_single_instrument() {
+        assert!(View::builder().with_name("renamed").build().is_err());
+        assert!(View::builder()
+            .with_instrument_name("http.*")
+            .with_name("renamed")
+            .build()
+            .is_err());
+        assert!(View::builder()
+            .with_instrument_name("http.*")
+            .with_description("all http instruments")
+            .build()
+            .is_ok());
+    }
+}
@@ -3,17 +3,19 @@ use crate::{
         trace::{ExportResult, SpanData, SpanExporter},
         ExportError,
     },
-    trace::{Config, EvictedHashMap, EvictedQueue},
+    trace::{Config, EvictedHashMap, EvictedQueue, Span, SpanProcessor},
     InstrumentationLibrary,
 };
 use async_trait::async_trait;
 use futures_util::future::BoxFuture;
 pub use opentelemetry_api::testing::trace::TestSpan;
 use opentelemetry_api::trace::{
-    SpanContext, SpanId, SpanKind, Status, TraceFlags, TraceId, TraceState,
+    SpanContext, SpanId, SpanKind, Status, TraceFlags, TraceId, TraceResult, TraceState,
 };
+use opentelemetry_api::Context;
 use std::fmt::{Display, Formatter};
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
 
 pub fn new_test_export_span_data() -> SpanData {
     let config = Config::default();
@@ -164,3 +166,31 @@ impl SpanExporter for NoopSpanExporter {
         Box::pin(std::future::ready(Ok(())))
     }
 }
+
+/// A [`SpanProcessor`] keeping the ended spans it receives, for the tests of
+/// processors wrapping another one.
+#[derive(Debug, Default)]
+pub struct RecordingSpanProcessor(Mutex<Vec<SpanData>>);
+
+impl RecordingSpanProcessor {
+    /// The spans received so far, in the order they ended.
+    pub fn into_spans(self) -> Vec<SpanData> {
+        self.0.into_inner().unwrap()
+    }
+}
+
+impl SpanProcessor for RecordingSpanProcessor {
+    fn on_start(&self, _span: &mut Span, _cx: &Context) {}
+
+    fn on_end(&self, span: SpanData) {
+        self.0.lock().unwrap().push(span);
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        Ok(())
+    }
+
+    fn shutdown(&mut self) -> TraceResult<()> {
+        Ok(())
+    }
+}
@@ -73,6 +73,12 @@ impl Config {
         self
     }
 
+    /// Specify the max length of string attribute values, longer values are truncated.
+    pub fn with_max_attribute_value_length(mut self, max_length: u32) -> Self {
+        self.span_limits.max_attribute_value_length = Some(max_length);
+        self
+    }
+
     /// Specify all limit via the span_limits
     pub fn with_span_limits(mut self, span_limits: SpanLimits) -> Self {
         self.span_limits = span_limits;
@@ -117,6 +123,20 @@ impl Default for Config {
             config.span_limits.max_links_per_span = max_links_per_span;
         }
 
+        // the span specific limit takes precedence over the general one when both are valid
+        if let Some(max_attribute_value_length) = [
+            "OTEL_SPAN_ATTRIBUTE_VALUE_LENGTH_LIMIT",
+            "OTEL_ATTRIBUTE_VALUE_LENGTH_LIMIT",
+        ]
+        .iter()
+        .find_map(|name| {
+            env::var(name)
+                .ok()
+                .and_then(|length_limit| u32::from_str(&length_limit).ok())
+        }) {
+            config.span_limits.max_attribute_value_length = Some(max_attribute_value_length);
+        }
+
         let sampler_arg = env::var("OTEL_TRACES_SAMPLER_ARG").ok();
         if let Ok(sampler) = env::var("OTEL_TRACES_SAMPLER") {
             config.sampler = match sampler.as_str() {
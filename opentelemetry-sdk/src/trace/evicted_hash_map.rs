@@ -10,8 +10,8 @@ use std::collections::{HashMap, LinkedList};
 pub struct EvictedHashMap {
     map: HashMap<Key, Value>,
     evict_list: LinkedList<Key>,
-    max_len: u32,
-    dropped_count: u32,
+    pub(crate) max_len: u32,
+    pub(crate) dropped_count: u32,
 }
 
 impl EvictedHashMap {
@@ -79,6 +79,33 @@ impl EvictedHashMap {
         self.map.get(key)
     }
 
+    /// Returns an iterator from the least to the most recently set entry.
+    #[cfg(feature = "persistent_queue")]
+    pub(crate) fn iter_by_age(&self) -> impl Iterator<Item = (&Key, &Value)> {
+        self.evict_list
+            .iter()
+            .rev()
+            .filter_map(move |key| self.map.get_key_value(key))
+    }
+
+    /// Retains only the entries for which `f` returns `true`, `f` may also modify the values.
+    ///
+    /// Removed entries are not counted as dropped.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Key, &mut Value) -> bool,
+    {
+        let len = self.map.len();
+        self.map.retain(f);
+        if self.map.len() != len {
+            let map = &self.map;
+            self.evict_list = std::mem::take(&mut self.evict_list)
+                .into_iter()
+                .filter(|key| map.contains_key(key))
+                .collect();
+        }
+    }
+
     fn move_key_to_front(&mut self, key: Key) {
         if self.evict_list.is_empty() {
             // If empty, push front
@@ -171,4 +198,29 @@ mod tests {
                 .collect::<HashSet<_>>()
         );
     }
+
+    #[test]
+    fn retain_test() {
+        let max_len = 3;
+        let mut map = EvictedHashMap::new(max_len, max_len as usize);
+        for i in 0..max_len {
+            map.insert(Key::new(i.to_string()).i64(i.into()))
+        }
+
+        map.retain(|key, value| {
+            *value = Value::I64(10);
+            key.as_str() != "0"
+        });
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&Key::new("1")), Some(&Value::I64(10)));
+
+        // the removed key no longer counts toward the limit
+        map.insert(Key::new("3").bool(true));
+        map.insert(Key::new("4").bool(true));
+        assert_eq!(map.dropped_count(), 1);
+        assert_eq!(
+            map.map.keys().cloned().collect::<HashSet<_>>(),
+            ["2", "3", "4"].iter().map(|k| Key::new(*k)).collect()
+        );
+    }
 }
@@ -8,8 +8,8 @@ use std::collections::VecDeque;
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EvictedQueue<T> {
     queue: Option<VecDeque<T>>,
-    max_len: u32,
-    dropped_count: u32,
+    pub(crate) max_len: u32,
+    pub(crate) dropped_count: u32,
 }
 
 impl<T> EvictedQueue<T> {
@@ -48,6 +48,11 @@ impl<T> EvictedQueue<T> {
         Iter(self.queue.as_ref().map(|queue| queue.iter()))
     }
 
+    /// Returns a front-to-back iterator that allows modifying each element.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut(self.queue.as_mut().map(|queue| queue.iter_mut()))
+    }
+
     /// Returns the number of elements in the `EvictedQueue`.
     pub fn len(&self) -> usize {
         self.queue.as_ref().map_or(0, |queue| queue.len())
@@ -92,6 +97,18 @@ impl<'a, T: 'static> Iterator for Iter<'a, T> {
     }
 }
 
+/// A mutable iterator over the entries of an `EvictedQueue`.
+#[derive(Debug)]
+pub struct IterMut<'a, T>(Option<std::collections::vec_deque::IterMut<'a, T>>);
+
+impl<'a, T: 'static> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.as_mut().and_then(|iter| iter.next())
+    }
+}
+
 impl<T> Extend<T> for EvictedQueue<T> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         iter.into_iter().for_each(move |elt| self.push_back(elt));
@@ -0,0 +1,127 @@
+//! # Filtering Span Processor
+//!
+//! Forwards only the spans matching a predicate to another span processor, to
+//! drop noisy spans or route spans to different backends.
+use crate::export::trace::SpanData;
+use crate::trace::{Span, SpanProcessor};
+use opentelemetry_api::trace::TraceResult;
+use opentelemetry_api::Context;
+use std::fmt;
+
+/// A [`SpanProcessor`] that passes the ended spans for which a predicate
+/// returns `true` to another span processor and drops the others.
+///
+/// The predicate is called synchronously when spans end, it should be cheap and
+/// must not block. [`SpanProcessor::on_start`], `force_flush` and `shutdown`
+/// are always forwarded.
+///
+/// # Examples
+///
+/// Export only error spans and the spans of at least 1 ms that are not from
+/// a noisy library through the given processors:
+///
+/// ```
+/// use opentelemetry_api::trace::Status;
+/// use opentelemetry_sdk::trace::{FilteringSpanProcessor, SpanProcessor, TracerProvider};
+/// use std::time::Duration;
+///
+/// fn init_provider<A, B>(all_spans: A, error_spans: B) -> TracerProvider
+/// where
+///     A: SpanProcessor + 'static,
+///     B: SpanProcessor + 'static,
+/// {
+///     TracerProvider::builder()
+///         .with_span_processor(FilteringSpanProcessor::new(all_spans, |span| {
+///             span.instrumentation_lib.name != "noisy-library"
+///                 && span
+///                     .end_time
+///                     .duration_since(span.start_time)
+///                     .map_or(false, |duration| duration >= Duration::from_millis(1))
+///         }))
+///         .with_span_processor(FilteringSpanProcessor::new(error_spans, |span| {
+///             matches!(span.status, Status::Error { .. })
+///         }))
+///         .build()
+/// }
+/// ```
+pub struct FilteringSpanProcessor<P> {
+    inner: P,
+    predicate: Box<dyn Fn(&SpanData) -> bool + Send + Sync>,
+}
+
+impl<P: SpanProcessor> FilteringSpanProcessor<P> {
+    /// Create a new `FilteringSpanProcessor` forwarding the spans for which
+    /// `predicate` returns `true` to `inner`.
+    pub fn new<F>(inner: P, predicate: F) -> Self
+    where
+        F: Fn(&SpanData) -> bool + Send + Sync + 'static,
+    {
+        FilteringSpanProcessor {
+            inner,
+            predicate: Box::new(predicate),
+        }
+    }
+}
+
+impl<P: fmt::Debug> fmt::Debug for FilteringSpanProcessor<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilteringSpanProcessor")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<P: SpanProcessor> SpanProcessor for FilteringSpanProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx)
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if (self.predicate)(&span) {
+            self.inner.on_end(span)
+        }
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&mut self) -> TraceResult<()> {
+        self.inner.shutdown()
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::trace::{new_test_export_span_data, RecordingSpanProcessor};
+    use opentelemetry_api::trace::{SpanKind, Status};
+
+    #[test]
+    fn test_forward_matching_spans() {
+        let processor =
+            FilteringSpanProcessor::new(RecordingSpanProcessor::default(), |span: &SpanData| {
+                span.span_kind == SpanKind::Server || span.status != Status::Unset
+            });
+
+        let mut server = new_test_export_span_data();
+        server.span_kind = SpanKind::Server;
+        server.name = "server".into();
+        let mut error = new_test_export_span_data();
+        error.status = Status::error("failed");
+        error.name = "error".into();
+        let internal = new_test_export_span_data();
+
+        for span in [server, internal, error] {
+            processor.on_end(span);
+        }
+
+        let names: Vec<_> = processor
+            .inner
+            .into_spans()
+            .into_iter()
+            .map(|span| span.name)
+            .collect();
+        assert_eq!(names, vec!["server", "error"]);
+    }
+}
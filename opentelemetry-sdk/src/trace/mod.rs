@@ -9,29 +9,46 @@
 mod config;
 mod evicted_hash_map;
 mod evicted_queue;
+mod filtering;
 mod id_generator;
+#[cfg(feature = "persistent_queue")]
+mod persistent;
 mod provider;
+#[cfg(feature = "redaction_processor")]
+mod redaction;
 mod runtime;
 mod sampler;
 mod span;
 mod span_limit;
 mod span_processor;
+pub mod tail_sampling;
 mod tracer;
 
 pub use config::{config, Config};
 pub use evicted_hash_map::EvictedHashMap;
 pub use evicted_queue::EvictedQueue;
+pub use filtering::FilteringSpanProcessor;
 pub use id_generator::{aws::XrayIdGenerator, IdGenerator, RandomIdGenerator};
 pub use provider::{Builder, TracerProvider};
 pub use runtime::{TraceRuntime, TrySend};
-pub use sampler::{Sampler, ShouldSample};
+pub use sampler::{RateLimitingSampler, Sampler, ShouldSample};
 pub use span::Span;
 pub use span_limit::SpanLimits;
 pub use span_processor::{
     BatchConfig, BatchMessage, BatchSpanProcessor, BatchSpanProcessorBuilder, SimpleSpanProcessor,
     SpanProcessor,
 };
+pub use tail_sampling::TailSamplingSpanProcessor;
 pub use tracer::Tracer;
 
+#[cfg(feature = "persistent_queue")]
+pub use persistent::{PersistentSpanExporter, PersistentSpanExporterBuilder};
+#[cfg(feature = "redaction_processor")]
+pub use redaction::{RedactionRule, RedactionSpanProcessor, RedactionSpanProcessorBuilder};
+#[cfg(feature = "rule_based_sampler")]
+pub use sampler::{
+    AttributeRuleConfig, AttributeValueConfig, RuleBasedSampler, RuleBasedSamplerConfig,
+    SamplerConfig, SamplingRule, SamplingRuleBuilder, SamplingRuleConfig, SpanKindConfig,
+};
 #[cfg(feature = "jaeger_remote_sampler")]
-pub use sampler::{JaegerRemoteSampler, JaegerRemoteSamplerBuilder};
+pub use sampler::{JaegerFileSamplerBuilder, JaegerRemoteSampler, JaegerRemoteSamplerBuilder};
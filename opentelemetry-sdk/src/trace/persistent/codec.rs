@@ -0,0 +1,479 @@
+//! Binary encoding of `SpanData` batches stored in the persistent queue.
+//!
+//! Integers are little-endian, strings and collections are prefixed with their
+//! `u32` length, and each batch starts with a format version so that the
+//! encoding can evolve.
+use crate::export::trace::SpanData;
+use crate::trace::{EvictedHashMap, EvictedQueue};
+use crate::{InstrumentationLibrary, Resource};
+use opentelemetry_api::trace::{
+    Event, Link, SpanContext, SpanId, SpanKind, Status, TraceFlags, TraceId, TraceState,
+};
+use opentelemetry_api::{Array, Key, KeyValue, StringValue, Value};
+use std::borrow::Cow;
+use std::convert::TryInto;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const FORMAT_VERSION: u8 = 1;
+
+/// Error returned when a stored batch cannot be decoded.
+#[derive(Debug)]
+pub(crate) struct DecodeError(&'static str);
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid persisted span batch: {}", self.0)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+pub(crate) fn encode_batch(batch: &[SpanData]) -> Vec<u8> {
+    let mut encoder = Encoder(Vec::with_capacity(batch.len() * 256));
+    encoder.u8(FORMAT_VERSION);
+    encoder.len(batch.len());
+    for span in batch {
+        encoder.span(span);
+    }
+    encoder.0
+}
+
+pub(crate) fn decode_batch(bytes: &[u8]) -> Result<Vec<SpanData>, DecodeError> {
+    let mut decoder = Decoder(bytes);
+    if decoder.u8()? != FORMAT_VERSION {
+        return Err(DecodeError("unsupported format version"));
+    }
+    let len = decoder.len()?;
+    let mut batch = Vec::with_capacity(len);
+    for _ in 0..len {
+        batch.push(decoder.span()?);
+    }
+    if !decoder.0.is_empty() {
+        return Err(DecodeError("trailing bytes"));
+    }
+    Ok(batch)
+}
+
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) {
+        self.u32(len as u32);
+    }
+
+    fn str(&mut self, value: &str) {
+        self.len(value.len());
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn opt_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                self.str(value);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn time(&mut self, time: SystemTime) {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        self.u64(since_epoch.as_secs());
+        self.u32(since_epoch.subsec_nanos());
+    }
+
+    fn value(&mut self, value: &Value) {
+        match value {
+            Value::Bool(value) => {
+                self.u8(0);
+                self.u8(*value as u8);
+            }
+            Value::I64(value) => {
+                self.u8(1);
+                self.u64(*value as u64);
+            }
+            Value::F64(value) => {
+                self.u8(2);
+                self.u64(value.to_bits());
+            }
+            Value::String(value) => {
+                self.u8(3);
+                self.str(value.as_str());
+            }
+            Value::Array(Array::Bool(values)) => {
+                self.u8(4);
+                self.len(values.len());
+                values.iter().for_each(|value| self.u8(*value as u8));
+            }
+            Value::Array(Array::I64(values)) => {
+                self.u8(5);
+                self.len(values.len());
+                values.iter().for_each(|value| self.u64(*value as u64));
+            }
+            Value::Array(Array::F64(values)) => {
+                self.u8(6);
+                self.len(values.len());
+                values.iter().for_each(|value| self.u64(value.to_bits()));
+            }
+            Value::Array(Array::String(values)) => {
+                self.u8(7);
+                self.len(values.len());
+                values.iter().for_each(|value| self.str(value.as_str()));
+            }
+        }
+    }
+
+    fn attributes<'a, I>(&mut self, len: usize, attributes: I)
+    where
+        I: Iterator<Item = (&'a Key, &'a Value)>,
+    {
+        self.len(len);
+        for (key, value) in attributes {
+            self.str(key.as_str());
+            self.value(value);
+        }
+    }
+
+    fn key_values(&mut self, attributes: &[KeyValue]) {
+        self.attributes(
+            attributes.len(),
+            attributes.iter().map(|kv| (&kv.key, &kv.value)),
+        );
+    }
+
+    fn span_context(&mut self, span_context: &SpanContext) {
+        self.0
+            .extend_from_slice(&span_context.trace_id().to_bytes());
+        self.0.extend_from_slice(&span_context.span_id().to_bytes());
+        self.u8(span_context.trace_flags().to_u8());
+        self.u8(span_context.is_remote() as u8);
+        self.str(&span_context.trace_state().header());
+    }
+
+    fn span(&mut self, span: &SpanData) {
+        self.span_context(&span.span_context);
+        self.0.extend_from_slice(&span.parent_span_id.to_bytes());
+        self.u8(match span.span_kind {
+            SpanKind::Client => 0,
+            SpanKind::Server => 1,
+            SpanKind::Producer => 2,
+            SpanKind::Consumer => 3,
+            SpanKind::Internal => 4,
+        });
+        self.str(&span.name);
+        self.time(span.start_time);
+        self.time(span.end_time);
+
+        self.u32(span.attributes.max_len);
+        self.u32(span.attributes.dropped_count());
+        self.attributes(span.attributes.len(), span.attributes.iter_by_age());
+
+        self.u32(span.events.max_len);
+        self.u32(span.events.dropped_count());
+        self.len(span.events.len());
+        for event in span.events.iter() {
+            self.str(&event.name);
+            self.time(event.timestamp);
+            self.key_values(&event.attributes);
+            self.u32(event.dropped_attributes_count);
+        }
+
+        self.u32(span.links.max_len);
+        self.u32(span.links.dropped_count());
+        self.len(span.links.len());
+        for link in span.links.iter() {
+            self.span_context(&link.span_context);
+            self.key_values(&link.attributes);
+            self.u32(link.dropped_attributes_count);
+        }
+
+        match &span.status {
+            Status::Unset => self.u8(0),
+            Status::Error { description } => {
+                self.u8(1);
+                self.str(description);
+            }
+            Status::Ok => self.u8(2),
+        }
+
+        self.opt_str(span.resource.schema_url());
+        self.attributes(span.resource.len(), span.resource.iter());
+
+        let library = &span.instrumentation_lib;
+        self.str(&library.name);
+        self.opt_str(library.version.as_deref());
+        self.opt_str(library.schema_url.as_deref());
+    }
+}
+
+struct Decoder<'a>(&'a [u8]);
+
+impl<'a> Decoder<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.0.len() < len {
+            return Err(DecodeError("unexpected end of batch"));
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self
+            .bytes(N)?
+            .try_into()
+            .expect("slice has the array length"))
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, DecodeError> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn len(&mut self) -> Result<usize, DecodeError> {
+        let len = self.u32()? as usize;
+        // every item takes at least one byte, reject lengths no batch can hold
+        if len > self.0.len() {
+            return Err(DecodeError("invalid length"));
+        }
+        Ok(len)
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let len = self.len()?;
+        String::from_utf8(self.bytes(len)?.to_vec()).map_err(|_| DecodeError("invalid string"))
+    }
+
+    fn opt_string(&mut self) -> Result<Option<String>, DecodeError> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => self.string().map(Some),
+        }
+    }
+
+    fn time(&mut self) -> Result<SystemTime, DecodeError> {
+        let secs = self.u64()?;
+        let nanos = self.u32()?;
+        UNIX_EPOCH
+            .checked_add(Duration::new(secs, nanos))
+            .ok_or(DecodeError("invalid time"))
+    }
+
+    fn value(&mut self) -> Result<Value, DecodeError> {
+        Ok(match self.u8()? {
+            0 => Value::Bool(self.bool()?),
+            1 => Value::I64(self.u64()? as i64),
+            2 => Value::F64(f64::from_bits(self.u64()?)),
+            3 => Value::String(self.string()?.into()),
+            4 => Value::Array(Array::Bool(self.items(Self::bool)?)),
+            5 => Value::Array(Array::I64(self.items(|d| Ok(d.u64()? as i64))?)),
+            6 => Value::Array(Array::F64(self.items(|d| Ok(f64::from_bits(d.u64()?)))?)),
+            7 => Value::Array(Array::String(
+                self.items(|d| Ok(StringValue::from(d.string()?)))?,
+            )),
+            _ => return Err(DecodeError("invalid value type")),
+        })
+    }
+
+    fn items<T, F>(&mut self, mut item: F) -> Result<Vec<T>, DecodeError>
+    where
+        F: FnMut(&mut Self) -> Result<T, DecodeError>,
+    {
+        let len = self.len()?;
+        let mut items = Vec::with_capacity(len);
+        for _ in 0..len {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    fn key_values(&mut self) -> Result<Vec<KeyValue>, DecodeError> {
+        self.items(|d| Ok(KeyValue::new(d.string()?, d.value()?)))
+    }
+
+    fn span_context(&mut self) -> Result<SpanContext, DecodeError> {
+        let trace_id = TraceId::from_bytes(self.array()?);
+        let span_id = SpanId::from_bytes(self.array()?);
+        let trace_flags = TraceFlags::new(self.u8()?);
+        let is_remote = self.bool()?;
+        let trace_state = TraceState::from_str(&self.string()?)
+            .map_err(|_| DecodeError("invalid trace state"))?;
+        Ok(SpanContext::new(
+            trace_id,
+            span_id,
+            trace_flags,
+            is_remote,
+            trace_state,
+        ))
+    }
+
+    fn span(&mut self) -> Result<SpanData, DecodeError> {
+        let span_context = self.span_context()?;
+        let parent_span_id = SpanId::from_bytes(self.array()?);
+        let span_kind = match self.u8()? {
+            0 => SpanKind::Client,
+            1 => SpanKind::Server,
+            2 => SpanKind::Producer,
+            3 => SpanKind::Consumer,
+            4 => SpanKind::Internal,
+            _ => return Err(DecodeError("invalid span kind")),
+        };
+        let name = self.string()?;
+        let start_time = self.time()?;
+        let end_time = self.time()?;
+
+        let max_len = self.u32()?;
+        let dropped_count = self.u32()?;
+        let key_values = self.key_values()?;
+        let mut attributes = EvictedHashMap::new(max_len, key_values.len());
+        key_values.into_iter().for_each(|kv| attributes.insert(kv));
+        attributes.dropped_count = dropped_count;
+
+        let mut events = EvictedQueue::new(self.u32()?);
+        let dropped_count = self.u32()?;
+        let mut items = self.items(|d| {
+            Ok(Event::new(
+                d.string()?,
+                d.time()?,
+                d.key_values()?,
+                d.u32()?,
+            ))
+        })?;
+        events.append_vec(&mut items);
+        events.dropped_count = dropped_count;
+
+        let mut links = EvictedQueue::new(self.u32()?);
+        let dropped_count = self.u32()?;
+        let mut items = self.items(|d| {
+            let mut link = Link::new(d.span_context()?, d.key_values()?);
+            link.dropped_attributes_count = d.u32()?;
+            Ok(link)
+        })?;
+        links.append_vec(&mut items);
+        links.dropped_count = dropped_count;
+
+        let status = match self.u8()? {
+            0 => Status::Unset,
+            1 => Status::error(self.string()?),
+            2 => Status::Ok,
+            _ => return Err(DecodeError("invalid status")),
+        };
+
+        let schema_url = self.opt_string()?;
+        let resource_attributes = self.key_values()?;
+        let resource = match schema_url {
+            Some(schema_url) => Resource::from_schema_url(resource_attributes, schema_url),
+            None => Resource::new(resource_attributes),
+        };
+
+        let instrumentation_lib = InstrumentationLibrary::new(
+            Cow::Owned(self.string()?),
+            self.opt_string()?.map(Cow::Owned),
+            self.opt_string()?.map(Cow::Owned),
+        );
+
+        Ok(SpanData {
+            span_context,
+            parent_span_id,
+            span_kind,
+            name: name.into(),
+            start_time,
+            end_time,
+            attributes,
+            events,
+            links,
+            status,
+            resource: Cow::Owned(resource),
+            instrumentation_lib,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::trace::new_test_export_span_data;
+
+    #[test]
+    fn test_round_trip() {
+        let mut span = new_test_export_span_data();
+        span.span_context = SpanContext::new(
+            TraceId::from_u128(42),
+            SpanId::from_u64(7),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::from_key_value(vec![("vendor", "value")]).unwrap(),
+        );
+        span.parent_span_id = SpanId::from_u64(3);
+        span.span_kind = SpanKind::Server;
+        span.attributes.insert(KeyValue::new("bool", true));
+        span.attributes.insert(KeyValue::new("i64", -12));
+        span.attributes.insert(KeyValue::new("f64", 1.5));
+        span.attributes.insert(KeyValue::new("string", "value"));
+        span.attributes.insert(KeyValue::new(
+            "array",
+            Value::Array(vec![StringValue::from("a"), "b".into()].into()),
+        ));
+        span.attributes.dropped_count = 3;
+        span.events.push_back(Event::new(
+            "event",
+            span.start_time,
+            vec![KeyValue::new("key", Value::Array(vec![1i64, 2].into()))],
+            1,
+        ));
+        span.links.push_back(Link::new(
+            SpanContext::new(
+                TraceId::from_u128(1),
+                SpanId::from_u64(1),
+                TraceFlags::default(),
+                false,
+                TraceState::default(),
+            ),
+            vec![KeyValue::new("link", "value")],
+        ));
+        span.status = Status::error("failed");
+        span.resource = Cow::Owned(Resource::from_schema_url(
+            vec![KeyValue::new("service.name", "test")],
+            "https://opentelemetry.io/schemas/1.9.0",
+        ));
+        span.instrumentation_lib =
+            InstrumentationLibrary::new("library", Some("1.0.0"), Some("schema"));
+
+        let batch = vec![span, new_test_export_span_data()];
+        let decoded = decode_batch(&encode_batch(&batch)).unwrap();
+        assert_eq!(decoded, batch);
+    }
+
+    #[test]
+    fn test_decode_invalid_batch() {
+        let encoded = encode_batch(&[new_test_export_span_data()]);
+        assert!(decode_batch(&encoded[..encoded.len() - 1]).is_err());
+        assert!(decode_batch(&[FORMAT_VERSION + 1, 0, 0, 0, 0]).is_err());
+        assert!(decode_batch(&[FORMAT_VERSION, 255, 255, 255, 255]).is_err());
+    }
+}
@@ -0,0 +1,363 @@
+//! # Persistent Span Exporter
+//!
+//! Buffers exported spans on disk so that they survive exporter outages and
+//! process restarts.
+use crate::export::trace::{ExportResult, SpanData, SpanExporter};
+use futures_util::future::BoxFuture;
+use opentelemetry_api::global;
+use opentelemetry_api::trace::{TraceError, TraceResult};
+use queue::SegmentedQueue;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+mod codec;
+mod queue;
+
+/// Default maximum size of the queue, 64 MiB.
+const DEFAULT_MAX_SIZE: u64 = 64 * 1024 * 1024;
+/// Default maximum size of a segment file, 4 MiB.
+const DEFAULT_MAX_SEGMENT_SIZE: u64 = 4 * 1024 * 1024;
+/// Default delay before retrying a failed export.
+const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A [`SpanExporter`] that stores the batches it receives in an on-disk queue
+/// and replays them to another exporter from a background thread.
+///
+/// Batches are appended to segment files in a directory and removed once the
+/// wrapped exporter succeeds. Failed exports are retried after a retry
+/// interval, and batches left in the directory when the process stops are
+/// replayed when an exporter is built on the same directory again. When the
+/// queue reaches its maximum size, its oldest segments are dropped.
+///
+/// Writes are not synced to disk, batches can still be lost if the operating
+/// system crashes. A directory must be used by only one exporter at a time.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry_api::trace::TraceResult;
+/// use opentelemetry_sdk::export::trace::SpanExporter;
+/// use opentelemetry_sdk::trace::{PersistentSpanExporter, TracerProvider};
+///
+/// fn init_provider<E>(exporter: E) -> TraceResult<TracerProvider>
+/// where
+///     E: SpanExporter + 'static,
+/// {
+///     let exporter = PersistentSpanExporter::builder(exporter, "/var/lib/otel/spans")
+///         .with_max_size(256 * 1024 * 1024)
+///         .build()?;
+///
+///     Ok(TracerProvider::builder()
+///         .with_simple_exporter(exporter)
+///         .build())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PersistentSpanExporter {
+    queue: Arc<Mutex<SegmentedQueue>>,
+    sender: crossbeam_channel::Sender<Message>,
+    worker: Option<thread::JoinHandle<()>>,
+}
+
+#[derive(Debug)]
+enum Message {
+    /// New batches were added to the queue.
+    Pushed,
+    Shutdown,
+}
+
+impl PersistentSpanExporter {
+    /// Create a new [`PersistentSpanExporterBuilder`] replaying the batches
+    /// stored in `path` to `exporter`.
+    pub fn builder<E, P>(exporter: E, path: P) -> PersistentSpanExporterBuilder
+    where
+        E: SpanExporter + 'static,
+        P: Into<PathBuf>,
+    {
+        PersistentSpanExporterBuilder {
+            exporter: Box::new(exporter),
+            path: path.into(),
+            max_size: None,
+            max_segment_size: None,
+            retry_interval: None,
+        }
+    }
+}
+
+impl SpanExporter for PersistentSpanExporter {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        let encoded = codec::encode_batch(&batch);
+        let result = match self.queue.lock() {
+            Ok(mut queue) => queue.push(&encoded).map_err(queue_error),
+            Err(err) => Err(TraceError::from(err.to_string())),
+        };
+
+        let result = result.map(|evicted| {
+            if evicted > 0 {
+                global::handle_error(TraceError::from(format!(
+                    "persistent span queue is full, dropped its {} oldest segment(s)",
+                    evicted
+                )));
+            }
+            // the worker is already notified if the channel is full
+            let _ = self.sender.try_send(Message::Pushed);
+        });
+
+        Box::pin(std::future::ready(result))
+    }
+
+    fn shutdown(&mut self) {
+        // the stored batches are replayed on the next start
+        let _ = self.sender.send(Message::Shutdown);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for PersistentSpanExporter {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn queue_error(err: std::io::Error) -> TraceError {
+    TraceError::Other(Box::new(err))
+}
+
+/// Replays the queue to the wrapped exporter.
+struct Worker {
+    exporter: Box<dyn SpanExporter>,
+    queue: Arc<Mutex<SegmentedQueue>>,
+    receiver: crossbeam_channel::Receiver<Message>,
+    retry_interval: Duration,
+}
+
+impl Worker {
+    fn run(mut self) {
+        // replay the batches stored by a previous process first
+        let mut next = self.export_queue();
+        loop {
+            let message = match next {
+                Next::Stop => break,
+                Next::Retry(interval) => match self.receiver.recv_timeout(interval) {
+                    Ok(message) => message,
+                    Err(crossbeam_channel::RecvTimeoutError::Timeout) => Message::Pushed,
+                    Err(crossbeam_channel::RecvTimeoutError::Disconnected) => break,
+                },
+                Next::Wait => match self.receiver.recv() {
+                    Ok(message) => message,
+                    Err(_) => break,
+                },
+            };
+
+            match message {
+                Message::Pushed => next = self.export_queue(),
+                Message::Shutdown => break,
+            }
+        }
+
+        self.exporter.shutdown();
+    }
+
+    /// Export the queued batches until the queue is empty or an export fails.
+    fn export_queue(&mut self) -> Next {
+        loop {
+            if let Ok(Message::Shutdown) = self.receiver.try_recv() {
+                return Next::Stop;
+            }
+
+            let record = match self.queue.lock() {
+                Ok(mut queue) => queue.peek(),
+                Err(_) => return Next::Stop,
+            };
+            let record = match record {
+                Ok(Some(record)) => record,
+                Ok(None) => return Next::Wait,
+                Err(err) if err.kind() == std::io::ErrorKind::InvalidData => {
+                    global::handle_error(queue_error(err));
+                    continue;
+                }
+                Err(err) => {
+                    global::handle_error(queue_error(err));
+                    return Next::Retry(self.retry_interval);
+                }
+            };
+
+            match codec::decode_batch(&record.data) {
+                Ok(batch) => {
+                    if let Err(err) = futures_executor::block_on(self.exporter.export(batch)) {
+                        global::handle_error(err);
+                        return Next::Retry(self.retry_interval);
+                    }
+                }
+                Err(err) => global::handle_error(TraceError::Other(Box::new(err))),
+            }
+
+            if let Ok(mut queue) = self.queue.lock() {
+                if let Err(err) = queue.pop(&record) {
+                    global::handle_error(queue_error(err));
+                }
+            }
+        }
+    }
+}
+
+/// What the worker does after exporting the queue.
+enum Next {
+    /// Wait for new batches.
+    Wait,
+    /// Retry after the given interval.
+    Retry(Duration),
+    Stop,
+}
+
+/// A builder for creating [`PersistentSpanExporter`] instances.
+#[derive(Debug)]
+pub struct PersistentSpanExporterBuilder {
+    exporter: Box<dyn SpanExporter>,
+    path: PathBuf,
+    max_size: Option<u64>,
+    max_segment_size: Option<u64>,
+    retry_interval: Option<Duration>,
+}
+
+impl PersistentSpanExporterBuilder {
+    /// Set the maximum size in bytes of the queue, 64 MiB by default.
+    ///
+    /// The oldest segments are dropped to keep the queue under this size.
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Set the maximum size in bytes of a segment file, 4 MiB by default.
+    ///
+    /// Smaller segments make eviction drop fewer batches at once.
+    pub fn with_max_segment_size(mut self, max_segment_size: u64) -> Self {
+        self.max_segment_size = Some(max_segment_size);
+        self
+    }
+
+    /// Set the delay before retrying a failed export, 5 seconds by default.
+    pub fn with_retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = Some(retry_interval);
+        self
+    }
+
+    /// Open the queue and start replaying it.
+    ///
+    /// Returns an error if the queue directory cannot be created or read.
+    pub fn build(self) -> TraceResult<PersistentSpanExporter> {
+        let queue = SegmentedQueue::open(
+            &self.path,
+            self.max_size.unwrap_or(DEFAULT_MAX_SIZE),
+            self.max_segment_size.unwrap_or(DEFAULT_MAX_SEGMENT_SIZE),
+        )
+        .map_err(queue_error)?;
+        let queue = Arc::new(Mutex::new(queue));
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        let worker = Worker {
+            exporter: self.exporter,
+            queue: queue.clone(),
+            receiver,
+            retry_interval: self.retry_interval.unwrap_or(DEFAULT_RETRY_INTERVAL),
+        };
+        let worker = thread::Builder::new()
+            .name("opentelemetry-persistent-exporter".to_string())
+            .spawn(move || worker.run())
+            .map_err(queue_error)?;
+
+        Ok(PersistentSpanExporter {
+            queue,
+            sender,
+            worker: Some(worker),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::queue::TempDir;
+    use super::*;
+    use crate::testing::trace::new_test_export_span_data;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Instant;
+
+    /// Records the exported spans and fails while `failing` is set.
+    #[derive(Debug, Clone, Default)]
+    struct TestExporter {
+        spans: Arc<Mutex<Vec<SpanData>>>,
+        failing: Arc<AtomicBool>,
+    }
+
+    impl SpanExporter for TestExporter {
+        fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+            let result = if self.failing.load(Ordering::SeqCst) {
+                Err(TraceError::from("unreachable".to_string()))
+            } else {
+                self.spans.lock().unwrap().extend(batch);
+                Ok(())
+            };
+            Box::pin(std::future::ready(result))
+        }
+    }
+
+    fn span(name: &'static str) -> SpanData {
+        let mut span = new_test_export_span_data();
+        span.name = name.into();
+        span
+    }
+
+    fn wait_for_spans(exporter: &TestExporter, count: usize) -> Vec<String> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let names: Vec<_> = exporter
+                .spans
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|span| span.name.to_string())
+                .collect();
+            if names.len() >= count || Instant::now() > deadline {
+                return names;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_replay_after_failure_and_restart() {
+        let dir = TempDir::new("exporter");
+        let exporter = TestExporter::default();
+        exporter.failing.store(true, Ordering::SeqCst);
+
+        let mut persistent = PersistentSpanExporter::builder(exporter.clone(), &dir.0)
+            .with_retry_interval(Duration::from_millis(10))
+            .build()
+            .unwrap();
+        futures_executor::block_on(persistent.export(vec![span("first")])).unwrap();
+        futures_executor::block_on(persistent.export(vec![span("second")])).unwrap();
+        // the batches stay queued while the exporter fails
+        thread::sleep(Duration::from_millis(50));
+        persistent.shutdown();
+        assert!(exporter.spans.lock().unwrap().is_empty());
+
+        // a new exporter on the same directory replays them
+        exporter.failing.store(false, Ordering::SeqCst);
+        let mut persistent = PersistentSpanExporter::builder(exporter.clone(), &dir.0)
+            .build()
+            .unwrap();
+        assert_eq!(wait_for_spans(&exporter, 2), vec!["first", "second"]);
+
+        futures_executor::block_on(persistent.export(vec![span("third")])).unwrap();
+        assert_eq!(
+            wait_for_spans(&exporter, 3),
+            vec!["first", "second", "third"]
+        );
+        persistent.shutdown();
+    }
+}
@@ -0,0 +1,441 @@
+//! An append-only queue of records stored in segment files.
+//!
+//! Records are appended to the newest segment, and a new segment is started
+//! once it reaches the maximum segment size or when the queue is opened. The
+//! position of the oldest unread record is saved in a cursor file each time a
+//! record is removed, fully read segments are deleted, and the oldest segments
+//! are evicted when the queue would exceed its maximum size.
+//!
+//! Each record is its `u32` length and its CRC-32 checksum followed by the
+//! record itself, so that records torn by a crash are detected and skipped.
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const SEGMENT_EXTENSION: &str = "segment";
+const CURSOR_FILE: &str = "cursor";
+const HEADER_LEN: u64 = 8;
+
+/// A position in the queue.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Position {
+    segment: u64,
+    offset: u64,
+}
+
+/// A record read from the queue.
+#[derive(Debug)]
+pub(crate) struct Record {
+    position: Position,
+    next: Position,
+    pub(crate) data: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct Segment {
+    id: u64,
+    len: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct SegmentedQueue {
+    dir: PathBuf,
+    max_size: u64,
+    max_segment_size: u64,
+    /// Segments from the oldest to the one being written.
+    segments: VecDeque<Segment>,
+    writer: File,
+    cursor: Position,
+}
+
+impl SegmentedQueue {
+    /// Open the queue stored in `dir`, creating it if needed.
+    pub(crate) fn open(dir: &Path, max_size: u64, max_segment_size: u64) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+
+        let mut segments = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_EXTENSION) {
+                continue;
+            }
+            if let Some(id) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse().ok())
+            {
+                segments.push(Segment {
+                    id,
+                    len: fs::metadata(&path)?.len(),
+                });
+            }
+        }
+        segments.sort_by_key(|segment| segment.id);
+        let mut segments = VecDeque::from(segments);
+
+        // always write to a new segment, the last one may end with a torn record
+        let writer_id = segments.back().map_or(0, |segment| segment.id + 1);
+        let writer = create_segment(dir, writer_id)?;
+        segments.push_back(Segment {
+            id: writer_id,
+            len: 0,
+        });
+
+        let first_id = segments.front().map_or(writer_id, |segment| segment.id);
+        let cursor = read_cursor(dir)
+            .filter(|cursor| segments.iter().any(|segment| segment.id == cursor.segment))
+            .unwrap_or(Position {
+                segment: first_id,
+                offset: 0,
+            });
+
+        let mut queue = SegmentedQueue {
+            dir: dir.to_path_buf(),
+            max_size,
+            max_segment_size,
+            segments,
+            writer,
+            cursor,
+        };
+        // remove segments fully read before the cursor was saved
+        while queue.segments.front().map(|segment| segment.id) != Some(queue.cursor.segment) {
+            queue.remove_oldest_segment()?;
+        }
+
+        Ok(queue)
+    }
+
+    /// Append a record, evicting the oldest segments if the queue would
+    /// exceed its maximum size.
+    ///
+    /// Returns the number of evicted segments.
+    pub(crate) fn push(&mut self, data: &[u8]) -> io::Result<usize> {
+        let record_len = HEADER_LEN + data.len() as u64;
+        if record_len > self.max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "record of {} bytes exceeds the queue max size of {} bytes",
+                    record_len, self.max_size
+                ),
+            ));
+        }
+
+        if self.writer_len() > 0 && self.writer_len() + record_len > self.max_segment_size {
+            self.roll()?;
+        }
+
+        let mut evicted = 0;
+        while self.size() + record_len > self.max_size {
+            if self.segments.len() == 1 {
+                self.roll()?;
+            }
+            if self.remove_oldest_segment()? > 0 {
+                evicted += 1;
+            }
+        }
+
+        let mut record = Vec::with_capacity(record_len as usize);
+        record.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        record.extend_from_slice(&crc32(data).to_le_bytes());
+        record.extend_from_slice(data);
+        if let Err(err) = self.writer.write_all(&record) {
+            // the segment may end with a partial record, keep it out of later writes
+            let _ = self.roll();
+            return Err(err);
+        }
+        if let Some(segment) = self.segments.back_mut() {
+            segment.len += record_len;
+        }
+
+        Ok(evicted)
+    }
+
+    /// Read the oldest record without removing it.
+    ///
+    /// A corrupted record is skipped together with the rest of its segment
+    /// and reported with an `InvalidData` error, the next call reads the
+    /// following segment.
+    pub(crate) fn peek(&mut self) -> io::Result<Option<Record>> {
+        loop {
+            let segment_len = match self.segments.front() {
+                Some(segment) => segment.len,
+                None => return Ok(None),
+            };
+            if self.cursor.offset >= segment_len {
+                if self.segments.len() == 1 {
+                    return Ok(None);
+                }
+                self.remove_oldest_segment()?;
+                self.save_cursor()?;
+                continue;
+            }
+
+            return match self.read_at(self.cursor, segment_len) {
+                Ok(record) => Ok(Some(record)),
+                Err(err) => {
+                    self.cursor.offset = segment_len;
+                    Err(err)
+                }
+            };
+        }
+    }
+
+    /// Remove `record`, which must have been returned by `peek`.
+    ///
+    /// Nothing is removed if the record has already been evicted.
+    pub(crate) fn pop(&mut self, record: &Record) -> io::Result<()> {
+        if record.position != self.cursor {
+            return Ok(());
+        }
+        self.cursor = record.next;
+        self.save_cursor()
+    }
+
+    /// The size in bytes of the records in the queue.
+    pub(crate) fn size(&self) -> u64 {
+        self.segments.iter().map(|segment| segment.len).sum()
+    }
+
+    fn writer_len(&self) -> u64 {
+        self.segments.back().map_or(0, |segment| segment.len)
+    }
+
+    fn read_at(&self, position: Position, segment_len: u64) -> io::Result<Record> {
+        let mut file = File::open(segment_path(&self.dir, position.segment))?;
+        file.seek(SeekFrom::Start(position.offset))?;
+
+        let mut header = [0; HEADER_LEN as usize];
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        if segment_len - position.offset < HEADER_LEN {
+            return Err(invalid("truncated record header"));
+        }
+        file.read_exact(&mut header)?;
+        let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        let checksum = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        if segment_len - position.offset - HEADER_LEN < len {
+            return Err(invalid("truncated record"));
+        }
+
+        let mut data = vec![0; len as usize];
+        file.read_exact(&mut data)?;
+        if crc32(&data) != checksum {
+            return Err(invalid("record checksum mismatch"));
+        }
+
+        Ok(Record {
+            position,
+            next: Position {
+                segment: position.segment,
+                offset: position.offset + HEADER_LEN + len,
+            },
+            data,
+        })
+    }
+
+    /// Start writing to a new segment.
+    fn roll(&mut self) -> io::Result<()> {
+        let id = self.segments.back().map_or(0, |segment| segment.id + 1);
+        self.writer = create_segment(&self.dir, id)?;
+        self.segments.push_back(Segment { id, len: 0 });
+        Ok(())
+    }
+
+    /// Remove the oldest segment and return its length.
+    fn remove_oldest_segment(&mut self) -> io::Result<u64> {
+        let mut len = 0;
+        if let Some(segment) = self.segments.pop_front() {
+            len = segment.len;
+            fs::remove_file(segment_path(&self.dir, segment.id))?;
+            if self.cursor.segment <= segment.id {
+                self.cursor = Position {
+                    segment: self.segments.front().map_or(segment.id + 1, |next| next.id),
+                    offset: 0,
+                };
+            }
+        }
+        Ok(len)
+    }
+
+    fn save_cursor(&self) -> io::Result<()> {
+        // write then rename so that a crash never leaves a partial cursor
+        let tmp = self.dir.join(format!("{}.tmp", CURSOR_FILE));
+        fs::write(
+            &tmp,
+            format!("{} {}", self.cursor.segment, self.cursor.offset),
+        )?;
+        fs::rename(tmp, self.dir.join(CURSOR_FILE))
+    }
+}
+
+fn segment_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", id, SEGMENT_EXTENSION))
+}
+
+fn create_segment(dir: &Path, id: u64) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(segment_path(dir, id))
+}
+
+fn read_cursor(dir: &Path) -> Option<Position> {
+    let cursor = fs::read_to_string(dir.join(CURSOR_FILE)).ok()?;
+    let mut fields = cursor.split_whitespace().map(|field| field.parse().ok());
+    Some(Position {
+        segment: fields.next()??,
+        offset: fields.next()??,
+    })
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// A temporary directory removed when dropped.
+#[cfg(test)]
+pub(super) struct TempDir(pub(super) PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    pub(super) fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "opentelemetry-persistent-queue-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        TempDir(dir)
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pop_all(queue: &mut SegmentedQueue) -> Vec<Vec<u8>> {
+        let mut records = Vec::new();
+        while let Some(record) = queue.peek().unwrap() {
+            queue.pop(&record).unwrap();
+            records.push(record.data);
+        }
+        records
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_push_peek_pop() {
+        let dir = TempDir::new("push-peek-pop");
+        let mut queue = SegmentedQueue::open(&dir.0, 1024, 32).unwrap();
+        assert!(queue.peek().unwrap().is_none());
+
+        for i in 0..5u8 {
+            assert_eq!(queue.push(&[i; 10]).unwrap(), 0);
+        }
+        // 18 bytes records, one per 32 bytes segment
+        assert_eq!(queue.segments.len(), 5);
+
+        let record = queue.peek().unwrap().unwrap();
+        assert_eq!(record.data, vec![0; 10]);
+        // peeking again returns the same record until it is popped
+        assert_eq!(queue.peek().unwrap().unwrap().data, vec![0; 10]);
+        queue.pop(&record).unwrap();
+
+        assert_eq!(
+            pop_all(&mut queue),
+            (1..5u8).map(|i| vec![i; 10]).collect::<Vec<_>>()
+        );
+        // read segments are deleted
+        assert_eq!(queue.segments.len(), 1);
+    }
+
+    #[test]
+    fn test_reopen() {
+        let dir = TempDir::new("reopen");
+        {
+            let mut queue = SegmentedQueue::open(&dir.0, 1024, 1024).unwrap();
+            queue.push(b"first").unwrap();
+            queue.push(b"second").unwrap();
+            queue.push(b"third").unwrap();
+            let record = queue.peek().unwrap().unwrap();
+            queue.pop(&record).unwrap();
+        }
+
+        let mut queue = SegmentedQueue::open(&dir.0, 1024, 1024).unwrap();
+        queue.push(b"fourth").unwrap();
+        assert_eq!(
+            pop_all(&mut queue),
+            vec![b"second".to_vec(), b"third".to_vec(), b"fourth".to_vec()]
+        );
+        drop(queue);
+
+        let mut queue = SegmentedQueue::open(&dir.0, 1024, 1024).unwrap();
+        assert!(queue.peek().unwrap().is_none());
+        assert_eq!(queue.size(), 0);
+    }
+
+    #[test]
+    fn test_evict_oldest_segments() {
+        let dir = TempDir::new("evict");
+        let mut queue = SegmentedQueue::open(&dir.0, 40, 20).unwrap();
+
+        // 18 bytes records, the third one evicts the first segment
+        assert_eq!(queue.push(&[1; 10]).unwrap(), 0);
+        let record = queue.peek().unwrap().unwrap();
+        assert_eq!(queue.push(&[2; 10]).unwrap(), 0);
+        assert_eq!(queue.push(&[3; 10]).unwrap(), 1);
+        assert!(queue.size() <= 40);
+
+        // popping an evicted record does nothing
+        queue.pop(&record).unwrap();
+        assert_eq!(pop_all(&mut queue), vec![vec![2; 10], vec![3; 10]]);
+
+        assert!(queue.push(&[0; 40]).is_err());
+    }
+
+    #[test]
+    fn test_skip_corrupted_segment() {
+        let dir = TempDir::new("corrupted");
+        {
+            let mut queue = SegmentedQueue::open(&dir.0, 1024, 1024).unwrap();
+            queue.push(b"valid").unwrap();
+            queue.push(b"torn").unwrap();
+        }
+        // simulate a crash in the middle of the second record
+        let path = segment_path(&dir.0, 0);
+        let len = fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 2)
+            .unwrap();
+
+        let mut queue = SegmentedQueue::open(&dir.0, 1024, 1024).unwrap();
+        queue.push(b"after restart").unwrap();
+        let record = queue.peek().unwrap().unwrap();
+        assert_eq!(record.data, b"valid");
+        queue.pop(&record).unwrap();
+        assert_eq!(queue.peek().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(pop_all(&mut queue), vec![b"after restart".to_vec()]);
+    }
+}
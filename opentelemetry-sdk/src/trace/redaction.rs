@@ -0,0 +1,393 @@
+//! # Redaction Span Processor
+//!
+//! Scrubs sensitive data, like emails, tokens or card numbers, from spans
+//! before they are handed to another span processor and leave the process.
+use crate::export::trace::SpanData;
+use crate::trace::{Span, SpanProcessor};
+use opentelemetry_api::trace::{Status, TraceError, TraceResult};
+use opentelemetry_api::{Array, Context, Key, KeyValue, StringValue, Value};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// A rule applied by the [`RedactionSpanProcessor`].
+#[derive(Clone, Debug)]
+pub struct RedactionRule(Rule);
+
+#[derive(Clone, Debug)]
+enum Rule {
+    Delete(HashSet<Key>),
+    Hash(HashSet<Key>),
+    Mask { pattern: Regex, replacement: String },
+    Allow(HashSet<Key>),
+}
+
+impl RedactionRule {
+    /// Remove the attributes with one of the given keys.
+    pub fn delete_keys<T, K>(keys: T) -> Self
+    where
+        T: IntoIterator<Item = K>,
+        K: Into<Key>,
+    {
+        RedactionRule(Rule::Delete(keys.into_iter().map(Into::into).collect()))
+    }
+
+    /// Replace the values of the attributes with one of the given keys by the
+    /// hex encoded SHA-256 hash of their string representation, so that equal
+    /// values can still be correlated.
+    pub fn hash_keys<T, K>(keys: T) -> Self
+    where
+        T: IntoIterator<Item = K>,
+        K: Into<Key>,
+    {
+        RedactionRule(Rule::Hash(keys.into_iter().map(Into::into).collect()))
+    }
+
+    /// Replace the matches of the `pattern` regex in string attribute values,
+    /// including the strings of array values, in the span name and in the
+    /// status description with `replacement`.
+    ///
+    /// `replacement` can refer to capture groups, see [`Regex::replace_all`].
+    ///
+    /// Returns an error if `pattern` is not a valid regex.
+    pub fn mask<T: Into<String>>(pattern: &str, replacement: T) -> TraceResult<Self> {
+        let pattern = Regex::new(pattern)
+            .map_err(|err| TraceError::Other(format!("invalid redaction regex: {}", err).into()))?;
+
+        Ok(RedactionRule(Rule::Mask {
+            pattern,
+            replacement: replacement.into(),
+        }))
+    }
+
+    /// Remove the attributes whose key is not one of the given keys.
+    pub fn allow_keys<T, K>(keys: T) -> Self
+    where
+        T: IntoIterator<Item = K>,
+        K: Into<Key>,
+    {
+        RedactionRule(Rule::Allow(keys.into_iter().map(Into::into).collect()))
+    }
+
+    /// Whether the attribute with `key` should be kept.
+    fn keeps(&self, key: &Key) -> bool {
+        match &self.0 {
+            Rule::Delete(keys) => !keys.contains(key),
+            Rule::Allow(keys) => keys.contains(key),
+            Rule::Hash(_) | Rule::Mask { .. } => true,
+        }
+    }
+
+    fn redact_value(&self, key: &Key, value: &mut Value) {
+        match &self.0 {
+            Rule::Hash(keys) if keys.contains(key) => {
+                *value = Value::String(hash(&value.as_str()).into());
+            }
+            Rule::Mask {
+                pattern,
+                replacement,
+            } => match value {
+                Value::String(string) => mask_string(string, pattern, replacement),
+                Value::Array(Array::String(strings)) => {
+                    for string in strings {
+                        mask_string(string, pattern, replacement);
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn redact_text(&self, text: &mut Cow<'static, str>) {
+        if let Rule::Mask {
+            pattern,
+            replacement,
+        } = &self.0
+        {
+            if let Cow::Owned(masked) = pattern.replace_all(text, replacement.as_str()) {
+                *text = masked.into();
+            }
+        }
+    }
+}
+
+fn hash(value: &str) -> String {
+    Sha256::digest(value.as_bytes())
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+}
+
+fn mask_string(string: &mut StringValue, pattern: &Regex, replacement: &str) {
+    // `replace_all` only allocates when the pattern matches
+    if let Cow::Owned(masked) = pattern.replace_all(string.as_str(), replacement) {
+        *string = masked.into();
+    }
+}
+
+/// A [`SpanProcessor`] that applies [`RedactionRule`]s to the spans it
+/// receives before passing them to another span processor.
+///
+/// Rules apply to the span attributes, the event attributes, the span name and
+/// the status description. Attributes matched by a key based rule are
+/// redacted whatever the other rules are, so the order of the rules only
+/// matters between `hash_keys` and `mask` rules, which are applied in the
+/// order they were added. Spans matched by no rule are passed on unchanged
+/// and without allocating.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry_api::trace::TraceResult;
+/// use opentelemetry_sdk::trace::{
+///     RedactionRule, RedactionSpanProcessor, SpanProcessor, TracerProvider,
+/// };
+///
+/// fn init_provider<P>(processor: P) -> TraceResult<TracerProvider>
+/// where
+///     P: SpanProcessor + 'static,
+/// {
+///     let processor = RedactionSpanProcessor::builder(processor)
+///         .with_rule(RedactionRule::delete_keys(["http.request.header.authorization"]))
+///         .with_rule(RedactionRule::hash_keys(["enduser.id"]))
+///         .with_rule(RedactionRule::mask(r"[\w.+-]+@[\w-]+\.[\w.]+", "<email>")?)
+///         .build();
+///
+///     Ok(TracerProvider::builder()
+///         .with_span_processor(processor)
+///         .build())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RedactionSpanProcessor<P> {
+    inner: P,
+    rules: Vec<RedactionRule>,
+}
+
+impl<P: SpanProcessor> RedactionSpanProcessor<P> {
+    /// Create a new [`RedactionSpanProcessorBuilder`] wrapping `inner`.
+    pub fn builder(inner: P) -> RedactionSpanProcessorBuilder<P> {
+        RedactionSpanProcessorBuilder {
+            inner,
+            rules: Vec::new(),
+        }
+    }
+
+    fn redact(&self, span: &mut SpanData) {
+        for rule in &self.rules {
+            rule.redact_text(&mut span.name);
+            if let Status::Error { description } = &mut span.status {
+                rule.redact_text(description);
+            }
+        }
+
+        span.attributes
+            .retain(|key, value| self.redact_attribute(key, value));
+        for event in span.events.iter_mut() {
+            event
+                .attributes
+                .retain(|attribute| self.keeps(&attribute.key));
+            for KeyValue { key, value } in event.attributes.iter_mut() {
+                self.redact_value(key, value);
+            }
+        }
+    }
+
+    fn keeps(&self, key: &Key) -> bool {
+        self.rules.iter().all(|rule| rule.keeps(key))
+    }
+
+    fn redact_value(&self, key: &Key, value: &mut Value) {
+        for rule in &self.rules {
+            rule.redact_value(key, value);
+        }
+    }
+
+    fn redact_attribute(&self, key: &Key, value: &mut Value) -> bool {
+        if !self.keeps(key) {
+            return false;
+        }
+        self.redact_value(key, value);
+        true
+    }
+}
+
+impl<P: SpanProcessor> SpanProcessor for RedactionSpanProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx)
+    }
+
+    fn on_end(&self, mut span: SpanData) {
+        self.redact(&mut span);
+        self.inner.on_end(span)
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&mut self) -> TraceResult<()> {
+        self.inner.shutdown()
+    }
+}
+
+/// A builder for creating [`RedactionSpanProcessor`] instances.
+#[derive(Debug)]
+pub struct RedactionSpanProcessorBuilder<P> {
+    inner: P,
+    rules: Vec<RedactionRule>,
+}
+
+impl<P: SpanProcessor> RedactionSpanProcessorBuilder<P> {
+    /// Add a rule applied to every span.
+    pub fn with_rule(mut self, rule: RedactionRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Build a new [`RedactionSpanProcessor`].
+    pub fn build(self) -> RedactionSpanProcessor<P> {
+        RedactionSpanProcessor {
+            inner: self.inner,
+            rules: self.rules,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::trace::{new_test_export_span_data, RecordingSpanProcessor};
+    use opentelemetry_api::trace::Event;
+
+    fn redacted(rules: Vec<RedactionRule>, span: SpanData) -> SpanData {
+        let mut builder = RedactionSpanProcessor::builder(RecordingSpanProcessor::default());
+        for rule in rules {
+            builder = builder.with_rule(rule);
+        }
+        let processor = builder.build();
+        processor.on_end(span);
+
+        let mut spans = processor.inner.into_spans();
+        assert_eq!(spans.len(), 1);
+        spans.remove(0)
+    }
+
+    fn attributes(span: &SpanData) -> Vec<KeyValue> {
+        let mut attributes: Vec<_> = span
+            .attributes
+            .iter()
+            .map(|(key, value)| KeyValue::new(key.clone(), value.clone()))
+            .collect();
+        attributes.sort_by(|a, b| a.key.cmp(&b.key));
+        attributes
+    }
+
+    fn span_with(attributes: Vec<KeyValue>) -> SpanData {
+        let mut span = new_test_export_span_data();
+        for attribute in attributes.clone() {
+            span.attributes.insert(attribute);
+        }
+        span.events
+            .push_back(Event::new("event", span.start_time, attributes, 0));
+        span
+    }
+
+    #[test]
+    fn test_key_rules() {
+        let span = redacted(
+            vec![
+                RedactionRule::delete_keys(["token"]),
+                RedactionRule::hash_keys(["user"]),
+            ],
+            span_with(vec![
+                KeyValue::new("token", "secret"),
+                KeyValue::new("user", "alice"),
+                KeyValue::new("http.method", "GET"),
+            ]),
+        );
+
+        let expected = vec![
+            KeyValue::new("http.method", "GET"),
+            KeyValue::new(
+                "user",
+                "2bd806c97f0e00af1a1fc3328fa763a9269723c8db8fac4f93af71db186d6e90",
+            ),
+        ];
+        assert_eq!(attributes(&span), expected);
+        let event = span.events.iter().next().unwrap();
+        let mut event_attributes = event.attributes.clone();
+        event_attributes.sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(event_attributes, expected);
+    }
+
+    #[test]
+    fn test_allow_keys() {
+        let span = redacted(
+            vec![RedactionRule::allow_keys(["http.method", "http.route"])],
+            span_with(vec![
+                KeyValue::new("http.method", "GET"),
+                KeyValue::new("http.url", "https://example.com/?token=secret"),
+            ]),
+        );
+
+        assert_eq!(attributes(&span), vec![KeyValue::new("http.method", "GET")]);
+        let event = span.events.iter().next().unwrap();
+        assert_eq!(event.attributes, vec![KeyValue::new("http.method", "GET")]);
+    }
+
+    #[test]
+    fn test_mask() {
+        let mut span = span_with(vec![
+            KeyValue::new("message", "sent to alice@example.com"),
+            KeyValue::new(
+                "recipients",
+                Value::Array(vec![StringValue::from("bob@example.com"), "team".into()].into()),
+            ),
+            KeyValue::new("count", 2),
+        ]);
+        span.name = "notify alice@example.com".into();
+        span.status = Status::error("cannot reach bob@example.com");
+
+        let span = redacted(
+            vec![RedactionRule::mask(r"[\w.+-]+@[\w-]+\.[\w.]+", "<email>").unwrap()],
+            span,
+        );
+
+        assert_eq!(span.name, "notify <email>");
+        assert_eq!(span.status, Status::error("cannot reach <email>"));
+        assert_eq!(
+            attributes(&span),
+            vec![
+                KeyValue::new("count", 2),
+                KeyValue::new("message", "sent to <email>"),
+                KeyValue::new(
+                    "recipients",
+                    Value::Array(vec![StringValue::from("<email>"), "team".into()].into())
+                ),
+            ]
+        );
+
+        assert!(RedactionRule::mask("(", "").is_err());
+    }
+
+    #[test]
+    fn test_no_match() {
+        let span = span_with(vec![KeyValue::new("http.method", "GET")]);
+        let redacted_span = redacted(
+            vec![
+                RedactionRule::delete_keys(["token"]),
+                RedactionRule::mask("secret", "***").unwrap(),
+            ],
+            span.clone(),
+        );
+        assert_eq!(redacted_span, span);
+        // unchanged values keep their original, borrowed, storage
+        assert!(matches!(redacted_span.name, Cow::Borrowed(_)));
+    }
+}
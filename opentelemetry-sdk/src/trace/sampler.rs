@@ -8,13 +8,25 @@ use opentelemetry_api::{
 };
 use std::convert::TryInto;
 
+mod consistent_probability;
 #[cfg(feature = "jaeger_remote_sampler")]
 mod jaeger_remote;
+mod rate_limit;
+#[cfg(feature = "rule_based_sampler")]
+mod rule_based;
 
 #[cfg(feature = "jaeger_remote_sampler")]
-pub use jaeger_remote::{JaegerRemoteSampler, JaegerRemoteSamplerBuilder};
+pub use jaeger_remote::{
+    JaegerFileSamplerBuilder, JaegerRemoteSampler, JaegerRemoteSamplerBuilder,
+};
 #[cfg(feature = "jaeger_remote_sampler")]
 use opentelemetry_http::HttpClient;
+pub use rate_limit::RateLimitingSampler;
+#[cfg(feature = "rule_based_sampler")]
+pub use rule_based::{
+    AttributeRuleConfig, AttributeValueConfig, RuleBasedSampler, RuleBasedSamplerConfig,
+    SamplerConfig, SamplingRule, SamplingRuleBuilder, SamplingRuleConfig, SpanKindConfig,
+};
 
 /// The [`ShouldSample`] interface allows implementations to provide samplers
 /// which will return a sampling [`SamplingResult`] based on information that
@@ -127,6 +139,31 @@ pub enum Sampler {
     /// *Note:* If this is used then all Spans in a trace will become sampled assuming that the
     /// first span is sampled as it is based on the `trace_id` not the `span_id`
     TraceIdRatioBased(f64),
+    /// Sample a given fraction of traces consistently across services, following the
+    /// [probability sampling] specification.
+    ///
+    /// The randomness of the trace (r-value) and the sampling probability of the span (p-value)
+    /// are recorded in the `ot` entry of the trace state, as in `ot=p:2;r:5`, so that the number
+    /// of spans represented by each sampled span can be computed downstream. The r-value of the
+    /// parent trace state is used if present, else a new one is generated.
+    ///
+    /// Fractions that are not powers of two are sampled with one of the two closest powers of
+    /// two, chosen randomly so that the expected fraction is kept.
+    ///
+    /// [probability sampling]: https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/trace/tracestate-probability-sampling.md
+    ConsistentProbabilityBased(f64),
+    /// Respects the parent span's sampling decision and propagates its consistent probability
+    /// sampling trace state, or delegates to a root sampler for root spans, usually
+    /// [`Sampler::ConsistentProbabilityBased`].
+    ///
+    /// The p-value of the parent is removed if the parent is not sampled or if the p-value is not
+    /// consistent with the parent's decision and r-value.
+    ParentConsistentProbabilityBased(Box<dyn ShouldSample>),
+    /// Sample at most a given number of traces per second, created with [`Sampler::rate_limited`].
+    ///
+    /// The budget refills continuously and allows bursts of up to a second of traces. Use it as
+    /// the delegate of [`Sampler::ParentBased`] to only limit the traces started by root spans.
+    RateLimited(RateLimitingSampler),
     /// Jaeger remote sampler supports any remote service that implemented the jaeger remote sampler protocol.
     /// The proto definition can be found [here](https://github.com/jaegertracing/jaeger-idl/blob/main/proto/api_v2/sampling.proto)
     ///
@@ -140,6 +177,20 @@ pub enum Sampler {
 }
 
 impl Sampler {
+    /// Create a sampler sampling at most `traces_per_second` traces per second.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry_sdk::trace::{config, Sampler};
+    ///
+    /// // sample at most 10 new traces per second, and all the children of sampled spans
+    /// let config = config().with_sampler(Sampler::ParentBased(Box::new(Sampler::rate_limited(10.0))));
+    /// ```
+    pub fn rate_limited(traces_per_second: f64) -> Self {
+        Sampler::RateLimited(RateLimitingSampler::new(traces_per_second))
+    }
+
     /// Create a jaeger remote sampler builder.
     ///
     /// ### Arguments
@@ -164,6 +215,39 @@ impl Sampler {
     {
         JaegerRemoteSamplerBuilder::new(runtime, http_client, default_sampler, service_name)
     }
+
+    /// Create a jaeger remote sampler builder reading the sampling strategies from a file
+    /// instead of polling a remote endpoint.
+    ///
+    /// The file uses the [`strategies.json` format] of Jaeger collectors. The strategy of
+    /// `service_name` is taken from `service_strategies`, services without strategy use
+    /// `default_strategy`. The file is checked for changes every update interval.
+    ///
+    /// If the file has neither a strategy for the service nor a `default_strategy`, the
+    /// `default_sampler` makes the sampling decisions until the file defines one.
+    ///
+    /// ### Arguments
+    /// * `runtime` - A runtime to watch the file.
+    /// * `path` - The path of the strategies file.
+    /// * `default_sampler` - A default sampler to make a sampling decision when the file doesn't define a strategy for the service.
+    /// * `service_name` - The name of the service, used to find its strategy in the file.
+    ///
+    /// [`strategies.json` format]: https://www.jaegertracing.io/docs/1.38/sampling/#collector-sampling-configuration
+    #[cfg(feature = "jaeger_remote_sampler")]
+    pub fn jaeger_file<Sampler, R, Svc, P>(
+        runtime: R,
+        path: P,
+        default_sampler: Sampler,
+        service_name: Svc,
+    ) -> JaegerFileSamplerBuilder<Sampler, R>
+    where
+        Sampler: ShouldSample,
+        R: crate::trace::TraceRuntime,
+        Svc: Into<String>,
+        P: Into<std::path::PathBuf>,
+    {
+        JaegerFileSamplerBuilder::new(runtime, path, default_sampler, service_name)
+    }
 }
 
 impl ShouldSample for Sampler {
@@ -211,6 +295,32 @@ impl ShouldSample for Sampler {
                 ),
             // Probabilistically sample the trace.
             Sampler::TraceIdRatioBased(prob) => sample_based_on_probability(prob, trace_id),
+            // Sample while the rate limit allows it.
+            Sampler::RateLimited(rate_limiting) => {
+                if rate_limiting.should_sample() {
+                    SamplingDecision::RecordAndSample
+                } else {
+                    SamplingDecision::Drop
+                }
+            }
+            // Consistent probability samplers write their own trace state.
+            Sampler::ConsistentProbabilityBased(ratio) => {
+                return consistent_probability::sample(*ratio, parent_context)
+            }
+            Sampler::ParentConsistentProbabilityBased(root_sampler) => {
+                return match parent_context.filter(|cx| cx.has_active_span()) {
+                    Some(cx) => consistent_probability::sample_parent(cx),
+                    None => root_sampler.should_sample(
+                        parent_context,
+                        trace_id,
+                        name,
+                        span_kind,
+                        attributes,
+                        links,
+                        instrumentation_library,
+                    ),
+                };
+            }
             #[cfg(feature = "jaeger_remote_sampler")]
             Sampler::JaegerRemote(remote_sampler) => {
                 remote_sampler
@@ -461,4 +571,85 @@ mod tests {
             assert_eq!(result.decision, expected);
         }
     }
+
+    #[test]
+    fn parent_consistent_probability_sampler() {
+        let sampler = Sampler::ParentConsistentProbabilityBased(Box::new(
+            Sampler::ConsistentProbabilityBased(1.0),
+        ));
+        let instrumentation_library = InstrumentationLibrary::default();
+        let should_sample = |parent_cx: &Context| {
+            sampler.should_sample(
+                Some(parent_cx),
+                TraceId::from_u128(1),
+                "consistent",
+                &SpanKind::Internal,
+                &Default::default(),
+                &[],
+                &instrumentation_library,
+            )
+        };
+
+        // root spans are sampled by the root sampler with a new r-value
+        let root = should_sample(&Context::new());
+        assert_eq!(root.decision, SamplingDecision::RecordAndSample);
+        let ot = root.trace_state.get("ot").unwrap();
+        assert!(ot.starts_with("p:0;r:"), "{}", ot);
+
+        // children keep the trace state of a consistent parent
+        let parent_cx = Context::current_with_span(TestSpan(SpanContext::new(
+            TraceId::from_u128(1),
+            SpanId::from_u64(1),
+            TraceFlags::SAMPLED,
+            false,
+            root.trace_state.clone(),
+        )));
+        let child = should_sample(&parent_cx);
+        assert_eq!(child.decision, SamplingDecision::RecordAndSample);
+        assert_eq!(child.trace_state, root.trace_state);
+    }
+
+    #[test]
+    fn parent_based_rate_limited_sampler() {
+        use super::rate_limit::RateLimitingSampler;
+        use std::sync::Arc;
+
+        // the clock never advances, only the initial budget is available
+        let now = std::time::Instant::now();
+        let sampler = Sampler::ParentBased(Box::new(Sampler::RateLimited(
+            RateLimitingSampler::with_clock(1.0, Arc::new(move || now)),
+        )));
+        let instrumentation_library = InstrumentationLibrary::default();
+        let should_sample = |parent_cx: &Context| {
+            sampler
+                .should_sample(
+                    Some(parent_cx),
+                    TraceId::from_u128(1),
+                    "rate limited",
+                    &SpanKind::Internal,
+                    &Default::default(),
+                    &[],
+                    &instrumentation_library,
+                )
+                .decision
+        };
+        let sampled_parent = Context::current_with_span(TestSpan(SpanContext::new(
+            TraceId::from_u128(1),
+            SpanId::from_u64(1),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::default(),
+        )));
+
+        assert_eq!(
+            should_sample(&Context::new()),
+            SamplingDecision::RecordAndSample
+        );
+        assert_eq!(should_sample(&Context::new()), SamplingDecision::Drop);
+        // children follow their parent without using the budget
+        assert_eq!(
+            should_sample(&sampled_parent),
+            SamplingDecision::RecordAndSample
+        );
+    }
 }
@@ -0,0 +1,294 @@
+//! Consistent probability sampling
+//!
+//! Implements the [probability sampling] specification, which records the
+//! sampling probability of a span in the `ot` entry of its trace state so that
+//! the spans of a trace sampled by services with different ratios stay
+//! consistent, and so that the number of spans each sampled span represents
+//! can be computed downstream.
+//!
+//! The entry holds two sub-keys:
+//! * `r`, the randomness of the trace, the number of leading zeros of a random
+//!   62 bits number, generated once for the trace and propagated to all its
+//!   spans.
+//! * `p`, the negated base 2 logarithm of the sampling probability of the span,
+//!   with 63 meaning a zero probability. A span is sampled if `p <= r`.
+//!
+//! [probability sampling]: https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/trace/tracestate-probability-sampling.md
+use opentelemetry_api::trace::{SamplingDecision, SamplingResult, TraceContextExt, TraceState};
+use opentelemetry_api::{global, Context};
+use rand::Rng;
+
+/// The trace state key of the OpenTelemetry entry.
+const OT_KEY: &str = "ot";
+/// The largest valid r-value.
+const MAX_R_VALUE: u8 = 62;
+/// The p-value of a zero sampling probability.
+const ZERO_PROBABILITY_P_VALUE: u8 = 63;
+
+/// The `ot` entry of a trace state.
+#[derive(Debug, Default, PartialEq)]
+struct OtTraceState {
+    p: Option<u8>,
+    r: Option<u8>,
+    /// Other sub-keys, kept as is.
+    rest: Vec<String>,
+}
+
+impl OtTraceState {
+    /// Parse the `ot` entry of `trace_state`, invalid p and r values are
+    /// ignored.
+    fn parse(trace_state: &TraceState) -> Self {
+        let mut ot = OtTraceState::default();
+        let value = match trace_state.get(OT_KEY) {
+            Some(value) => value,
+            None => return ot,
+        };
+
+        for field in value.split(';').filter(|field| !field.is_empty()) {
+            match field.split_once(':') {
+                Some(("p", p)) => ot.p = parse_value(p, ZERO_PROBABILITY_P_VALUE),
+                Some(("r", r)) => ot.r = parse_value(r, MAX_R_VALUE),
+                _ => ot.rest.push(field.to_string()),
+            }
+        }
+
+        ot
+    }
+
+    /// Replace the `ot` entry of `trace_state` with this one.
+    fn apply(&self, trace_state: &TraceState) -> TraceState {
+        let fields: Vec<String> = self
+            .p
+            .map(|p| format!("p:{}", p))
+            .into_iter()
+            .chain(self.r.map(|r| format!("r:{}", r)))
+            .chain(self.rest.iter().cloned())
+            .collect();
+
+        let result = if fields.is_empty() {
+            trace_state.delete(OT_KEY)
+        } else {
+            trace_state.insert(OT_KEY, fields.join(";"))
+        };
+        result.unwrap_or_else(|err| {
+            global::handle_error(err);
+            trace_state.clone()
+        })
+    }
+}
+
+fn parse_value(value: &str, max: u8) -> Option<u8> {
+    value.parse::<u8>().ok().filter(|value| *value <= max)
+}
+
+/// Generate the r-value of a new trace, `r` with probability `2^-(r+1)`, and
+/// 62 with probability `2^-62`.
+fn random_r_value<R: Rng>(rng: &mut R) -> u8 {
+    // the two lowest bits are set so that at most 62 leading zeros are counted
+    (rng.gen::<u64>() | 0b11).leading_zeros() as u8
+}
+
+/// Choose the p-value of a span sampled with probability `ratio`.
+///
+/// Ratios that are not powers of two are approximated by choosing randomly
+/// between the two closest p-values, so that the expected probability is
+/// `ratio`.
+fn p_value<R: Rng>(ratio: f64, rng: &mut R) -> u8 {
+    if ratio >= 1.0 {
+        return 0;
+    }
+    if ratio.is_nan() || ratio <= 0.0 {
+        return ZERO_PROBABILITY_P_VALUE;
+    }
+
+    // the largest p-value whose probability is at least the ratio
+    let p = -ratio.log2().ceil();
+    if p >= f64::from(MAX_R_VALUE) {
+        return if ratio >= 2f64.powi(-i32::from(MAX_R_VALUE)) {
+            MAX_R_VALUE
+        } else {
+            ZERO_PROBABILITY_P_VALUE
+        };
+    }
+
+    let p = p as u8;
+    let upper_probability = 2f64.powi(-i32::from(p));
+    let lower_probability = upper_probability / 2.0;
+    let upper_weight = (ratio - lower_probability) / (upper_probability - lower_probability);
+    if rng.gen::<f64>() < upper_weight {
+        p
+    } else {
+        p + 1
+    }
+}
+
+/// Sample a span with probability `ratio`, keeping the r-value of the parent
+/// trace state if any.
+pub(crate) fn sample(ratio: f64, parent_context: Option<&Context>) -> SamplingResult {
+    let trace_state = parent_trace_state(parent_context);
+    let mut ot = OtTraceState::parse(&trace_state);
+    let mut rng = rand::thread_rng();
+    let r = *ot.r.get_or_insert_with(|| random_r_value(&mut rng));
+    let p = p_value(ratio, &mut rng);
+
+    let decision = if p <= r {
+        ot.p = Some(p);
+        SamplingDecision::RecordAndSample
+    } else {
+        ot.p = None;
+        SamplingDecision::Drop
+    };
+
+    SamplingResult {
+        decision,
+        attributes: Vec::new(),
+        trace_state: ot.apply(&trace_state),
+    }
+}
+
+/// Follow the decision of the parent span, removing the parent p-value if it
+/// is not consistent with the decision or the r-value.
+pub(crate) fn sample_parent(parent_context: &Context) -> SamplingResult {
+    let trace_state = parent_trace_state(Some(parent_context));
+    let mut ot = OtTraceState::parse(&trace_state);
+    let sampled = parent_context.span().span_context().is_sampled();
+
+    let consistent = match (ot.p, ot.r) {
+        (Some(p), Some(r)) => sampled == (p <= r),
+        (Some(p), None) => sampled == (p != ZERO_PROBABILITY_P_VALUE),
+        (None, _) => true,
+    };
+    if !sampled || !consistent {
+        ot.p = None;
+    }
+
+    SamplingResult {
+        decision: if sampled {
+            SamplingDecision::RecordAndSample
+        } else {
+            SamplingDecision::Drop
+        },
+        attributes: Vec::new(),
+        trace_state: ot.apply(&trace_state),
+    }
+}
+
+fn parent_trace_state(parent_context: Option<&Context>) -> TraceState {
+    match parent_context {
+        Some(cx) => cx.span().span_context().trace_state().clone(),
+        None => TraceState::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_api::trace::{SpanContext, SpanId, TraceFlags, TraceId};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn parent(sampled: bool, ot: &str) -> Context {
+        Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from_u128(1),
+            SpanId::from_u64(1),
+            if sampled {
+                TraceFlags::SAMPLED
+            } else {
+                TraceFlags::default()
+            },
+            true,
+            TraceState::from_key_value(vec![("ot", ot), ("vendor", "value")]).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn test_parse_and_apply() {
+        let trace_state =
+            TraceState::from_key_value(vec![("ot", "p:8;r:62;x:y"), ("vendor", "value")]).unwrap();
+        let ot = OtTraceState::parse(&trace_state);
+        assert_eq!(
+            ot,
+            OtTraceState {
+                p: Some(8),
+                r: Some(62),
+                rest: vec!["x:y".to_string()],
+            }
+        );
+        assert_eq!(
+            ot.apply(&trace_state).header(),
+            "ot=p:8;r:62;x:y,vendor=value"
+        );
+
+        // invalid values are dropped
+        let trace_state = TraceState::from_key_value(vec![("ot", "p:64;r:63")]).unwrap();
+        let ot = OtTraceState::parse(&trace_state);
+        assert_eq!(ot, OtTraceState::default());
+        assert_eq!(ot.apply(&trace_state).header(), "");
+    }
+
+    #[test]
+    fn test_p_value() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(p_value(1.0, &mut rng), 0);
+        assert_eq!(p_value(0.5, &mut rng), 1);
+        assert_eq!(p_value(0.25, &mut rng), 2);
+        assert_eq!(p_value(2f64.powi(-62), &mut rng), 62);
+        assert_eq!(p_value(2f64.powi(-63), &mut rng), 63);
+        assert_eq!(p_value(0.0, &mut rng), 63);
+        assert_eq!(p_value(-1.0, &mut rng), 63);
+
+        // 0.3 is sampled with p = 1 20% of the time and p = 2 otherwise
+        let ones = (0..10_000)
+            .filter(|_| {
+                let p = p_value(0.3, &mut rng);
+                assert!(p == 1 || p == 2);
+                p == 1
+            })
+            .count();
+        assert!((1_700..2_300).contains(&ones), "{}", ones);
+    }
+
+    #[test]
+    fn test_random_r_value() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let values: Vec<u8> = (0..10_000).map(|_| random_r_value(&mut rng)).collect();
+        assert!(values.iter().all(|r| *r <= MAX_R_VALUE));
+        // about half of the traces have r = 0
+        let zeros = values.iter().filter(|r| **r == 0).count();
+        assert!((4_500..5_500).contains(&zeros), "{}", zeros);
+    }
+
+    #[test]
+    fn test_sample_uses_parent_r_value() {
+        let result = sample(0.25, Some(&parent(true, "r:2")));
+        assert_eq!(result.decision, SamplingDecision::RecordAndSample);
+        assert_eq!(result.trace_state.header(), "ot=p:2;r:2,vendor=value");
+
+        let result = sample(0.25, Some(&parent(true, "p:0;r:1")));
+        assert_eq!(result.decision, SamplingDecision::Drop);
+        assert_eq!(result.trace_state.header(), "ot=r:1,vendor=value");
+
+        // a new r-value is generated for root spans
+        let result = sample(1.0, None);
+        assert_eq!(result.decision, SamplingDecision::RecordAndSample);
+        let ot = OtTraceState::parse(&result.trace_state);
+        assert_eq!(ot.p, Some(0));
+        assert!(ot.r.is_some());
+    }
+
+    #[test]
+    fn test_sample_parent() {
+        let result = sample_parent(&parent(true, "p:1;r:2"));
+        assert_eq!(result.decision, SamplingDecision::RecordAndSample);
+        assert_eq!(result.trace_state.header(), "ot=p:1;r:2,vendor=value");
+
+        // inconsistent p-values are erased
+        let result = sample_parent(&parent(true, "p:3;r:2"));
+        assert_eq!(result.decision, SamplingDecision::RecordAndSample);
+        assert_eq!(result.trace_state.header(), "ot=r:2,vendor=value");
+
+        let result = sample_parent(&parent(false, "p:1;r:2"));
+        assert_eq!(result.decision, SamplingDecision::Drop);
+        assert_eq!(result.trace_state.header(), "ot=r:2,vendor=value");
+    }
+}
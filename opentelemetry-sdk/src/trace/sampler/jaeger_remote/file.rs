@@ -0,0 +1,233 @@
+//! Model of the `strategies.json` file read by Jaeger collectors.
+//!
+//! See the [Jaeger documentation](https://www.jaegertracing.io/docs/1.38/sampling/#collector-sampling-configuration)
+//! for the format.
+use crate::trace::sampler::jaeger_remote::remote::{
+    OperationSamplingStrategy, PerOperationSamplingStrategies, ProbabilisticSamplingStrategy,
+    RateLimitingSamplingStrategy, SamplingStrategyResponse, SamplingStrategyType,
+};
+
+#[derive(serde::Deserialize)]
+pub(crate) struct StrategiesFile {
+    #[serde(default)]
+    service_strategies: Vec<ServiceStrategy>,
+    default_strategy: Option<Strategy>,
+}
+
+#[derive(serde::Deserialize)]
+struct ServiceStrategy {
+    service: String,
+    #[serde(flatten)]
+    strategy: Strategy,
+}
+
+#[derive(serde::Deserialize)]
+struct Strategy {
+    #[serde(rename = "type")]
+    strategy_type: StrategyType,
+    param: f64,
+    #[serde(default)]
+    operation_strategies: Vec<OperationStrategy>,
+}
+
+#[derive(serde::Deserialize)]
+struct OperationStrategy {
+    operation: String,
+    #[serde(rename = "type")]
+    strategy_type: StrategyType,
+    param: f64,
+}
+
+#[derive(serde::Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum StrategyType {
+    Probabilistic,
+    RateLimiting,
+}
+
+impl StrategiesFile {
+    /// Build the response the collector would return to `service_name`.
+    ///
+    /// A service without strategy uses the default strategy. The operation strategies of the
+    /// default strategy also apply to the operations of probabilistic services that don't override
+    /// them.
+    ///
+    /// Returns `None` if the file has neither a strategy for the service nor a default strategy.
+    pub(crate) fn into_response(self, service_name: &str) -> Option<SamplingStrategyResponse> {
+        let default_strategy = self.default_strategy;
+
+        let strategy = match self
+            .service_strategies
+            .into_iter()
+            .find(|service| service.service == service_name)
+        {
+            Some(ServiceStrategy { mut strategy, .. }) => {
+                if strategy.strategy_type == StrategyType::Probabilistic {
+                    let default_operations = default_strategy
+                        .map(|default_strategy| default_strategy.operation_strategies)
+                        .unwrap_or_default();
+                    for default_operation in default_operations {
+                        if !strategy
+                            .operation_strategies
+                            .iter()
+                            .any(|operation| operation.operation == default_operation.operation)
+                        {
+                            strategy.operation_strategies.push(default_operation);
+                        }
+                    }
+                }
+                strategy
+            }
+            None => default_strategy?,
+        };
+
+        Some(strategy.into_response())
+    }
+}
+
+impl Strategy {
+    fn into_response(self) -> SamplingStrategyResponse {
+        match self.strategy_type {
+            StrategyType::RateLimiting => SamplingStrategyResponse {
+                strategy_type: SamplingStrategyType::RateLimiting,
+                probabilistic_sampling: None,
+                rate_limiting_sampling: Some(RateLimitingSamplingStrategy {
+                    max_traces_per_second: self.param as i32,
+                }),
+                operation_sampling: None,
+            },
+            StrategyType::Probabilistic => {
+                // only probabilistic operation strategies are supported
+                let per_operation_strategies: Vec<_> = self
+                    .operation_strategies
+                    .into_iter()
+                    .filter(|operation| operation.strategy_type == StrategyType::Probabilistic)
+                    .map(|operation| OperationSamplingStrategy {
+                        operation: operation.operation,
+                        probabilistic_sampling: ProbabilisticSamplingStrategy {
+                            sampling_rate: operation.param,
+                        },
+                    })
+                    .collect();
+
+                SamplingStrategyResponse {
+                    strategy_type: SamplingStrategyType::Probabilistic,
+                    probabilistic_sampling: Some(ProbabilisticSamplingStrategy {
+                        sampling_rate: self.param,
+                    }),
+                    rate_limiting_sampling: None,
+                    operation_sampling: if per_operation_strategies.is_empty() {
+                        None
+                    } else {
+                        Some(PerOperationSamplingStrategies {
+                            default_sampling_probability: self.param,
+                            default_lower_bound_traces_per_second: 0.0,
+                            per_operation_strategies,
+                            default_upper_bound_traces_per_second: 0.0,
+                        })
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRATEGIES: &str = r#"{
+        "service_strategies": [
+            {
+                "service": "foo",
+                "type": "probabilistic",
+                "param": 0.8,
+                "operation_strategies": [
+                    {"operation": "op1", "type": "probabilistic", "param": 0.2},
+                    {"operation": "op2", "type": "ratelimiting", "param": 10}
+                ]
+            },
+            {
+                "service": "bar",
+                "type": "ratelimiting",
+                "param": 5
+            }
+        ],
+        "default_strategy": {
+            "type": "probabilistic",
+            "param": 0.5,
+            "operation_strategies": [
+                {"operation": "op1", "type": "probabilistic", "param": 0.9},
+                {"operation": "/health", "type": "probabilistic", "param": 0.0}
+            ]
+        }
+    }"#;
+
+    fn response(service_name: &str) -> SamplingStrategyResponse {
+        let file: StrategiesFile = serde_json::from_str(STRATEGIES).unwrap();
+        file.into_response(service_name).unwrap()
+    }
+
+    fn operations(response: &SamplingStrategyResponse) -> Vec<(&str, f64)> {
+        response
+            .operation_sampling
+            .as_ref()
+            .unwrap()
+            .per_operation_strategies
+            .iter()
+            .map(|operation| {
+                (
+                    operation.operation.as_str(),
+                    operation.probabilistic_sampling.sampling_rate,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_service_strategy() {
+        let foo = response("foo");
+        let operation_sampling = foo.operation_sampling.as_ref().unwrap();
+        assert_eq!(operation_sampling.default_sampling_probability, 0.8);
+        assert_eq!(operations(&foo), vec![("op1", 0.2), ("/health", 0.0)]);
+
+        let bar = response("bar");
+        assert!(bar.strategy_type == SamplingStrategyType::RateLimiting);
+        assert_eq!(bar.rate_limiting_sampling.unwrap().max_traces_per_second, 5);
+        assert!(bar.operation_sampling.is_none());
+    }
+
+    #[test]
+    fn test_default_strategy() {
+        let unknown = response("unknown");
+        assert_eq!(
+            unknown
+                .probabilistic_sampling
+                .as_ref()
+                .unwrap()
+                .sampling_rate,
+            0.5
+        );
+        assert_eq!(operations(&unknown), vec![("op1", 0.9), ("/health", 0.0)]);
+    }
+
+    #[test]
+    fn test_no_strategy() {
+        let file: StrategiesFile = serde_json::from_str("{}").unwrap();
+        assert!(file.into_response("foo").is_none());
+
+        let file: StrategiesFile = serde_json::from_str(
+            r#"{"service_strategies": [{"service": "bar", "type": "ratelimiting", "param": 5}]}"#,
+        )
+        .unwrap();
+        assert!(file.into_response("foo").is_none());
+
+        let file: StrategiesFile = serde_json::from_str(
+            r#"{"service_strategies": [{"service": "foo", "type": "probabilistic", "param": 0.8}]}"#,
+        )
+        .unwrap();
+        let foo = file.into_response("foo").unwrap();
+        assert_eq!(foo.probabilistic_sampling.unwrap().sampling_rate, 0.8);
+        assert!(foo.operation_sampling.is_none());
+    }
+}
@@ -1,9 +1,11 @@
+mod file;
 mod rate_limit;
 mod remote;
 mod sampler;
 mod sampling_strategy;
+mod source;
 
-pub use sampler::{JaegerRemoteSampler, JaegerRemoteSamplerBuilder};
+pub use sampler::{JaegerFileSamplerBuilder, JaegerRemoteSampler, JaegerRemoteSamplerBuilder};
 
 #[cfg(test)]
 mod tests {}
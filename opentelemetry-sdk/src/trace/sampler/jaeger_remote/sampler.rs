@@ -1,11 +1,14 @@
-use crate::trace::sampler::jaeger_remote::remote::SamplingStrategyResponse;
 use crate::trace::sampler::jaeger_remote::sampling_strategy::Inner;
+use crate::trace::sampler::jaeger_remote::source::{
+    FileStrategySource, HttpStrategySource, StrategySource,
+};
 use crate::trace::{Sampler, ShouldSample, TraceRuntime};
 use futures_util::{stream, StreamExt as _};
 use http::Uri;
 use opentelemetry_api::trace::{Link, OrderMap, SamplingResult, SpanKind, TraceError, TraceId};
 use opentelemetry_api::{global, Context, InstrumentationLibrary, Key, Value};
 use opentelemetry_http::HttpClient;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -105,8 +108,7 @@ where
         Ok(Sampler::JaegerRemote(JaegerRemoteSampler::new(
             self.runtime,
             self.update_interval,
-            self.client,
-            endpoint,
+            HttpStrategySource::new(self.client, endpoint),
             self.default_sampler,
             self.leaky_bucket_size,
         )))
@@ -127,6 +129,96 @@ where
     }
 }
 
+/// Builder for a [`JaegerRemoteSampler`] reading its strategies from a file.
+/// See [Sampler::jaeger_file] for details.
+#[derive(Debug)]
+pub struct JaegerFileSamplerBuilder<S, R>
+where
+    R: TraceRuntime,
+    S: ShouldSample + 'static,
+{
+    pub(crate) update_interval: Duration,
+    pub(crate) path: PathBuf,
+    pub(crate) default_sampler: S,
+    pub(crate) leaky_bucket_size: f64,
+    pub(crate) runtime: R,
+    pub(crate) service_name: String,
+}
+
+impl<S, R> JaegerFileSamplerBuilder<S, R>
+where
+    S: ShouldSample + 'static,
+    R: TraceRuntime,
+{
+    pub(crate) fn new<Svc, P>(runtime: R, path: P, default_sampler: S, service_name: Svc) -> Self
+    where
+        Svc: Into<String>,
+        P: Into<PathBuf>,
+    {
+        JaegerFileSamplerBuilder {
+            runtime,
+            update_interval: Duration::from_secs(60),
+            path: path.into(),
+            default_sampler,
+            leaky_bucket_size: 100.0,
+            service_name: service_name.into(),
+        }
+    }
+
+    /// Change how often the SDK should check the file for changes.
+    ///
+    /// By default it checks every minute.
+    pub fn with_update_interval(self, interval: Duration) -> Self {
+        Self {
+            update_interval: interval,
+            ..self
+        }
+    }
+
+    /// The size of the leaky bucket.
+    ///
+    /// By default the size is 100.
+    ///
+    /// It's used when sampling strategy is rate limiting.
+    pub fn with_leaky_bucket_size(self, size: f64) -> Self {
+        Self {
+            leaky_bucket_size: size,
+            ..self
+        }
+    }
+
+    /// Build a [JaegerRemoteSampler] using provided configuration.
+    ///
+    /// The file is read once before returning, later changes are picked up every update
+    /// interval.
+    ///
+    /// Return errors if:
+    ///
+    /// - the service name provided is empty.
+    /// - the file cannot be read or is not a valid strategies file.
+    pub fn build(self) -> Result<Sampler, TraceError> {
+        if self.service_name.is_empty() {
+            return Err(TraceError::Other("service name cannot be empty".into()));
+        }
+        let source = FileStrategySource::new(self.path, self.service_name);
+        let initial_strategy = source
+            .load()
+            .map_err(|err_str| TraceError::Other(err_str.into()))?;
+
+        let sampler = JaegerRemoteSampler::new(
+            self.runtime,
+            self.update_interval,
+            source,
+            self.default_sampler,
+            self.leaky_bucket_size,
+        );
+        if let Some(strategy) = initial_strategy {
+            sampler.inner.update(strategy);
+        }
+        Ok(Sampler::JaegerRemote(sampler))
+    }
+}
+
 /// Sampler that fetches the sampling configuration from remotes.
 ///
 /// It offers the following sampling strategies:
@@ -136,6 +228,10 @@ where
 ///
 /// User can build a [`JaegerRemoteSampler`] by getting a [`JaegerRemoteSamplerBuilder`] from [`Sampler::jaeger_remote`].
 ///
+/// The strategies can also be read from a local file in the `strategies.json` format of Jaeger
+/// collectors, which is watched for changes. Get a [`JaegerFileSamplerBuilder`] from
+/// [`Sampler::jaeger_file`] to do so.
+///
 /// Note that the backend doesn't need to be Jaeger so long as it supports jaeger remote sampling
 /// protocol.
 #[derive(Clone, Debug)]
@@ -145,17 +241,16 @@ pub struct JaegerRemoteSampler {
 }
 
 impl JaegerRemoteSampler {
-    fn new<C, R, S>(
+    fn new<Src, R, S>(
         runtime: R,
         update_timeout: Duration,
-        client: C,
-        endpoint: Uri,
+        source: Src,
         default_sampler: S,
         leaky_bucket_size: f64,
     ) -> Self
     where
         R: TraceRuntime,
-        C: HttpClient + 'static,
+        Src: StrategySource,
         S: ShouldSample + 'static,
     {
         let (shutdown_tx, shutdown_rx) = futures_channel::mpsc::channel(1);
@@ -168,24 +263,22 @@ impl JaegerRemoteSampler {
             runtime,
             sampler.inner.clone(),
             update_timeout,
-            client,
+            source,
             shutdown_rx,
-            endpoint,
         );
         sampler
     }
 
     // start a updating thread/task
-    fn run_update_task<C, R>(
+    fn run_update_task<Src, R>(
         runtime: R,
         strategy: Arc<Inner>,
         update_timeout: Duration,
-        client: C,
+        source: Src,
         shutdown: futures_channel::mpsc::Receiver<()>,
-        endpoint: Uri,
     ) where
         R: TraceRuntime,
-        C: HttpClient + 'static,
+        Src: StrategySource,
     {
         // todo: review if we need 'static here
         let interval = runtime.interval(update_timeout);
@@ -199,9 +292,10 @@ impl JaegerRemoteSampler {
             while let Some(should_update) = update.next().await {
                 if should_update {
                     // poll next available configuration or shutdown
-                    // send request
-                    match Self::request_new_strategy(&client, endpoint.clone()).await {
-                        Ok(remote_strategy_resp) => strategy.update(remote_strategy_resp),
+                    match source.fetch().await {
+                        Ok(Some(strategy_resp)) => strategy.update(strategy_resp),
+                        // the strategy didn't change
+                        Ok(None) => {}
                         Err(err_msg) => global::handle_error(TraceError::Other(err_msg.into())),
                     };
                 } else {
@@ -211,36 +305,6 @@ impl JaegerRemoteSampler {
             }
         }));
     }
-
-    async fn request_new_strategy<C>(
-        client: &C,
-        endpoint: Uri,
-    ) -> Result<SamplingStrategyResponse, String>
-    where
-        C: HttpClient,
-    {
-        let request = http::Request::get(endpoint)
-            .header("Content-Type", "application/json")
-            .body(Vec::new())
-            .unwrap();
-
-        let resp = client
-            .send(request)
-            .await
-            .map_err(|err| format!("the request is failed to send {}", err))?;
-
-        // process failures
-        if resp.status() != http::StatusCode::OK {
-            return Err(format!(
-                "the http response code is not 200 but {}",
-                resp.status()
-            ));
-        }
-
-        // deserialize the response
-        serde_json::from_slice(&resp.body()[..])
-            .map_err(|err| format!("cannot deserialize the response, {}", err))
-    }
 }
 
 impl ShouldSample for JaegerRemoteSampler {
@@ -272,7 +336,9 @@ impl ShouldSample for JaegerRemoteSampler {
 
 #[cfg(test)]
 mod tests {
-    use crate::trace::sampler::jaeger_remote::remote::SamplingStrategyType;
+    use crate::trace::sampler::jaeger_remote::remote::{
+        SamplingStrategyResponse, SamplingStrategyType,
+    };
     use std::fmt::{Debug, Formatter};
 
     impl Debug for SamplingStrategyType {
@@ -292,7 +358,7 @@ mod tests {
                 "samplingRate": 0.5
             }
         }"#;
-        let resp: super::SamplingStrategyResponse = serde_json::from_str(json).unwrap();
+        let resp: SamplingStrategyResponse = serde_json::from_str(json).unwrap();
         assert_eq!(resp.strategy_type, SamplingStrategyType::Probabilistic);
         assert_eq!(resp.probabilistic_sampling.unwrap().sampling_rate, 0.5);
     }
@@ -0,0 +1,175 @@
+use crate::trace::sampler::jaeger_remote::file::StrategiesFile;
+use crate::trace::sampler::jaeger_remote::remote::{
+    SamplingStrategyResponse, SamplingStrategyType,
+};
+use futures_util::future::BoxFuture;
+use http::Uri;
+use opentelemetry_http::HttpClient;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Where [`JaegerRemoteSampler`] gets its sampling strategies from.
+///
+/// [`JaegerRemoteSampler`]: crate::trace::JaegerRemoteSampler
+pub(crate) trait StrategySource: Send + Sync + 'static {
+    /// Fetch the current sampling strategy of the service.
+    ///
+    /// Returns `None` if the strategy didn't change since the last fetch.
+    fn fetch(&self) -> BoxFuture<'_, Result<Option<SamplingStrategyResponse>, String>>;
+}
+
+/// Polls the sampling endpoint of a Jaeger agent or collector.
+pub(crate) struct HttpStrategySource<C> {
+    client: C,
+    endpoint: Uri,
+}
+
+impl<C: HttpClient> HttpStrategySource<C> {
+    pub(crate) fn new(client: C, endpoint: Uri) -> Self {
+        HttpStrategySource { client, endpoint }
+    }
+
+    async fn request_new_strategy(&self) -> Result<SamplingStrategyResponse, String> {
+        let request = http::Request::get(self.endpoint.clone())
+            .header("Content-Type", "application/json")
+            .body(Vec::new())
+            .unwrap();
+
+        let resp = self
+            .client
+            .send(request)
+            .await
+            .map_err(|err| format!("the request is failed to send {}", err))?;
+
+        // process failures
+        if resp.status() != http::StatusCode::OK {
+            return Err(format!(
+                "the http response code is not 200 but {}",
+                resp.status()
+            ));
+        }
+
+        // deserialize the response
+        serde_json::from_slice(&resp.body()[..])
+            .map_err(|err| format!("cannot deserialize the response, {}", err))
+    }
+}
+
+impl<C: HttpClient + 'static> StrategySource for HttpStrategySource<C> {
+    fn fetch(&self) -> BoxFuture<'_, Result<Option<SamplingStrategyResponse>, String>> {
+        Box::pin(async move { self.request_new_strategy().await.map(Some) })
+    }
+}
+
+/// Reads the strategies of a service from a Jaeger `strategies.json` file.
+///
+/// The file is only read again when its modification time or its size changes.
+pub(crate) struct FileStrategySource {
+    path: PathBuf,
+    service_name: String,
+    last_version: Mutex<Option<(SystemTime, u64)>>,
+}
+
+impl FileStrategySource {
+    pub(crate) fn new(path: PathBuf, service_name: String) -> Self {
+        FileStrategySource {
+            path,
+            service_name,
+            last_version: Mutex::new(None),
+        }
+    }
+
+    /// Load the strategy if the file changed since the last load.
+    ///
+    /// A file without strategy for the service loads an empty strategy, so that the sampler goes
+    /// back to its default sampler.
+    pub(crate) fn load(&self) -> Result<Option<SamplingStrategyResponse>, String> {
+        let version = fs::metadata(&self.path)
+            .and_then(|metadata| Ok((metadata.modified()?, metadata.len())))
+            .map_err(|err| format!("cannot read {}, {}", self.path.display(), err))?;
+        let mut last_version = self
+            .last_version
+            .lock()
+            .map_err(|_| "jaeger file sampler mutex poisoned".to_string())?;
+        if *last_version == Some(version) {
+            return Ok(None);
+        }
+
+        let content = fs::read(&self.path)
+            .map_err(|err| format!("cannot read {}, {}", self.path.display(), err))?;
+        let strategies: StrategiesFile = serde_json::from_slice(&content)
+            .map_err(|err| format!("cannot parse {}, {}", self.path.display(), err))?;
+        *last_version = Some(version);
+
+        Ok(Some(
+            strategies
+                .into_response(&self.service_name)
+                .unwrap_or(SamplingStrategyResponse {
+                    strategy_type: SamplingStrategyType::Probabilistic,
+                    probabilistic_sampling: None,
+                    rate_limiting_sampling: None,
+                    operation_sampling: None,
+                }),
+        ))
+    }
+}
+
+impl StrategySource for FileStrategySource {
+    fn fetch(&self) -> BoxFuture<'_, Result<Option<SamplingStrategyResponse>, String>> {
+        // the file is small, reading it doesn't block the runtime for long
+        Box::pin(std::future::ready(self.load()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_source_reloads_changed_file() {
+        let path = std::env::temp_dir().join(format!(
+            "opentelemetry-jaeger-strategies-{}.json",
+            std::process::id()
+        ));
+        fs::write(
+            &path,
+            r#"{"default_strategy": {"type": "probabilistic", "param": 0.5}}"#,
+        )
+        .unwrap();
+        let source = FileStrategySource::new(path.clone(), "foo".to_string());
+
+        let strategy = source.load().unwrap().unwrap();
+        assert_eq!(strategy.probabilistic_sampling.unwrap().sampling_rate, 0.5);
+        // unchanged file
+        assert!(source.load().unwrap().is_none());
+
+        fs::write(
+            &path,
+            r#"{"default_strategy": {"type": "ratelimiting", "param": 5}}"#,
+        )
+        .unwrap();
+        let strategy = source.load().unwrap().unwrap();
+        assert_eq!(
+            strategy
+                .rate_limiting_sampling
+                .unwrap()
+                .max_traces_per_second,
+            5
+        );
+
+        fs::write(
+            &path,
+            r#"{"service_strategies": [{"service": "bar", "type": "ratelimiting", "param": 5}]}"#,
+        )
+        .unwrap();
+        let strategy = source.load().unwrap().unwrap();
+        assert!(strategy.probabilistic_sampling.is_none());
+        assert!(strategy.rate_limiting_sampling.is_none());
+        assert!(strategy.operation_sampling.is_none());
+
+        fs::remove_file(&path).unwrap();
+        assert!(source.load().is_err());
+    }
+}
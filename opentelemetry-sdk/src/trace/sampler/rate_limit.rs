@@ -0,0 +1,189 @@
+use opentelemetry_api::trace::TraceError;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// The clock of a [`RateLimitingSampler`].
+type Clock = Arc<dyn Fn() -> Instant + Send + Sync>;
+
+/// State of [`Sampler::RateLimited`], created with [`Sampler::rate_limited`].
+///
+/// Clones of the sampler share the same budget.
+///
+/// [`Sampler::RateLimited`]: crate::trace::Sampler::RateLimited
+/// [`Sampler::rate_limited`]: crate::trace::Sampler::rate_limited
+#[derive(Clone)]
+pub struct RateLimitingSampler {
+    traces_per_second: f64,
+    bucket: Arc<Mutex<LeakyBucket>>,
+    clock: Clock,
+}
+
+impl RateLimitingSampler {
+    /// Create a sampler sampling at most `traces_per_second` traces per second.
+    pub fn new(traces_per_second: f64) -> Self {
+        Self::with_clock(traces_per_second, Arc::new(Instant::now))
+    }
+
+    pub(crate) fn with_clock(traces_per_second: f64, clock: Clock) -> Self {
+        let traces_per_second = traces_per_second.max(0.0);
+        // allow bursts of a second of traces, and at least one trace
+        let mut bucket = LeakyBucket::new(traces_per_second.max(1.0), traces_per_second);
+        bucket.last_time = clock();
+
+        RateLimitingSampler {
+            traces_per_second,
+            bucket: Arc::new(Mutex::new(bucket)),
+            clock,
+        }
+    }
+
+    /// The maximum number of traces sampled per second.
+    pub fn traces_per_second(&self) -> f64 {
+        self.traces_per_second
+    }
+
+    pub(crate) fn should_sample(&self) -> bool {
+        match self.bucket.lock() {
+            Ok(mut bucket) => bucket.check_availability(|| (self.clock)()),
+            Err(err) => {
+                opentelemetry_api::global::handle_error(TraceError::from(err.to_string()));
+                false
+            }
+        }
+    }
+}
+
+impl fmt::Debug for RateLimitingSampler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimitingSampler")
+            .field("traces_per_second", &self.traces_per_second)
+            .finish()
+    }
+}
+
+// leaky bucket based rate limit
+// should be Send+Sync
+pub(crate) struct LeakyBucket {
+    span_per_sec: f64,
+    available: f64,
+    bucket_size: f64,
+    last_time: Instant,
+}
+
+impl LeakyBucket {
+    pub(crate) fn new(bucket_size: f64, span_per_sec: f64) -> LeakyBucket {
+        LeakyBucket {
+            span_per_sec,
+            available: bucket_size,
+            bucket_size,
+            last_time: Instant::now(),
+        }
+    }
+
+    fn check_availability<F>(&mut self, now: F) -> bool
+    where
+        F: Fn() -> Instant,
+    {
+        if self.available >= 1.0 {
+            self.available -= 1.0;
+            true
+        } else {
+            // the clock is monotonic, wall clock adjustments don't refill the bucket
+            let cur_time = now();
+            if cur_time > self.last_time {
+                let elapsed = cur_time.duration_since(self.last_time);
+                self.last_time = cur_time;
+                self.available = f64::min(
+                    elapsed.as_secs_f64() * self.span_per_sec + self.available,
+                    self.bucket_size,
+                );
+            }
+
+            if self.available >= 1.0 {
+                self.available -= 1.0;
+                true
+            } else {
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LeakyBucket, RateLimitingSampler};
+    use std::ops::Add;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_leaky_bucket() {
+        // maximum bucket size 2, add 1 allowance every 10 seconds
+        let mut leaky_bucket = LeakyBucket::new(2.0, 0.1);
+        let current_time = Instant::now();
+        leaky_bucket.last_time = current_time;
+
+        let test_cases = vec![
+            (0, vec![true, true, false]),
+            (1, vec![false]),
+            (5, vec![false]),
+            (10, vec![true, false]),
+            (60, vec![true, true, false]), // maximum allowance is 2
+        ];
+
+        for (elapsed_sec, cases) in test_cases.into_iter() {
+            for should_pass in cases {
+                assert_eq!(
+                    should_pass,
+                    leaky_bucket.check_availability(|| {
+                        current_time.add(Duration::from_secs(elapsed_sec))
+                    })
+                )
+            }
+        }
+    }
+
+    #[test]
+    fn test_earlier_time_keeps_limiting() {
+        let mut leaky_bucket = LeakyBucket::new(1.0, 0.1);
+        let current_time = Instant::now();
+        leaky_bucket.last_time = current_time;
+
+        assert!(leaky_bucket.check_availability(|| current_time));
+        assert!(!leaky_bucket.check_availability(|| current_time - Duration::from_secs(10)));
+        // the earlier time isn't used as the start of the next refill
+        assert!(!leaky_bucket.check_availability(|| current_time + Duration::from_secs(5)));
+        assert!(leaky_bucket.check_availability(|| current_time + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_rate_limiting_sampler() {
+        let start = Instant::now();
+        let now = Arc::new(Mutex::new(start));
+        let clock = now.clone();
+        let sampler =
+            RateLimitingSampler::with_clock(2.0, Arc::new(move || *clock.lock().unwrap()));
+        let advance = |millis| {
+            let mut now = now.lock().unwrap();
+            *now = now.add(Duration::from_millis(millis));
+        };
+
+        // a burst of a second of traces is allowed
+        assert!(sampler.should_sample());
+        assert!(sampler.should_sample());
+        assert!(!sampler.should_sample());
+
+        // the budget refills continuously
+        advance(250);
+        assert!(!sampler.should_sample());
+        advance(250);
+        assert!(sampler.should_sample());
+        assert!(!sampler.should_sample());
+
+        // clones share the budget
+        advance(500);
+        assert!(sampler.clone().should_sample());
+        assert!(!sampler.should_sample());
+    }
+}
@@ -0,0 +1,575 @@
+use crate::trace::{Sampler, ShouldSample};
+use crate::InstrumentationLibrary;
+use opentelemetry_api::trace::{
+    Link, OrderMap, SamplingResult, SpanKind, TraceError, TraceId, TraceResult,
+};
+use opentelemetry_api::{Context, Key, KeyValue, Value};
+use regex::Regex;
+use serde::Deserialize;
+
+/// A sampler delegating the decision to the sampler of the first rule matching
+/// the span, or to a fallback sampler if no rule matches.
+///
+/// Rules match on the span name, either exactly or with a regular expression,
+/// on the span kind and on the span attributes given when the span is created.
+/// All the criteria of a rule must match for the rule to apply.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry_api::trace::SpanKind;
+/// use opentelemetry_api::KeyValue;
+/// use opentelemetry_sdk::trace::{RuleBasedSampler, Sampler, SamplingRule};
+///
+/// # fn main() -> opentelemetry_api::trace::TraceResult<()> {
+/// let sampler = RuleBasedSampler::new(
+///     vec![
+///         // drop health checks
+///         SamplingRule::builder(Sampler::AlwaysOff)
+///             .with_name_regex("^GET /health")
+///             .build()?,
+///         // keep all the payments
+///         SamplingRule::builder(Sampler::AlwaysOn)
+///             .with_span_kind(SpanKind::Server)
+///             .with_attribute(KeyValue::new("http.route", "/payments"))
+///             .build()?,
+///     ],
+///     Sampler::TraceIdRatioBased(0.1),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The rules can also be loaded from a configuration file with
+/// [`RuleBasedSampler::from_config`].
+#[derive(Clone, Debug)]
+pub struct RuleBasedSampler {
+    rules: Vec<SamplingRule>,
+    fallback: Box<dyn ShouldSample>,
+}
+
+impl RuleBasedSampler {
+    /// Create a sampler applying the first of `rules` matching a span, or
+    /// `fallback` if none of them matches.
+    pub fn new<S>(rules: Vec<SamplingRule>, fallback: S) -> Self
+    where
+        S: ShouldSample + 'static,
+    {
+        RuleBasedSampler {
+            rules,
+            fallback: Box::new(fallback),
+        }
+    }
+
+    /// Create a sampler from its deserialized configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry_sdk::trace::{RuleBasedSampler, RuleBasedSamplerConfig};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config: RuleBasedSamplerConfig = serde_json::from_str(
+    ///     r#"{
+    ///         "rules": [
+    ///             { "name_regex": "^GET /health", "sampler": { "type": "always_off" } },
+    ///             {
+    ///                 "span_kind": "server",
+    ///                 "attributes": [{ "key": "http.route", "value": "/payments" }],
+    ///                 "sampler": { "type": "always_on" }
+    ///             }
+    ///         ],
+    ///         "fallback": { "type": "trace_id_ratio_based", "ratio": 0.1 }
+    ///     }"#,
+    /// )?;
+    /// let sampler = RuleBasedSampler::from_config(config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_config(config: RuleBasedSamplerConfig) -> TraceResult<Self> {
+        let rules = config
+            .rules
+            .into_iter()
+            .map(SamplingRule::from_config)
+            .collect::<TraceResult<_>>()?;
+
+        Ok(RuleBasedSampler {
+            rules,
+            fallback: Box::new(config.fallback.into_sampler()),
+        })
+    }
+}
+
+impl ShouldSample for RuleBasedSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &OrderMap<Key, Value>,
+        links: &[Link],
+        instrumentation_library: &InstrumentationLibrary,
+    ) -> SamplingResult {
+        let sampler = self
+            .rules
+            .iter()
+            .find(|rule| rule.matches(name, span_kind, attributes))
+            .map_or(&self.fallback, |rule| &rule.sampler);
+
+        sampler.should_sample(
+            parent_context,
+            trace_id,
+            name,
+            span_kind,
+            attributes,
+            links,
+            instrumentation_library,
+        )
+    }
+}
+
+/// A rule of a [`RuleBasedSampler`], created with [`SamplingRule::builder`].
+#[derive(Clone, Debug)]
+pub struct SamplingRule {
+    name: Option<NameMatcher>,
+    span_kind: Option<SpanKind>,
+    attributes: Vec<AttributeMatcher>,
+    sampler: Box<dyn ShouldSample>,
+}
+
+impl SamplingRule {
+    /// Create a rule builder, `sampler` decides on the spans matching the rule.
+    ///
+    /// A rule without criteria matches all the spans.
+    pub fn builder<S>(sampler: S) -> SamplingRuleBuilder
+    where
+        S: ShouldSample + 'static,
+    {
+        SamplingRuleBuilder {
+            name: None,
+            name_regex: None,
+            span_kind: None,
+            attributes: Vec::new(),
+            sampler: Box::new(sampler),
+        }
+    }
+
+    fn from_config(config: SamplingRuleConfig) -> TraceResult<Self> {
+        let mut builder = SamplingRule::builder(config.sampler.into_sampler());
+        if let Some(name) = config.name {
+            builder = builder.with_name(name);
+        }
+        if let Some(name_regex) = config.name_regex {
+            builder = builder.with_name_regex(name_regex);
+        }
+        if let Some(span_kind) = config.span_kind {
+            builder = builder.with_span_kind(span_kind.into());
+        }
+        for attribute in config.attributes {
+            builder = match attribute.value {
+                Some(value) => builder.with_attribute(KeyValue::new(attribute.key, value)),
+                None => builder.with_attribute_key(attribute.key),
+            };
+        }
+
+        builder.build()
+    }
+
+    fn matches(&self, name: &str, span_kind: &SpanKind, attributes: &OrderMap<Key, Value>) -> bool {
+        self.name
+            .as_ref()
+            .map_or(true, |matcher| matcher.matches(name))
+            && self
+                .span_kind
+                .as_ref()
+                .map_or(true, |kind| kind == span_kind)
+            && self
+                .attributes
+                .iter()
+                .all(|matcher| matcher.matches(attributes))
+    }
+}
+
+#[derive(Clone, Debug)]
+enum NameMatcher {
+    Exact(String),
+    Regex(Regex),
+}
+
+impl NameMatcher {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            NameMatcher::Exact(expected) => expected == name,
+            NameMatcher::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum AttributeMatcher {
+    Equals(KeyValue),
+    Present(Key),
+}
+
+impl AttributeMatcher {
+    fn matches(&self, attributes: &OrderMap<Key, Value>) -> bool {
+        match self {
+            AttributeMatcher::Equals(kv) => attributes.get(&kv.key) == Some(&kv.value),
+            AttributeMatcher::Present(key) => attributes.contains_key(key),
+        }
+    }
+}
+
+/// Builder for [`SamplingRule`].
+#[derive(Debug)]
+pub struct SamplingRuleBuilder {
+    name: Option<String>,
+    name_regex: Option<String>,
+    span_kind: Option<SpanKind>,
+    attributes: Vec<AttributeMatcher>,
+    sampler: Box<dyn ShouldSample>,
+}
+
+impl SamplingRuleBuilder {
+    /// Match spans with exactly the given name.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Match spans whose name matches the given regular expression. The
+    /// expression is not anchored, use `^` and `$` to match the whole name.
+    pub fn with_name_regex(mut self, name_regex: impl Into<String>) -> Self {
+        self.name_regex = Some(name_regex.into());
+        self
+    }
+
+    /// Match spans of the given kind.
+    pub fn with_span_kind(mut self, span_kind: SpanKind) -> Self {
+        self.span_kind = Some(span_kind);
+        self
+    }
+
+    /// Match spans created with the given attribute.
+    pub fn with_attribute(mut self, attribute: KeyValue) -> Self {
+        self.attributes.push(AttributeMatcher::Equals(attribute));
+        self
+    }
+
+    /// Match spans created with an attribute of the given key, whatever its
+    /// value.
+    pub fn with_attribute_key(mut self, key: impl Into<Key>) -> Self {
+        self.attributes.push(AttributeMatcher::Present(key.into()));
+        self
+    }
+
+    /// Build the rule.
+    ///
+    /// An error is returned if both an exact name and a name regular
+    /// expression are set, or if the regular expression is invalid.
+    pub fn build(self) -> TraceResult<SamplingRule> {
+        let name = match (self.name, self.name_regex) {
+            (Some(_), Some(_)) => {
+                return Err(TraceError::from(
+                    "a sampling rule cannot match both an exact name and a name regex",
+                ))
+            }
+            (Some(name), None) => Some(NameMatcher::Exact(name)),
+            (None, Some(name_regex)) => Some(NameMatcher::Regex(Regex::new(&name_regex).map_err(
+                |err| TraceError::from(format!("invalid sampling rule name regex: {}", err)),
+            )?)),
+            (None, None) => None,
+        };
+
+        Ok(SamplingRule {
+            name,
+            span_kind: self.span_kind,
+            attributes: self.attributes,
+            sampler: self.sampler,
+        })
+    }
+}
+
+/// Deserializable configuration of a [`RuleBasedSampler`].
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RuleBasedSamplerConfig {
+    /// The rules, in the order they are tried.
+    #[serde(default)]
+    pub rules: Vec<SamplingRuleConfig>,
+    /// The sampler of the spans matching no rule.
+    pub fallback: SamplerConfig,
+}
+
+/// Deserializable configuration of a [`SamplingRule`].
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SamplingRuleConfig {
+    /// Match spans with exactly this name.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Match spans whose name matches this regular expression.
+    #[serde(default)]
+    pub name_regex: Option<String>,
+    /// Match spans of this kind.
+    #[serde(default)]
+    pub span_kind: Option<SpanKindConfig>,
+    /// Match spans with all these attributes.
+    #[serde(default)]
+    pub attributes: Vec<AttributeRuleConfig>,
+    /// The sampler of the spans matching the rule.
+    pub sampler: SamplerConfig,
+}
+
+/// Deserializable span kind, in snake case.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanKindConfig {
+    /// [`SpanKind::Client`]
+    Client,
+    /// [`SpanKind::Server`]
+    Server,
+    /// [`SpanKind::Producer`]
+    Producer,
+    /// [`SpanKind::Consumer`]
+    Consumer,
+    /// [`SpanKind::Internal`]
+    Internal,
+}
+
+impl From<SpanKindConfig> for SpanKind {
+    fn from(kind: SpanKindConfig) -> Self {
+        match kind {
+            SpanKindConfig::Client => SpanKind::Client,
+            SpanKindConfig::Server => SpanKind::Server,
+            SpanKindConfig::Producer => SpanKind::Producer,
+            SpanKindConfig::Consumer => SpanKind::Consumer,
+            SpanKindConfig::Internal => SpanKind::Internal,
+        }
+    }
+}
+
+/// Deserializable attribute criterion of a [`SamplingRuleConfig`].
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AttributeRuleConfig {
+    /// The attribute key.
+    pub key: String,
+    /// The attribute value, any value matches if it is not set.
+    #[serde(default)]
+    pub value: Option<AttributeValueConfig>,
+}
+
+/// Deserializable attribute value.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum AttributeValueConfig {
+    /// A boolean value.
+    Bool(bool),
+    /// An integer value.
+    I64(i64),
+    /// A floating point value.
+    F64(f64),
+    /// A string value.
+    String(String),
+}
+
+impl From<AttributeValueConfig> for Value {
+    fn from(value: AttributeValueConfig) -> Self {
+        match value {
+            AttributeValueConfig::Bool(value) => value.into(),
+            AttributeValueConfig::I64(value) => value.into(),
+            AttributeValueConfig::F64(value) => value.into(),
+            AttributeValueConfig::String(value) => value.into(),
+        }
+    }
+}
+
+/// Deserializable delegate sampler, tagged by its `type` in snake case.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+#[non_exhaustive]
+pub enum SamplerConfig {
+    /// [`Sampler::AlwaysOn`]
+    AlwaysOn,
+    /// [`Sampler::AlwaysOff`]
+    AlwaysOff,
+    /// [`Sampler::TraceIdRatioBased`]
+    TraceIdRatioBased {
+        /// The fraction of traces sampled.
+        ratio: f64,
+    },
+    /// [`Sampler::ConsistentProbabilityBased`]
+    ConsistentProbabilityBased {
+        /// The fraction of traces sampled.
+        ratio: f64,
+    },
+    /// [`Sampler::ParentBased`]
+    ParentBased {
+        /// The sampler of the root spans.
+        root: Box<SamplerConfig>,
+    },
+}
+
+impl SamplerConfig {
+    fn into_sampler(self) -> Sampler {
+        match self {
+            SamplerConfig::AlwaysOn => Sampler::AlwaysOn,
+            SamplerConfig::AlwaysOff => Sampler::AlwaysOff,
+            SamplerConfig::TraceIdRatioBased { ratio } => Sampler::TraceIdRatioBased(ratio),
+            SamplerConfig::ConsistentProbabilityBased { ratio } => {
+                Sampler::ConsistentProbabilityBased(ratio)
+            }
+            SamplerConfig::ParentBased { root } => {
+                Sampler::ParentBased(Box::new(root.into_sampler()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_api::trace::SamplingDecision;
+
+    fn decision(
+        sampler: &RuleBasedSampler,
+        name: &str,
+        span_kind: SpanKind,
+        attributes: Vec<KeyValue>,
+    ) -> SamplingDecision {
+        let attributes = attributes
+            .into_iter()
+            .map(|kv| (kv.key, kv.value))
+            .collect();
+        sampler
+            .should_sample(
+                None,
+                TraceId::from_u128(1),
+                name,
+                &span_kind,
+                &attributes,
+                &[],
+                &InstrumentationLibrary::default(),
+            )
+            .decision
+    }
+
+    #[test]
+    fn test_rules_are_tried_in_order() {
+        let sampler = RuleBasedSampler::new(
+            vec![
+                SamplingRule::builder(Sampler::AlwaysOff)
+                    .with_name_regex("^GET /health")
+                    .build()
+                    .unwrap(),
+                SamplingRule::builder(Sampler::AlwaysOn)
+                    .with_span_kind(SpanKind::Server)
+                    .with_attribute(KeyValue::new("http.route", "/payments"))
+                    .build()
+                    .unwrap(),
+                SamplingRule::builder(Sampler::AlwaysOn)
+                    .with_name("debug")
+                    .with_attribute_key("debug.id")
+                    .build()
+                    .unwrap(),
+            ],
+            Sampler::AlwaysOff,
+        );
+
+        let payments = vec![KeyValue::new("http.route", "/payments")];
+        assert_eq!(
+            decision(&sampler, "GET /health/live", SpanKind::Server, vec![]),
+            SamplingDecision::Drop
+        );
+        assert_eq!(
+            decision(&sampler, "POST", SpanKind::Server, payments.clone()),
+            SamplingDecision::RecordAndSample
+        );
+        // all the criteria of a rule must match
+        assert_eq!(
+            decision(&sampler, "POST", SpanKind::Client, payments),
+            SamplingDecision::Drop
+        );
+        assert_eq!(
+            decision(
+                &sampler,
+                "debug",
+                SpanKind::Internal,
+                vec![KeyValue::new("debug.id", 3)]
+            ),
+            SamplingDecision::RecordAndSample
+        );
+        assert_eq!(
+            decision(&sampler, "debug", SpanKind::Internal, vec![]),
+            SamplingDecision::Drop
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert!(SamplingRule::builder(Sampler::AlwaysOn)
+            .with_name_regex("(")
+            .build()
+            .is_err());
+        assert!(SamplingRule::builder(Sampler::AlwaysOn)
+            .with_name("a")
+            .with_name_regex("b")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config: RuleBasedSamplerConfig = serde_json::from_str(
+            r#"{
+                "rules": [
+                    {
+                        "name": "checkout",
+                        "attributes": [
+                            { "key": "retry", "value": true },
+                            { "key": "user.id" }
+                        ],
+                        "sampler": { "type": "always_on" }
+                    },
+                    {
+                        "span_kind": "consumer",
+                        "sampler": { "type": "parent_based", "root": { "type": "always_on" } }
+                    }
+                ],
+                "fallback": { "type": "trace_id_ratio_based", "ratio": 0.0 }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.rules[0].attributes[0].value,
+            Some(AttributeValueConfig::Bool(true))
+        );
+
+        let sampler = RuleBasedSampler::from_config(config).unwrap();
+        assert_eq!(
+            decision(
+                &sampler,
+                "checkout",
+                SpanKind::Internal,
+                vec![KeyValue::new("retry", true), KeyValue::new("user.id", 1)]
+            ),
+            SamplingDecision::RecordAndSample
+        );
+        assert_eq!(
+            decision(&sampler, "queue", SpanKind::Consumer, vec![]),
+            SamplingDecision::RecordAndSample
+        );
+        assert_eq!(
+            decision(&sampler, "checkout", SpanKind::Internal, vec![]),
+            SamplingDecision::Drop
+        );
+
+        // unknown fields are rejected
+        assert!(serde_json::from_str::<RuleBasedSamplerConfig>(
+            r#"{ "fallback": { "type": "always_on" }, "rule": [] }"#
+        )
+        .is_err());
+    }
+}
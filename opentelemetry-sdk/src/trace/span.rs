@@ -96,10 +96,12 @@ impl opentelemetry_api::trace::Span for Span {
     ) where
         T: Into<Cow<'static, str>>,
     {
-        let event_attributes_limit = self.span_limits.max_attributes_per_event as usize;
+        let span_limits = self.span_limits;
+        let event_attributes_limit = span_limits.max_attributes_per_event as usize;
         self.with_data(|data| {
             let dropped_attributes_count = attributes.len().saturating_sub(event_attributes_limit);
             attributes.truncate(event_attributes_limit);
+            span_limits.truncate_attributes(&mut attributes);
 
             data.events.push_back(Event::new(
                 name,
@@ -127,8 +129,10 @@ impl opentelemetry_api::trace::Span for Span {
     /// Note that the OpenTelemetry project documents certain ["standard
     /// attributes"](https://github.com/open-telemetry/opentelemetry-specification/tree/v0.5.0/specification/trace/semantic_conventions/README.md)
     /// that have prescribed semantic meanings.
-    fn set_attribute(&mut self, attribute: KeyValue) {
+    fn set_attribute(&mut self, mut attribute: KeyValue) {
+        let span_limits = self.span_limits;
         self.with_data(|data| {
+            attribute.value = span_limits.truncate_value(attribute.value);
             data.attributes.insert(attribute);
         });
     }
@@ -245,7 +249,9 @@ mod tests {
         DEFAULT_MAX_ATTRIBUTES_PER_EVENT, DEFAULT_MAX_ATTRIBUTES_PER_LINK,
     };
     use opentelemetry_api::trace::{Link, TraceFlags, TraceId, Tracer};
-    use opentelemetry_api::{trace::Span as _, trace::TracerProvider, KeyValue};
+    use opentelemetry_api::{
+        trace::Span as _, trace::TracerProvider, KeyValue, StringValue, Value,
+    };
     use std::time::Duration;
 
     fn init() -> (crate::trace::Tracer, SpanData) {
@@ -564,6 +570,62 @@ mod tests {
         assert_eq!(processed_link.attributes.len(), 128);
     }
 
+    #[test]
+    fn truncate_attribute_values() {
+        let provider = crate::trace::TracerProvider::builder()
+            .with_simple_exporter(NoopSpanExporter::new())
+            .with_config(crate::trace::config().with_max_attribute_value_length(4))
+            .build();
+        let tracer = provider.tracer("opentelemetry-test");
+        let link = Link::new(
+            SpanContext::new(
+                TraceId::from_u128(12),
+                SpanId::from_u64(12),
+                TraceFlags::default(),
+                false,
+                Default::default(),
+            ),
+            vec![KeyValue::new("link", "link value")],
+        );
+        let mut event = Event::with_name("test event");
+        event.attributes.push(KeyValue::new("event", "event value"));
+
+        let span_builder = tracer
+            .span_builder("test")
+            .with_attributes(vec![KeyValue::new("builder", "builder value")])
+            .with_links(vec![link])
+            .with_events(vec![event]);
+        let mut span = tracer.build(span_builder);
+        span.set_attribute(KeyValue::new(
+            "array",
+            Value::Array(vec![StringValue::from("array value")].into()),
+        ));
+        span.add_event("another event", vec![KeyValue::new("event", "ünïcode")]);
+
+        let data = span.data.clone().expect("span should be recording");
+        let mut attributes: Vec<_> = data
+            .attributes
+            .iter()
+            .map(|(key, value)| KeyValue::new(key.clone(), value.clone()))
+            .collect();
+        attributes.sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(
+            attributes,
+            vec![
+                KeyValue::new(
+                    "array",
+                    Value::Array(vec![StringValue::from("arra")].into())
+                ),
+                KeyValue::new("builder", "buil"),
+            ]
+        );
+        let links: Vec<_> = data.links.iter().collect();
+        assert_eq!(links[0].attributes, vec![KeyValue::new("link", "link")]);
+        let events: Vec<_> = data.events.iter().collect();
+        assert_eq!(events[0].attributes, vec![KeyValue::new("event", "even")]);
+        assert_eq!(events[1].attributes, vec![KeyValue::new("event", "ünïc")]);
+    }
+
     #[test]
     fn test_span_exported_data() {
         let provider = crate::trace::TracerProvider::builder()
@@ -1,3 +1,5 @@
+use opentelemetry_api::{Array, KeyValue, StringValue, Value};
+
 /// # Span limit
 /// Erroneous code can add unintended attributes, events, and links to a span. If these collections
 /// are unbounded, they can quickly exhaust available memory, resulting in crashes that are
@@ -9,9 +11,13 @@
 ///  - Maximum allowed span link count
 ///  - Maximum allowed attribute per span event count
 ///  - Maximum allowed attribute per span link count
+///  - Maximum allowed attribute value length
 ///
 /// If the limit has been breached. The attributes, events or links will be dropped based on their
 /// index in the collection. The one added to collections later will be dropped first.
+///
+/// String attribute values, and the strings of string array values, longer than the attribute
+/// value length limit are truncated to that many characters. There is no such limit by default.
 
 pub(crate) const DEFAULT_MAX_EVENT_PER_SPAN: u32 = 128;
 pub(crate) const DEFAULT_MAX_ATTRIBUTES_PER_SPAN: u32 = 128;
@@ -32,6 +38,9 @@ pub struct SpanLimits {
     pub max_attributes_per_event: u32,
     /// The max attributes that can be added into a `Link`
     pub max_attributes_per_link: u32,
+    /// The max length, in characters, of string attribute values of a `Span`, its `Event`s and
+    /// its `Link`s. `None` means values are never truncated.
+    pub max_attribute_value_length: Option<u32>,
 }
 
 impl Default for SpanLimits {
@@ -42,6 +51,92 @@ impl Default for SpanLimits {
             max_links_per_span: DEFAULT_MAX_LINKS_PER_SPAN,
             max_attributes_per_link: DEFAULT_MAX_ATTRIBUTES_PER_LINK,
             max_attributes_per_event: DEFAULT_MAX_ATTRIBUTES_PER_EVENT,
+            max_attribute_value_length: None,
+        }
+    }
+}
+
+impl SpanLimits {
+    /// Truncate `value` if it is longer than the attribute value length limit.
+    pub(crate) fn truncate_value(&self, value: Value) -> Value {
+        let limit = match self.max_attribute_value_length {
+            Some(limit) => limit as usize,
+            None => return value,
+        };
+
+        match value {
+            Value::String(value) => Value::String(truncate_string(value, limit)),
+            Value::Array(Array::String(values)) => Value::Array(Array::String(
+                values
+                    .into_iter()
+                    .map(|value| truncate_string(value, limit))
+                    .collect(),
+            )),
+            value => value,
+        }
+    }
+
+    /// Truncate the values of `attributes` longer than the attribute value length limit.
+    pub(crate) fn truncate_attributes(&self, attributes: &mut [KeyValue]) {
+        if self.max_attribute_value_length.is_none() {
+            return;
+        }
+
+        for attribute in attributes {
+            let value = std::mem::replace(&mut attribute.value, Value::Bool(false));
+            attribute.value = self.truncate_value(value);
         }
     }
 }
+
+/// Keep the first `limit` characters of `value`.
+fn truncate_string(value: StringValue, limit: usize) -> StringValue {
+    match value.as_str().char_indices().nth(limit) {
+        Some((end, _)) => value.as_str()[..end].to_string().into(),
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_value() {
+        let limits = SpanLimits {
+            max_attribute_value_length: Some(3),
+            ..SpanLimits::default()
+        };
+
+        assert_eq!(limits.truncate_value("abcdef".into()), "abc".into());
+        assert_eq!(limits.truncate_value("ab".into()), "ab".into());
+        // multi-byte characters are kept whole
+        assert_eq!(limits.truncate_value("héllo".into()), "hél".into());
+        assert_eq!(limits.truncate_value("日本語です".into()), "日本語".into());
+        assert_eq!(
+            limits.truncate_value(Value::Array(Array::String(vec![
+                "abcdef".into(),
+                "ab".into()
+            ]))),
+            Value::Array(Array::String(vec!["abc".into(), "ab".into()]))
+        );
+        assert_eq!(limits.truncate_value(12345.into()), 12345.into());
+
+        let unlimited = SpanLimits::default();
+        assert_eq!(unlimited.truncate_value("abcdef".into()), "abcdef".into());
+    }
+
+    #[test]
+    fn test_truncate_attributes() {
+        let limits = SpanLimits {
+            max_attribute_value_length: Some(0),
+            ..SpanLimits::default()
+        };
+        let mut attributes = vec![KeyValue::new("key", "value"), KeyValue::new("bool", true)];
+        limits.truncate_attributes(&mut attributes);
+        assert_eq!(
+            attributes,
+            vec![KeyValue::new("key", ""), KeyValue::new("bool", true)]
+        );
+    }
+}
@@ -0,0 +1,803 @@
+//! # Tail-based Sampling
+//!
+//! Span processor deciding whether to export a trace once all its spans are
+//! finished, rather than when its first span starts.
+//!
+//! The [`TailSamplingSpanProcessor`] is also exported from the `trace` module,
+//! the policies deciding which traces are kept are defined here.
+use crate::export::trace::{SpanData, SpanExporter};
+use crate::trace::sampler::sample_based_on_probability;
+use crate::trace::{Span, SpanProcessor};
+use opentelemetry_api::trace::{
+    SamplingDecision, SpanId, Status, TraceError, TraceId, TraceResult,
+};
+use opentelemetry_api::{global, Context, Key, Value};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default time to wait for the spans of a trace before deciding on it.
+const DEFAULT_DECISION_WAIT: Duration = Duration::from_secs(30);
+/// Default maximum number of traces buffered.
+const DEFAULT_MAX_TRACES: usize = 10_000;
+/// Default maximum number of spans buffered for a trace.
+const DEFAULT_MAX_SPANS_PER_TRACE: usize = 1_000;
+/// Default maximum number of spans waiting to be processed by the worker.
+const DEFAULT_MAX_QUEUE_SIZE: usize = 2_048;
+/// Maximum number of spans of the decided traces sent to the exporter at once.
+const MAX_EXPORT_BATCH_SIZE: usize = 512;
+
+/// Decides whether a complete trace is kept by a [`TailSamplingSpanProcessor`].
+pub trait TracePolicy: Send + Sync + fmt::Debug {
+    /// Returns `true` if the trace made of `spans` should be exported.
+    ///
+    /// `spans` holds the finished spans of a single trace, in the order they
+    /// ended, and is never empty.
+    fn should_keep(&self, spans: &[SpanData]) -> bool;
+}
+
+/// The status code matched by [`Policy::StatusCode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusCode {
+    /// The default status.
+    Unset,
+    /// The operation completed successfully.
+    Ok,
+    /// The operation contains an error.
+    Error,
+}
+
+impl StatusCode {
+    fn matches(&self, status: &Status) -> bool {
+        matches!(
+            (self, status),
+            (StatusCode::Unset, Status::Unset)
+                | (StatusCode::Ok, Status::Ok)
+                | (StatusCode::Error, Status::Error { .. })
+        )
+    }
+}
+
+/// Built-in tail sampling policies.
+///
+/// Policies are evaluated in order within [`Policy::And`] and [`Policy::Or`],
+/// and evaluation stops as soon as the result is known. This matters for
+/// [`Policy::RateLimiting`], which only counts the traces it keeps.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Policy {
+    /// Keep traces with at least one span of the given status code.
+    StatusCode(StatusCode),
+    /// Keep traces lasting at least the given duration, from the earliest span
+    /// start to the latest span end.
+    Latency(Duration),
+    /// Keep traces with at least one span having the attribute `key` set to
+    /// one of `values`, or set to any value if `values` is empty.
+    Attribute {
+        /// The attribute key to look for.
+        key: Key,
+        /// The accepted values of the attribute.
+        values: Vec<Value>,
+    },
+    /// Keep traces as long as the spans kept during the current second stay
+    /// within a budget, see [`Policy::rate_limiting`].
+    RateLimiting(RateLimiting),
+    /// Keep a given fraction of the traces, based on the trace id like the
+    /// [`Sampler::TraceIdRatioBased`] head sampler.
+    ///
+    /// [`Sampler::TraceIdRatioBased`]: crate::trace::Sampler::TraceIdRatioBased
+    Probabilistic(f64),
+    /// Keep traces kept by all the policies.
+    And(Vec<Policy>),
+    /// Keep traces kept by any of the policies.
+    Or(Vec<Policy>),
+    /// Keep traces kept by a custom policy.
+    Custom(Box<dyn TracePolicy>),
+}
+
+impl Policy {
+    /// A policy keeping at most `spans_per_second` spans every second.
+    pub fn rate_limiting(spans_per_second: u64) -> Self {
+        Policy::RateLimiting(RateLimiting {
+            spans_per_second,
+            state: Mutex::new(None),
+        })
+    }
+}
+
+impl TracePolicy for Policy {
+    fn should_keep(&self, spans: &[SpanData]) -> bool {
+        match self {
+            Policy::StatusCode(code) => spans.iter().any(|span| code.matches(&span.status)),
+            Policy::Latency(threshold) => {
+                let start = spans.iter().map(|span| span.start_time).min();
+                let end = spans.iter().map(|span| span.end_time).max();
+                match (start, end) {
+                    (Some(start), Some(end)) => end
+                        .duration_since(start)
+                        .map_or(false, |latency| latency >= *threshold),
+                    _ => false,
+                }
+            }
+            Policy::Attribute { key, values } => spans.iter().any(|span| {
+                span.attributes
+                    .get(key)
+                    .map_or(false, |value| values.is_empty() || values.contains(value))
+            }),
+            Policy::RateLimiting(rate_limiting) => rate_limiting.should_keep(spans),
+            Policy::Probabilistic(ratio) => spans.first().map_or(false, |span| {
+                sample_based_on_probability(ratio, span.span_context.trace_id())
+                    == SamplingDecision::RecordAndSample
+            }),
+            Policy::And(policies) => policies.iter().all(|policy| policy.should_keep(spans)),
+            Policy::Or(policies) => policies.iter().any(|policy| policy.should_keep(spans)),
+            Policy::Custom(policy) => policy.should_keep(spans),
+        }
+    }
+}
+
+/// State of [`Policy::RateLimiting`], created with [`Policy::rate_limiting`].
+#[derive(Debug)]
+pub struct RateLimiting {
+    spans_per_second: u64,
+    /// The start of the current second and the number of spans kept since.
+    state: Mutex<Option<(Instant, u64)>>,
+}
+
+impl RateLimiting {
+    fn should_keep(&self, spans: &[SpanData]) -> bool {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(err) => {
+                global::handle_error(TraceError::from(err.to_string()));
+                return false;
+            }
+        };
+        let now = Instant::now();
+        let (second_start, kept) = match *state {
+            Some((second_start, kept))
+                if now.duration_since(second_start) < Duration::from_secs(1) =>
+            {
+                (second_start, kept)
+            }
+            _ => (now, 0),
+        };
+
+        let kept_with_trace = kept + spans.len() as u64;
+        if kept_with_trace <= self.spans_per_second {
+            *state = Some((second_start, kept_with_trace));
+            true
+        } else {
+            *state = Some((second_start, kept));
+            false
+        }
+    }
+}
+
+/// What happens to the oldest buffered trace when the processor buffers the
+/// maximum number of traces and receives the first span of a new one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Decide on the oldest trace right away with the spans received so far.
+    DecideOldest,
+    /// Drop the oldest trace without exporting it.
+    DropOldest,
+}
+
+impl Default for EvictionPolicy {
+    fn default() -> Self {
+        EvictionPolicy::DecideOldest
+    }
+}
+
+/// A [`SpanProcessor`] buffering the spans of each trace to decide whether to
+/// export it once it is complete.
+///
+/// Head sampling decides whether to keep a trace when its first span starts,
+/// before anything is known about how the trace turns out. This processor
+/// instead buffers the finished spans of each trace and decides once the trace
+/// is complete, so that traces with errors or high latency can always be kept
+/// while only a fraction of the others are exported.
+///
+/// A trace is considered complete when its root span, the span without parent,
+/// ends, or when the decision wait has elapsed since its first span was
+/// received, as for traces continued from a remote parent. The
+/// [`Policy`]s of the processor then decide whether all the buffered spans of
+/// the trace are forwarded to the exporter. Spans of the trace ending after the
+/// decision follow it.
+///
+/// Finished spans are queued for a worker thread, spans ending while the queue
+/// is full are dropped. The kept traces are exported in batches.
+///
+/// Note that only sampled spans reach the processor, the tracer provider
+/// should use a sampler recording all traces, such as [`Sampler::AlwaysOn`].
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry_sdk::testing::trace::NoopSpanExporter;
+/// use opentelemetry_sdk::trace::tail_sampling::{Policy, StatusCode};
+/// use opentelemetry_sdk::trace::{self, TailSamplingSpanProcessor};
+/// use std::time::Duration;
+///
+/// // keep traces with errors or lasting more than a second, and 1% of the others
+/// let processor = TailSamplingSpanProcessor::builder(NoopSpanExporter::new())
+///     .with_policy(Policy::StatusCode(StatusCode::Error))
+///     .with_policy(Policy::Latency(Duration::from_secs(1)))
+///     .with_policy(Policy::Probabilistic(0.01))
+///     .with_decision_wait(Duration::from_secs(10))
+///     .build();
+///
+/// let provider = trace::TracerProvider::builder()
+///     .with_span_processor(processor)
+///     .build();
+/// ```
+///
+/// [`Sampler::AlwaysOn`]: crate::trace::Sampler::AlwaysOn
+#[derive(Debug)]
+pub struct TailSamplingSpanProcessor {
+    sender: crossbeam_channel::Sender<TailSamplingMessage>,
+    shutdown: crossbeam_channel::Receiver<()>,
+}
+
+impl TailSamplingSpanProcessor {
+    /// Create a new tail sampling processor builder forwarding the kept traces
+    /// to `exporter`.
+    pub fn builder<E>(exporter: E) -> TailSamplingSpanProcessorBuilder
+    where
+        E: SpanExporter + 'static,
+    {
+        TailSamplingSpanProcessorBuilder {
+            exporter: Box::new(exporter),
+            policies: Vec::new(),
+            decision_wait: None,
+            max_traces: None,
+            max_spans_per_trace: None,
+            max_queue_size: None,
+            eviction_policy: EvictionPolicy::default(),
+        }
+    }
+
+    fn new(mut buffer: TraceBuffer, max_queue_size: usize) -> Self {
+        let (message_tx, message_rx) = crossbeam_channel::bounded(max_queue_size);
+        let (shutdown_tx, shutdown_rx) = crossbeam_channel::bounded(0);
+
+        let _ = thread::Builder::new()
+            .name("opentelemetry-tail-sampling".to_string())
+            .spawn(move || {
+                'worker: loop {
+                    let mut message = match buffer.next_deadline() {
+                        Some(deadline) => message_rx
+                            .recv_deadline(deadline)
+                            .map_err(|err| err.is_disconnected()),
+                        None => message_rx.recv().map_err(|_| true),
+                    };
+
+                    // handle the queued messages before exporting, so that the
+                    // traces they complete are exported together
+                    loop {
+                        match message {
+                            Ok(TailSamplingMessage::Span(span)) => buffer.on_span(span),
+                            Ok(TailSamplingMessage::Flush(ack)) => {
+                                buffer.decide_all();
+                                buffer.export_decided();
+                                let _ = ack.send(());
+                            }
+                            Ok(TailSamplingMessage::Shutdown) | Err(true) => break 'worker,
+                            // the decision wait of the oldest trace elapsed
+                            Err(false) => {}
+                        }
+                        if buffer.decided.len() >= MAX_EXPORT_BATCH_SIZE {
+                            break;
+                        }
+                        message = match message_rx.try_recv() {
+                            Ok(message) => Ok(message),
+                            Err(_) => break,
+                        };
+                    }
+                    buffer.decide_expired();
+                    buffer.export_decided();
+                }
+
+                buffer.decide_all();
+                buffer.export_decided();
+                buffer.exporter.shutdown();
+
+                if let Err(err) = shutdown_tx.send(()) {
+                    global::handle_error(TraceError::from(format!(
+                        "could not send shutdown: {:?}",
+                        err
+                    )));
+                }
+            });
+
+        TailSamplingSpanProcessor {
+            sender: message_tx,
+            shutdown: shutdown_rx,
+        }
+    }
+}
+
+impl SpanProcessor for TailSamplingSpanProcessor {
+    fn on_start(&self, _span: &mut Span, _cx: &Context) {
+        // Ignored
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if !span.span_context.is_sampled() {
+            return;
+        }
+
+        if let Err(err) = self.sender.try_send(TailSamplingMessage::Span(span)) {
+            global::handle_error(TraceError::from(format!("error processing span {:?}", err)));
+        }
+    }
+
+    /// Decides on all the buffered traces right away, without waiting for
+    /// them to complete, and exports the kept ones.
+    fn force_flush(&self) -> TraceResult<()> {
+        let (ack_tx, ack_rx) = crossbeam_channel::bounded(1);
+        self.sender
+            .send(TailSamplingMessage::Flush(ack_tx))
+            .map_err(|err| TraceError::from(format!("error flushing spans {:?}", err)))?;
+
+        ack_rx
+            .recv()
+            .map_err(|err| TraceError::from(format!("error flushing spans {:?}", err)))
+    }
+
+    fn shutdown(&mut self) -> TraceResult<()> {
+        if self.sender.send(TailSamplingMessage::Shutdown).is_ok() {
+            if let Err(err) = self.shutdown.recv() {
+                global::handle_error(TraceError::from(format!(
+                    "error shutting down span processor: {:?}",
+                    err
+                )))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// The size difference is not a concern, most of the messages are spans.
+#[allow(clippy::large_enum_variant)]
+enum TailSamplingMessage {
+    Span(SpanData),
+    Flush(crossbeam_channel::Sender<()>),
+    Shutdown,
+}
+
+/// Builder for [`TailSamplingSpanProcessor`].
+#[derive(Debug)]
+pub struct TailSamplingSpanProcessorBuilder {
+    exporter: Box<dyn SpanExporter>,
+    policies: Vec<Policy>,
+    decision_wait: Option<Duration>,
+    max_traces: Option<usize>,
+    max_spans_per_trace: Option<usize>,
+    max_queue_size: Option<usize>,
+    eviction_policy: EvictionPolicy,
+}
+
+impl TailSamplingSpanProcessorBuilder {
+    /// Add a policy, a trace is kept if any of the policies keeps it.
+    ///
+    /// All the traces are kept if no policy is added.
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policies.push(policy);
+        self
+    }
+
+    /// Set the time to wait for the root span of a trace, from the first
+    /// span received, before deciding on the trace with the spans received so
+    /// far. The default is 30 seconds.
+    pub fn with_decision_wait(mut self, decision_wait: Duration) -> Self {
+        self.decision_wait = Some(decision_wait);
+        self
+    }
+
+    /// Set the maximum number of traces buffered, the oldest one is evicted
+    /// according to the eviction policy when a new trace would go over it.
+    /// The default is 10000.
+    pub fn with_max_traces(mut self, max_traces: usize) -> Self {
+        self.max_traces = Some(max_traces.max(1));
+        self
+    }
+
+    /// Set the maximum number of spans buffered for a trace. A trace reaching
+    /// it is decided on right away. The default is 1000.
+    pub fn with_max_spans_per_trace(mut self, max_spans_per_trace: usize) -> Self {
+        self.max_spans_per_trace = Some(max_spans_per_trace.max(1));
+        self
+    }
+
+    /// Set the maximum number of finished spans queued for the worker thread,
+    /// spans ending while the queue is full are dropped. The default is 2048.
+    pub fn with_max_queue_size(mut self, max_queue_size: usize) -> Self {
+        self.max_queue_size = Some(max_queue_size.max(1));
+        self
+    }
+
+    /// Set what happens to the oldest trace when too many traces are buffered.
+    /// The default is [`EvictionPolicy::DecideOldest`].
+    pub fn with_eviction_policy(mut self, eviction_policy: EvictionPolicy) -> Self {
+        self.eviction_policy = eviction_policy;
+        self
+    }
+
+    /// Build a tail sampling processor.
+    pub fn build(self) -> TailSamplingSpanProcessor {
+        let policy = match self.policies.len() {
+            0 => None,
+            1 => self.policies.into_iter().next(),
+            _ => Some(Policy::Or(self.policies)),
+        };
+
+        let max_queue_size = self.max_queue_size.unwrap_or(DEFAULT_MAX_QUEUE_SIZE);
+        TailSamplingSpanProcessor::new(
+            TraceBuffer {
+                exporter: self.exporter,
+                policy,
+                decision_wait: self.decision_wait.unwrap_or(DEFAULT_DECISION_WAIT),
+                max_traces: self.max_traces.unwrap_or(DEFAULT_MAX_TRACES),
+                max_spans_per_trace: self
+                    .max_spans_per_trace
+                    .unwrap_or(DEFAULT_MAX_SPANS_PER_TRACE),
+                eviction_policy: self.eviction_policy,
+                traces: HashMap::new(),
+                arrivals: BTreeMap::new(),
+                next_arrival: 0,
+                decisions: HashMap::new(),
+                decision_order: VecDeque::new(),
+                decided: Vec::new(),
+            },
+            max_queue_size,
+        )
+    }
+}
+
+/// The spans received for a trace not decided on yet.
+struct PendingTrace {
+    /// Position of the trace in the arrival order.
+    arrival: u64,
+    received: Instant,
+    spans: Vec<SpanData>,
+}
+
+/// The state of the worker thread of a [`TailSamplingSpanProcessor`].
+struct TraceBuffer {
+    exporter: Box<dyn SpanExporter>,
+    policy: Option<Policy>,
+    decision_wait: Duration,
+    max_traces: usize,
+    max_spans_per_trace: usize,
+    eviction_policy: EvictionPolicy,
+    traces: HashMap<TraceId, PendingTrace>,
+    /// The pending traces, oldest first.
+    arrivals: BTreeMap<u64, TraceId>,
+    next_arrival: u64,
+    /// The latest decisions, applied to the spans ending after them.
+    decisions: HashMap<TraceId, bool>,
+    decision_order: VecDeque<TraceId>,
+    /// The spans of the kept traces, waiting to be exported.
+    decided: Vec<SpanData>,
+}
+
+impl TraceBuffer {
+    fn on_span(&mut self, span: SpanData) {
+        let trace_id = span.span_context.trace_id();
+        if let Some(keep) = self.decisions.get(&trace_id) {
+            if *keep {
+                self.decided.push(span);
+            }
+            return;
+        }
+
+        if !self.traces.contains_key(&trace_id) && self.traces.len() >= self.max_traces {
+            self.evict_oldest();
+        }
+
+        let is_root = span.parent_span_id == SpanId::INVALID;
+        let next_arrival = &mut self.next_arrival;
+        let arrivals = &mut self.arrivals;
+        let trace = self.traces.entry(trace_id).or_insert_with(|| {
+            let arrival = *next_arrival;
+            *next_arrival += 1;
+            arrivals.insert(arrival, trace_id);
+            PendingTrace {
+                arrival,
+                received: Instant::now(),
+                spans: Vec::new(),
+            }
+        });
+        trace.spans.push(span);
+
+        if is_root || trace.spans.len() >= self.max_spans_per_trace {
+            self.decide(trace_id);
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = match self.arrivals.values().next() {
+            Some(trace_id) => *trace_id,
+            None => return,
+        };
+
+        match self.eviction_policy {
+            EvictionPolicy::DecideOldest => self.decide(oldest),
+            EvictionPolicy::DropOldest => {
+                if let Some(trace) = self.traces.remove(&oldest) {
+                    self.arrivals.remove(&trace.arrival);
+                    self.record_decision(oldest, false);
+                }
+            }
+        }
+    }
+
+    /// The time at which the decision wait of the oldest trace elapses.
+    fn next_deadline(&self) -> Option<Instant> {
+        self.arrivals
+            .values()
+            .next()
+            .and_then(|trace_id| self.traces.get(trace_id))
+            .map(|trace| trace.received + self.decision_wait)
+    }
+
+    fn decide_expired(&mut self) {
+        let now = Instant::now();
+        while let Some(deadline) = self.next_deadline() {
+            if deadline > now {
+                break;
+            }
+            if let Some(oldest) = self.arrivals.values().next().copied() {
+                self.decide(oldest);
+            }
+        }
+    }
+
+    fn decide_all(&mut self) {
+        let pending: Vec<TraceId> = self.arrivals.values().copied().collect();
+        for trace_id in pending {
+            self.decide(trace_id);
+        }
+    }
+
+    fn decide(&mut self, trace_id: TraceId) {
+        if let Some(trace) = self.traces.remove(&trace_id) {
+            self.arrivals.remove(&trace.arrival);
+            let keep = self
+                .policy
+                .as_ref()
+                .map_or(true, |policy| policy.should_keep(&trace.spans));
+            self.record_decision(trace_id, keep);
+            if keep {
+                self.decided.extend(trace.spans);
+            }
+        }
+    }
+
+    fn record_decision(&mut self, trace_id: TraceId, keep: bool) {
+        if self.decision_order.len() >= self.max_traces {
+            if let Some(oldest) = self.decision_order.pop_front() {
+                self.decisions.remove(&oldest);
+            }
+        }
+        self.decision_order.push_back(trace_id);
+        self.decisions.insert(trace_id, keep);
+    }
+
+    /// Export the spans of the kept traces, in batches of at most
+    /// `MAX_EXPORT_BATCH_SIZE` spans.
+    fn export_decided(&mut self) {
+        while !self.decided.is_empty() {
+            let batch = if self.decided.len() > MAX_EXPORT_BATCH_SIZE {
+                let rest = self.decided.split_off(MAX_EXPORT_BATCH_SIZE);
+                std::mem::replace(&mut self.decided, rest)
+            } else {
+                std::mem::take(&mut self.decided)
+            };
+            if let Err(err) = futures_executor::block_on(self.exporter.export(batch)) {
+                global::handle_error(err);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing", feature = "trace"))]
+mod tests {
+    use super::*;
+    use crate::export::trace::ExportResult;
+    use crate::testing::trace::{new_test_export_span_data, new_test_exporter};
+    use futures_util::future::BoxFuture;
+    use opentelemetry_api::trace::{SpanContext, TraceFlags, TraceState};
+    use opentelemetry_api::KeyValue;
+    use std::sync::mpsc::Receiver;
+    use std::sync::Arc;
+
+    fn span(trace_id: u128, span_id: u64, parent_span_id: u64) -> SpanData {
+        let mut span = new_test_export_span_data();
+        span.span_context = SpanContext::new(
+            TraceId::from_u128(trace_id),
+            SpanId::from_u64(span_id),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::default(),
+        );
+        span.parent_span_id = SpanId::from_u64(parent_span_id);
+        span
+    }
+
+    fn exported(rx_export: &Receiver<SpanData>) -> Vec<(u128, u64)> {
+        let mut spans: Vec<(u128, u64)> = rx_export
+            .try_iter()
+            .map(|span| {
+                (
+                    u128::from_be_bytes(span.span_context.trace_id().to_bytes()),
+                    u64::from_be_bytes(span.span_context.span_id().to_bytes()),
+                )
+            })
+            .collect();
+        spans.sort_unstable();
+        spans
+    }
+
+    #[test]
+    fn test_keeps_traces_matching_policies() {
+        let (exporter, rx_export, _rx_shutdown) = new_test_exporter();
+        let mut processor = TailSamplingSpanProcessor::builder(exporter)
+            .with_policy(Policy::StatusCode(StatusCode::Error))
+            .with_policy(Policy::Attribute {
+                key: Key::new("debug"),
+                values: vec![],
+            })
+            .build();
+
+        // trace 1 has an error in a child span
+        let mut failed = span(1, 2, 1);
+        failed.status = Status::error("failed");
+        processor.on_end(failed);
+        processor.on_end(span(1, 1, 0));
+        // trace 2 is kept because of its root span attribute
+        let mut root = span(2, 1, 0);
+        root.attributes.insert(KeyValue::new("debug", true));
+        processor.on_end(root);
+        // trace 3 is dropped
+        processor.on_end(span(3, 2, 1));
+        processor.on_end(span(3, 1, 0));
+
+        processor.force_flush().unwrap();
+        assert_eq!(exported(&rx_export), vec![(1, 1), (1, 2), (2, 1)]);
+
+        // late spans follow the decision of their trace
+        processor.on_end(span(1, 3, 1));
+        processor.on_end(span(3, 3, 1));
+        processor.force_flush().unwrap();
+        assert_eq!(exported(&rx_export), vec![(1, 3)]);
+
+        processor.shutdown().unwrap();
+    }
+
+    #[test]
+    fn test_decision_wait() {
+        let (exporter, rx_export, rx_shutdown) = new_test_exporter();
+        let mut processor = TailSamplingSpanProcessor::builder(exporter)
+            .with_decision_wait(Duration::from_millis(10))
+            .build();
+
+        // the root span never ends, the trace is decided on after the wait
+        processor.on_end(span(1, 2, 1));
+        assert!(rx_export
+            .recv_timeout(Duration::from_secs(5))
+            .map(|span| span.span_context.trace_id() == TraceId::from_u128(1))
+            .unwrap());
+
+        processor.shutdown().unwrap();
+        assert!(rx_shutdown.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_eviction() {
+        for (eviction_policy, expected) in [
+            (EvictionPolicy::DecideOldest, vec![(1, 2), (2, 2)]),
+            (EvictionPolicy::DropOldest, vec![(2, 2)]),
+        ] {
+            let (exporter, rx_export, _rx_shutdown) = new_test_exporter();
+            let mut processor = TailSamplingSpanProcessor::builder(exporter)
+                .with_max_traces(1)
+                .with_eviction_policy(eviction_policy)
+                .build();
+
+            processor.on_end(span(1, 2, 1));
+            processor.on_end(span(2, 2, 1));
+            processor.force_flush().unwrap();
+            assert_eq!(exported(&rx_export), expected);
+
+            processor.shutdown().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_max_spans_per_trace() {
+        let (exporter, rx_export, _rx_shutdown) = new_test_exporter();
+        let mut processor = TailSamplingSpanProcessor::builder(exporter)
+            .with_max_spans_per_trace(2)
+            .build();
+
+        processor.on_end(span(1, 2, 1));
+        processor.on_end(span(1, 3, 1));
+        // the trace is decided on without waiting for a flush
+        assert!(rx_export.recv_timeout(Duration::from_secs(5)).is_ok());
+        assert!(rx_export.recv_timeout(Duration::from_secs(5)).is_ok());
+
+        processor.shutdown().unwrap();
+    }
+
+    #[test]
+    fn test_exports_decided_traces_together() {
+        #[derive(Debug, Default)]
+        struct BatchSizes(Arc<Mutex<Vec<usize>>>);
+
+        impl SpanExporter for BatchSizes {
+            fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+                self.0.lock().unwrap().push(batch.len());
+                Box::pin(std::future::ready(Ok(())))
+            }
+        }
+
+        let exporter = BatchSizes::default();
+        let batch_sizes = exporter.0.clone();
+        let mut processor = TailSamplingSpanProcessor::builder(exporter).build();
+        for trace_id in 1..=3 {
+            processor.on_end(span(trace_id, 2, 1));
+            processor.on_end(span(trace_id, 3, 1));
+        }
+        processor.force_flush().unwrap();
+        assert_eq!(*batch_sizes.lock().unwrap(), vec![6]);
+
+        processor.shutdown().unwrap();
+    }
+
+    #[test]
+    fn test_policies() {
+        let mut slow = span(1, 1, 0);
+        slow.end_time = slow.start_time + Duration::from_millis(500);
+        let trace = vec![slow, span(1, 2, 1)];
+
+        assert!(Policy::Latency(Duration::from_millis(500)).should_keep(&trace));
+        assert!(!Policy::Latency(Duration::from_secs(1)).should_keep(&trace));
+        assert!(Policy::StatusCode(StatusCode::Unset).should_keep(&trace));
+        assert!(!Policy::StatusCode(StatusCode::Ok).should_keep(&trace));
+        assert!(Policy::Probabilistic(1.0).should_keep(&trace));
+        assert!(!Policy::Probabilistic(0.0).should_keep(&trace));
+        assert!(!Policy::Attribute {
+            key: Key::new("user"),
+            values: vec!["alice".into()],
+        }
+        .should_keep(&trace));
+
+        assert!(Policy::And(vec![
+            Policy::Latency(Duration::from_millis(100)),
+            Policy::Probabilistic(1.0),
+        ])
+        .should_keep(&trace));
+        assert!(!Policy::And(vec![
+            Policy::Latency(Duration::from_millis(100)),
+            Policy::Probabilistic(0.0),
+        ])
+        .should_keep(&trace));
+        assert!(Policy::Or(vec![
+            Policy::StatusCode(StatusCode::Error),
+            Policy::Latency(Duration::from_millis(100)),
+        ])
+        .should_keep(&trace));
+
+        // a budget of 3 spans per second keeps a single trace of 2 spans
+        let rate_limiting = Policy::rate_limiting(3);
+        assert!(rate_limiting.should_keep(&trace));
+        assert!(!rate_limiting.should_keep(&trace));
+        assert!(rate_limiting.should_keep(&trace[..1]));
+    }
+}
@@ -205,7 +205,7 @@ impl opentelemetry_api::trace::Tracer for Tracer {
             let mut attributes =
                 EvictedHashMap::new(span_limits.max_attributes_per_span, attribute_options.len());
             for (key, value) in attribute_options {
-                attributes.insert(KeyValue::new(key, value));
+                attributes.insert(KeyValue::new(key, span_limits.truncate_value(value)));
             }
             let mut links = EvictedQueue::new(span_limits.max_links_per_span);
             if let Some(link_options) = &mut link_options {
@@ -214,6 +214,7 @@ impl opentelemetry_api::trace::Tracer for Tracer {
                     let dropped_attributes_count =
                         link.attributes.len().saturating_sub(link_attributes_limit);
                     link.attributes.truncate(link_attributes_limit);
+                    span_limits.truncate_attributes(&mut link.attributes);
                     link.dropped_attributes_count = dropped_attributes_count as u32;
                 }
                 links.append_vec(link_options);
@@ -229,6 +230,7 @@ impl opentelemetry_api::trace::Tracer for Tracer {
                         .len()
                         .saturating_sub(event_attributes_limit);
                     event.attributes.truncate(event_attributes_limit);
+                    span_limits.truncate_attributes(&mut event.attributes);
                     event.dropped_attributes_count = dropped_attributes_count as u32;
                 }
                 events_queue.append_vec(&mut events);
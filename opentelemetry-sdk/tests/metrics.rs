@@ -200,4 +200,631 @@ mod metrics {
             vec![("up_down_counter", 1.0)],
         ])
     }
+
+    #[test]
+    fn test_views() {
+        use opentelemetry_api::{Key, KeyValue};
+        use opentelemetry_sdk::export::metrics::aggregation::Count;
+        use opentelemetry_sdk::metrics::aggregators::HistogramAggregator;
+        use opentelemetry_sdk::metrics::view::{StreamAggregation, View};
+
+        let controller = controllers::basic(processors::factory(
+            selectors::simple::inexpensive(),
+            cumulative_temporality_selector(),
+        ))
+        .with_collect_period(Duration::ZERO)
+        .with_view(
+            View::builder()
+                .with_instrument_name("requests")
+                .with_meter_name("server")
+                .with_name("http.requests")
+                .with_allowed_attribute_keys(vec![Key::new("method")])
+                .build()
+                .unwrap(),
+        )
+        .with_view(
+            View::builder()
+                .with_instrument_name("requests")
+                .with_name("http.request.sizes")
+                .with_aggregation(StreamAggregation::Histogram(vec![10.0]))
+                .build()
+                .unwrap(),
+        )
+        .with_view(
+            View::builder()
+                .with_instrument_name("debug.*")
+                .with_aggregation(StreamAggregation::Drop)
+                .build()
+                .unwrap(),
+        )
+        .build();
+        let cx = Context::new();
+
+        let server = controller.versioned_meter("server", None, None);
+        let requests = server.f64_counter("requests").init();
+        let debug = server.f64_counter("debug.requests").init();
+        let other = controller
+            .versioned_meter("client", None, None)
+            .f64_counter("requests")
+            .init();
+        for (method, status) in [("GET", "200"), ("GET", "404"), ("POST", "200")] {
+            let attributes = [
+                KeyValue::new("method", method),
+                KeyValue::new("status", status),
+            ];
+            requests.add(&cx, 15.0, &attributes);
+            debug.add(&cx, 1.0, &attributes);
+            other.add(&cx, 1.0, &attributes);
+        }
+
+        // collect twice to go through the cumulative aggregators of the processor
+        controller.collect(&cx).unwrap();
+        requests.add(&cx, 5.0, &[KeyValue::new("method", "GET")]);
+        controller.collect(&cx).unwrap();
+
+        let mut results = Vec::new();
+        let temporality = cumulative_temporality_selector();
+        controller
+            .try_for_each(&mut |library, reader| {
+                reader.try_for_each(&temporality, &mut |record| {
+                    let aggregator = record.aggregator().unwrap().as_any();
+                    let value = if let Some(sum) = aggregator.downcast_ref::<SumAggregator>() {
+                        sum.sum().unwrap().to_f64(&NumberKind::F64)
+                    } else {
+                        let histogram = aggregator.downcast_ref::<HistogramAggregator>().unwrap();
+                        histogram.count().unwrap() as f64
+                    };
+                    results.push((
+                        library.name.to_string(),
+                        record.descriptor().name().to_string(),
+                        record.attributes().len(),
+                        value,
+                    ));
+                    Ok(())
+                })
+            })
+            .unwrap();
+        results.sort_by(|a, b| {
+            (&a.0, &a.1, a.2)
+                .cmp(&(&b.0, &b.1, b.2))
+                .then(a.3.total_cmp(&b.3))
+        });
+
+        assert_eq!(
+            results,
+            vec![
+                ("client".into(), "http.request.sizes".into(), 2, 1.0),
+                ("client".into(), "http.request.sizes".into(), 2, 1.0),
+                ("client".into(), "http.request.sizes".into(), 2, 1.0),
+                ("server".into(), "http.request.sizes".into(), 1, 1.0),
+                ("server".into(), "http.request.sizes".into(), 2, 1.0),
+                ("server".into(), "http.request.sizes".into(), 2, 1.0),
+                ("server".into(), "http.request.sizes".into(), 2, 1.0),
+                ("server".into(), "http.requests".into(), 1, 15.0),
+                ("server".into(), "http.requests".into(), 1, 35.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_views_with_same_output_stream() {
+        use opentelemetry_api::{Key, KeyValue};
+        use opentelemetry_sdk::metrics::sdk_api::InstrumentKind;
+        use opentelemetry_sdk::metrics::view::View;
+
+        // both views produce a `requests` stream keeping the `method` attribute
+        let controller = controllers::basic(processors::factory(
+            selectors::simple::inexpensive(),
+            cumulative_temporality_selector(),
+        ))
+        .with_collect_period(Duration::ZERO)
+        .with_view(
+            View::builder()
+                .with_instrument_name("requests")
+                .with_allowed_attribute_keys(vec![Key::new("method")])
+                .build()
+                .unwrap(),
+        )
+        .with_view(
+            View::builder()
+                .with_instrument_kind(InstrumentKind::Counter)
+                .with_allowed_attribute_keys(vec![Key::new("method")])
+                .build()
+                .unwrap(),
+        )
+        .build();
+        let cx = Context::new();
+
+        let requests = controller
+            .versioned_meter("server", None, None)
+            .u64_counter("requests")
+            .init();
+        for (method, status, value) in [("GET", "200", 3), ("GET", "404", 4), ("POST", "200", 5)] {
+            requests.add(
+                &cx,
+                value,
+                &[
+                    KeyValue::new("method", method),
+                    KeyValue::new("status", status),
+                ],
+            );
+        }
+        controller.collect(&cx).unwrap();
+
+        let mut series = Vec::new();
+        let temporality = cumulative_temporality_selector();
+        controller
+            .try_for_each(&mut |_library, reader| {
+                reader.try_for_each(&temporality, &mut |record| {
+                    let sum = record
+                        .aggregator()
+                        .unwrap()
+                        .as_any()
+                        .downcast_ref::<SumAggregator>()
+                        .unwrap()
+                        .sum()?;
+                    series.push((
+                        record.descriptor().name().to_string(),
+                        record
+                            .attributes()
+                            .iter()
+                            .map(|(k, v)| format!("{}={}", k, v))
+                            .collect::<Vec<_>>(),
+                        sum.to_u64(&NumberKind::U64),
+                    ));
+                    Ok(())
+                })
+            })
+            .unwrap();
+        series.sort();
+
+        // only the stream of the first view is kept, measurements are not counted twice
+        assert_eq!(
+            series,
+            vec![
+                ("requests".to_string(), vec!["method=GET".to_string()], 7),
+                ("requests".to_string(), vec!["method=POST".to_string()], 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exponential_histogram() {
+        use opentelemetry_sdk::export::metrics::aggregation::{
+            Count, ExponentialBuckets, ExponentialHistogram,
+        };
+        use opentelemetry_sdk::metrics::aggregators::ExponentialHistogramAggregator;
+
+        let controller = controllers::basic(processors::factory(
+            selectors::simple::exponential_histogram(4, 0),
+            cumulative_temporality_selector(),
+        ))
+        .with_collect_period(Duration::ZERO)
+        .build();
+        let cx = Context::new();
+        let latency = controller
+            .versioned_meter("server", None, None)
+            .f64_histogram("latency")
+            .init();
+
+        for value in [0.0, 1.0, 2.0] {
+            latency.record(&cx, value, &[]);
+        }
+        controller.collect(&cx).unwrap();
+        // 16 doesn't fit in 4 buckets with the previous values at scale 0
+        latency.record(&cx, 16.0, &[]);
+        controller.collect(&cx).unwrap();
+
+        let temporality = cumulative_temporality_selector();
+        controller
+            .try_for_each(&mut |_library, reader| {
+                reader.try_for_each(&temporality, &mut |record| {
+                    let histogram = record
+                        .aggregator()
+                        .unwrap()
+                        .as_any()
+                        .downcast_ref::<ExponentialHistogramAggregator>()
+                        .unwrap();
+                    assert_eq!(histogram.count()?, 4);
+                    assert_eq!(histogram.sum()?.to_f64(&NumberKind::F64), 19.0);
+                    assert_eq!(histogram.zero_count()?, 1);
+                    assert_eq!(histogram.scale()?, -1);
+                    assert_eq!(
+                        histogram.positive()?,
+                        ExponentialBuckets::new(-1, vec![1, 1, 1])
+                    );
+                    Ok(())
+                })
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_exemplars() {
+        use opentelemetry_api::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+        };
+        use opentelemetry_api::{Key, KeyValue};
+        use opentelemetry_sdk::metrics::view::View;
+
+        let controller = controllers::basic(processors::factory(
+            selectors::simple::histogram([10.0]),
+            cumulative_temporality_selector(),
+        ))
+        .with_collect_period(Duration::ZERO)
+        .with_view(
+            View::builder()
+                .with_instrument_name("latency")
+                .with_allowed_attribute_keys(vec![Key::new("route")])
+                .build()
+                .unwrap(),
+        )
+        .build();
+        let latency = controller
+            .versioned_meter("server", None, None)
+            .f64_histogram("latency")
+            .init();
+        let traced = |trace_id, flags| {
+            Context::new().with_remote_span_context(SpanContext::new(
+                TraceId::from_u128(trace_id),
+                SpanId::from_u64(1),
+                flags,
+                true,
+                TraceState::default(),
+            ))
+        };
+        let attributes = [KeyValue::new("route", "/"), KeyValue::new("user", "alice")];
+
+        latency.record(&traced(1, TraceFlags::SAMPLED), 5.0, &attributes);
+        latency.record(&traced(2, TraceFlags::SAMPLED), 50.0, &attributes);
+        // unsampled spans and measurements without span are not sampled
+        latency.record(&traced(3, TraceFlags::default()), 6.0, &attributes);
+        latency.record(&Context::new(), 60.0, &attributes);
+        controller.collect(&Context::new()).unwrap();
+
+        let mut exemplars = Vec::new();
+        let temporality = cumulative_temporality_selector();
+        controller
+            .try_for_each(&mut |_library, reader| {
+                reader.try_for_each(&temporality, &mut |record| {
+                    exemplars.extend(record.exemplars().iter().map(|exemplar| {
+                        (
+                            exemplar.value().to_f64(&NumberKind::F64),
+                            exemplar.trace_id(),
+                            exemplar.filtered_attributes().to_vec(),
+                        )
+                    }));
+                    Ok(())
+                })
+            })
+            .unwrap();
+
+        // the reservoir of histograms keeps one exemplar per bucket
+        assert_eq!(
+            exemplars,
+            vec![
+                (
+                    5.0,
+                    Some(TraceId::from_u128(1)),
+                    vec![KeyValue::new("user", "alice")]
+                ),
+                (
+                    50.0,
+                    Some(TraceId::from_u128(2)),
+                    vec![KeyValue::new("user", "alice")]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cardinality_limit() {
+        use opentelemetry_api::global::{self, Error};
+        use opentelemetry_api::metrics::MetricsError;
+        use opentelemetry_api::KeyValue;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let limit_errors = Arc::new(AtomicUsize::new(0));
+        let errors = limit_errors.clone();
+        global::set_error_handler(move |err| {
+            if let Error::Metric(MetricsError::CardinalityLimitReached(name, 2)) = err {
+                assert_eq!(name, "requests");
+                errors.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+
+        let controller = controllers::basic(processors::factory(
+            selectors::simple::inexpensive(),
+            cumulative_temporality_selector(),
+        ))
+        .with_collect_period(Duration::ZERO)
+        .with_cardinality_limit(2)
+        .build();
+        let requests = controller
+            .versioned_meter("server", None, None)
+            .u64_counter("requests")
+            .init();
+        let cx = Context::new();
+        for user in ["a", "b", "c", "d", "a", "c"] {
+            requests.add(&cx, 1, &[KeyValue::new("user", user)]);
+        }
+        controller.collect(&cx).unwrap();
+
+        let mut series = Vec::new();
+        let temporality = cumulative_temporality_selector();
+        controller
+            .try_for_each(&mut |_library, reader| {
+                reader.try_for_each(&temporality, &mut |record| {
+                    let sum = record
+                        .aggregator()
+                        .unwrap()
+                        .as_any()
+                        .downcast_ref::<SumAggregator>()
+                        .unwrap()
+                        .sum()?;
+                    series.push((
+                        record
+                            .attributes()
+                            .iter()
+                            .next()
+                            .map(|(k, v)| format!("{}={}", k, v)),
+                        sum.to_u64(&NumberKind::U64),
+                    ));
+                    Ok(())
+                })
+            })
+            .unwrap();
+        series.sort();
+
+        // the first attribute sets are kept, the others fold into the overflow series
+        assert_eq!(
+            series,
+            vec![
+                (Some("otel.metric.overflow=true".to_string()), 3),
+                (Some("user=a".to_string()), 2),
+                (Some("user=b".to_string()), 1),
+            ]
+        );
+        // the limit is reported once
+        assert_eq!(limit_errors.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_cardinality_limit_across_cumulative_collections() {
+        use opentelemetry_api::KeyValue;
+
+        let controller = controllers::basic(processors::factory(
+            selectors::simple::inexpensive(),
+            cumulative_temporality_selector(),
+        ))
+        .with_collect_period(Duration::ZERO)
+        .with_cardinality_limit(3)
+        .build();
+        let requests = controller
+            .versioned_meter("server", None, None)
+            .u64_counter("rotating.requests")
+            .init();
+        let cx = Context::new();
+
+        let temporality = cumulative_temporality_selector();
+        let mut series = Vec::new();
+        for collection in 0..3 {
+            // every collection records new attribute sets
+            for user in 0..2 {
+                let user = format!("{}-{}", collection, user);
+                requests.add(&cx, 1, &[KeyValue::new("user", user)]);
+            }
+            controller.collect(&cx).unwrap();
+
+            series.clear();
+            controller
+                .try_for_each(&mut |_library, reader| {
+                    reader.try_for_each(&temporality, &mut |record| {
+                        let sum = record
+                            .aggregator()
+                            .unwrap()
+                            .as_any()
+                            .downcast_ref::<SumAggregator>()
+                            .unwrap()
+                            .sum()?;
+                        series.push((
+                            record
+                                .attributes()
+                                .iter()
+                                .next()
+                                .map(|(k, v)| format!("{}={}", k, v)),
+                            sum.to_u64(&NumberKind::U64),
+                        ));
+                        Ok(())
+                    })
+                })
+                .unwrap();
+            // the processor remembers every exported series, they stay at the cap
+            assert!(series.len() <= 4);
+        }
+        series.sort();
+
+        assert_eq!(
+            series,
+            vec![
+                (Some("otel.metric.overflow=true".to_string()), 3),
+                (Some("user=0-0".to_string()), 1),
+                (Some("user=0-1".to_string()), 1),
+                (Some("user=1-0".to_string()), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_readers() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Arc;
+
+        fn values(
+            controller: &BasicController,
+            temporality: &dyn TemporalitySelector,
+        ) -> Vec<(String, u64)> {
+            let mut values = Vec::new();
+            controller
+                .try_for_each(&mut |_library, reader| {
+                    reader.try_for_each(temporality, &mut |record| {
+                        let aggregator = record.aggregator().unwrap().as_any();
+                        let value = if let Some(sum) = aggregator.downcast_ref::<SumAggregator>() {
+                            sum.sum()?
+                        } else {
+                            let last_value =
+                                aggregator.downcast_ref::<LastValueAggregator>().unwrap();
+                            last_value.last_value()?.0
+                        };
+                        values.push((
+                            record.descriptor().name().to_string(),
+                            value.to_u64(&NumberKind::U64),
+                        ));
+                        Ok(())
+                    })
+                })
+                .unwrap();
+            values.sort();
+            values
+        }
+
+        let reader = controllers::basic(processors::factory(
+            selectors::simple::inexpensive(),
+            cumulative_temporality_selector(),
+        ))
+        .with_collect_period(Duration::ZERO)
+        .build();
+        let controller = controllers::basic(processors::factory(
+            selectors::simple::inexpensive(),
+            delta_temporality_selector(),
+        ))
+        .with_collect_period(Duration::ZERO)
+        .with_reader(reader.clone())
+        .build();
+
+        let meter = controller.versioned_meter("test", None, None);
+        let counter = meter.u64_counter("counter").init();
+        let observations = Arc::new(AtomicU64::new(0));
+        let gauge = meter.u64_observable_gauge("gauge").init();
+        let callback_observations = observations.clone();
+        meter
+            .register_callback(move |cx| {
+                let total = callback_observations.fetch_add(1, Ordering::SeqCst) + 1;
+                gauge.observe(cx, total, &[]);
+            })
+            .unwrap();
+
+        let cx = Context::new();
+        let delta = delta_temporality_selector();
+        let cumulative = cumulative_temporality_selector();
+        for value in [1, 2] {
+            counter.add(&cx, value, &[]);
+            controller.collect(&cx).unwrap();
+            reader.collect(&cx).unwrap();
+        }
+
+        // each controller runs the callback once per collection and only
+        // records its own observation, the third and fourth ones here
+        assert_eq!(observations.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            values(&controller, &delta),
+            vec![("counter".to_string(), 2), ("gauge".to_string(), 3)]
+        );
+        assert_eq!(
+            values(&reader, &cumulative),
+            vec![("counter".to_string(), 3), ("gauge".to_string(), 4)]
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    mod stop {
+        use super::*;
+        use futures_util::future::{self, BoxFuture};
+        use opentelemetry_api::metrics::{MetricsError, Result};
+        use opentelemetry_sdk::export::metrics::aggregation::{AggregationKind, Temporality};
+        use opentelemetry_sdk::export::metrics::MetricsExporter;
+        use opentelemetry_sdk::metrics::sdk_api::Descriptor;
+        use opentelemetry_sdk::{runtime, Resource};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        /// Exporter whose shutdown either fails right away or never completes.
+        #[derive(Clone, Default)]
+        struct PendingExporter {
+            exports: Arc<AtomicUsize>,
+            hang_on_shutdown: bool,
+        }
+
+        impl TemporalitySelector for PendingExporter {
+            fn temporality_for(&self, _: &Descriptor, _: &AggregationKind) -> Temporality {
+                Temporality::Cumulative
+            }
+        }
+
+        impl MetricsExporter for PendingExporter {
+            fn export(
+                &self,
+                _cx: &Context,
+                _res: &Resource,
+                _reader: &dyn InstrumentationLibraryReader,
+            ) -> Result<()> {
+                self.exports.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+
+            fn shutdown(&self) -> BoxFuture<'static, Result<()>> {
+                if self.hang_on_shutdown {
+                    Box::pin(future::pending())
+                } else {
+                    Box::pin(future::ready(Err(MetricsError::Other(
+                        "flush failed".into(),
+                    ))))
+                }
+            }
+        }
+
+        fn start(exporter: PendingExporter) -> BasicController {
+            let controller = controllers::basic(processors::factory(
+                selectors::simple::inexpensive(),
+                cumulative_temporality_selector(),
+            ))
+            .with_exporter(exporter)
+            .with_collect_period(Duration::from_secs(60))
+            .with_push_timeout(Duration::from_millis(50))
+            .build();
+            controller
+                .start(&Context::new(), runtime::Tokio)
+                .expect("controller should start");
+            controller
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_stop_reports_exporter_shutdown_result() {
+            let exporter = PendingExporter::default();
+            let controller = start(exporter.clone());
+
+            let result = tokio::task::spawn_blocking(move || controller.stop(&Context::new()))
+                .await
+                .unwrap();
+            assert!(matches!(result, Err(MetricsError::Other(msg)) if msg == "flush failed"));
+            // the final collection is exported before shutting down, the first tick of the
+            // interval may have triggered an export already
+            assert!(exporter.exports.load(Ordering::SeqCst) >= 1);
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_stop_waits_up_to_push_timeout() {
+            let controller = start(PendingExporter {
+                hang_on_shutdown: true,
+                ..Default::default()
+            });
+
+            let result = tokio::task::spawn_blocking(move || controller.stop(&Context::new()))
+                .await
+                .unwrap();
+            assert!(result.is_err());
+        }
+    }
 }
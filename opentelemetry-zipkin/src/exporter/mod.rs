@@ -26,6 +26,7 @@ use std::borrow::Cow;
     feature = "surf-client"
 ))]
 use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -38,10 +39,64 @@ pub struct Exporter {
 }
 
 impl Exporter {
-    fn new(local_endpoint: Endpoint, client: Arc<dyn HttpClient>, collector_endpoint: Uri) -> Self {
-        Exporter {
+    fn new(
+        local_endpoint: Endpoint,
+        client: Arc<dyn HttpClient>,
+        collector_endpoint: Uri,
+        encoding: Encoding,
+        compression: Option<Compression>,
+        max_payload_size: Option<usize>,
+    ) -> Result<Self, Error> {
+        Ok(Exporter {
             local_endpoint,
-            uploader: uploader::Uploader::new(client, collector_endpoint),
+            uploader: uploader::Uploader::new(
+                client,
+                collector_endpoint,
+                encoding,
+                compression,
+                max_payload_size,
+            )?,
+        })
+    }
+}
+
+/// The encoding of the spans sent to the Zipkin collector.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Encoding {
+    /// JSON v2 model, sent as `application/json`.
+    Json,
+    /// Protobuf `ListOfSpans` of the proto3 model, sent as `application/x-protobuf`.
+    ///
+    /// Requires the `proto3` feature.
+    Proto3,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Encoding::Json
+    }
+}
+
+impl Display for Encoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Encoding::Json => write!(f, "json"),
+            Encoding::Proto3 => write!(f, "proto3"),
+        }
+    }
+}
+
+/// The compression algorithm applied to the request bodies sent to the Zipkin collector.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Compression {
+    /// Compresses data using gzip, requires the `gzip` feature.
+    Gzip,
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::Gzip => write!(f, "gzip"),
         }
     }
 }
@@ -59,6 +114,9 @@ pub struct ZipkinPipelineBuilder {
     collector_endpoint: String,
     trace_config: Option<sdk::trace::Config>,
     client: Option<Arc<dyn HttpClient>>,
+    encoding: Encoding,
+    compression: Option<Compression>,
+    max_payload_size: Option<usize>,
 }
 
 impl Default for ZipkinPipelineBuilder {
@@ -103,6 +161,9 @@ impl Default for ZipkinPipelineBuilder {
             service_addr: None,
             collector_endpoint: env::get_endpoint(),
             trace_config: None,
+            encoding: Encoding::default(),
+            compression: None,
+            max_payload_size: None,
         }
     }
 }
@@ -160,7 +221,10 @@ impl ZipkinPipelineBuilder {
                 self.collector_endpoint
                     .parse()
                     .map_err::<Error, _>(Into::into)?,
-            );
+                self.encoding,
+                self.compression,
+                self.max_payload_size,
+            )?;
             Ok(exporter)
         } else {
             Err(Error::NoHttpClient.into())
@@ -234,6 +298,35 @@ impl ZipkinPipelineBuilder {
         self.trace_config = Some(config);
         self
     }
+
+    /// Assign the encoding of the spans, defaults to [`Encoding::Json`].
+    ///
+    /// Initializing the exporter fails if the crate feature of the encoding is not enabled.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Compress the request bodies with the given algorithm, they are not compressed by default.
+    ///
+    /// Initializing the exporter fails if the crate feature of the algorithm is not enabled.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Limit the size in bytes of the request bodies sent to the collector.
+    ///
+    /// Spans of an export are split into several requests so that each body, after compression,
+    /// stays under the limit. The requests are sent even if some of them fail, the export then
+    /// returns an [`Error::PartialUploadFailed`] error. A span too large to fit in the limit on
+    /// its own is dropped.
+    ///
+    /// The whole export is sent in a single request by default.
+    pub fn with_max_payload_size(mut self, max_payload_size: usize) -> Self {
+        self.max_payload_size = Some(max_payload_size);
+        self
+    }
 }
 
 async fn zipkin_export(
@@ -277,6 +370,38 @@ pub enum Error {
     #[error("invalid uri")]
     InvalidUri(#[from] http::uri::InvalidUri),
 
+    /// The encoding needs a crate feature that is not enabled.
+    #[error("feature '{0}' is required to use the encoding '{1}'")]
+    FeatureRequiredForEncoding(&'static str, Encoding),
+
+    /// The compression algorithm needs a crate feature that is not enabled.
+    #[error("feature '{0}' is required to use the compression algorithm '{1}'")]
+    FeatureRequiredForCompression(&'static str, Compression),
+
+    /// Compressing the request body failed.
+    #[error("compression failed with {0}")]
+    CompressionFailed(std::io::Error),
+
+    /// A span can't be sent because its payload is larger than the maximum payload size.
+    #[error("payload size of {size} bytes over max payload size of {max_payload_size} bytes")]
+    PayloadTooLarge {
+        /// Size of the payload of the span
+        size: usize,
+        /// The maximum payload size
+        max_payload_size: usize,
+    },
+
+    /// Some of the requests of an export split over several requests failed.
+    #[error("{failed_spans} of {total_spans} spans failed to upload: {}", .errors.join("; "))]
+    PartialUploadFailed {
+        /// Number of spans in the failed requests
+        failed_spans: usize,
+        /// Number of spans in the export
+        total_spans: usize,
+        /// Errors of the failed requests
+        errors: Vec<String>,
+    },
+
     /// Other errors
     #[error("export error: {0}")]
     Other(String),
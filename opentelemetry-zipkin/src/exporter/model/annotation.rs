@@ -1,5 +1,7 @@
 use std::time::{Duration, SystemTime};
 
+#[cfg(feature = "proto3")]
+use crate::exporter::model::proto;
 use opentelemetry::trace::Event;
 use serde::Serialize;
 
@@ -30,6 +32,16 @@ impl From<Event> for Annotation {
     }
 }
 
+#[cfg(feature = "proto3")]
+impl From<Annotation> for proto::Annotation {
+    fn from(annotation: Annotation) -> Self {
+        proto::Annotation {
+            timestamp: annotation.timestamp.unwrap_or_default(),
+            value: annotation.value.unwrap_or_default(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::exporter::model::annotation::Annotation;
@@ -1,3 +1,5 @@
+#[cfg(feature = "proto3")]
+use crate::exporter::model::proto;
 use serde::Serialize;
 use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
 
@@ -36,6 +38,24 @@ impl Endpoint {
     }
 }
 
+#[cfg(feature = "proto3")]
+impl From<Endpoint> for proto::Endpoint {
+    fn from(endpoint: Endpoint) -> Self {
+        proto::Endpoint {
+            service_name: endpoint.service_name.unwrap_or_default(),
+            ipv4: endpoint
+                .ipv4
+                .map(|ip| ip.octets().to_vec())
+                .unwrap_or_default(),
+            ipv6: endpoint
+                .ipv6
+                .map(|ip| ip.octets().to_vec())
+                .unwrap_or_default(),
+            port: endpoint.port.map(i32::from).unwrap_or_default(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::exporter::model::endpoint::Endpoint;
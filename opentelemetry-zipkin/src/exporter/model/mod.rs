@@ -8,6 +8,8 @@ use std::time::{Duration, SystemTime};
 
 pub(crate) mod annotation;
 pub(crate) mod endpoint;
+#[cfg(feature = "proto3")]
+pub(crate) mod proto;
 pub(crate) mod span;
 
 use endpoint::Endpoint;
@@ -0,0 +1,94 @@
+//! Zipkin [proto3 model](https://github.com/openzipkin/zipkin-api/blob/master/zipkin.proto), as
+//! accepted by collectors with the `application/x-protobuf` content type.
+use std::collections::HashMap;
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct Endpoint {
+    #[prost(string, tag = "1")]
+    pub(crate) service_name: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub(crate) ipv4: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub(crate) ipv6: Vec<u8>,
+    #[prost(int32, tag = "4")]
+    pub(crate) port: i32,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct Annotation {
+    #[prost(fixed64, tag = "1")]
+    pub(crate) timestamp: u64,
+    #[prost(string, tag = "2")]
+    pub(crate) value: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct Span {
+    #[prost(bytes = "vec", tag = "1")]
+    pub(crate) trace_id: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub(crate) parent_id: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub(crate) id: Vec<u8>,
+    #[prost(enumeration = "Kind", tag = "4")]
+    pub(crate) kind: i32,
+    #[prost(string, tag = "5")]
+    pub(crate) name: String,
+    #[prost(fixed64, tag = "6")]
+    pub(crate) timestamp: u64,
+    #[prost(uint64, tag = "7")]
+    pub(crate) duration: u64,
+    #[prost(message, optional, tag = "8")]
+    pub(crate) local_endpoint: Option<Endpoint>,
+    #[prost(message, optional, tag = "9")]
+    pub(crate) remote_endpoint: Option<Endpoint>,
+    #[prost(message, repeated, tag = "10")]
+    pub(crate) annotations: Vec<Annotation>,
+    #[prost(map = "string, string", tag = "11")]
+    pub(crate) tags: HashMap<String, String>,
+    #[prost(bool, tag = "12")]
+    pub(crate) debug: bool,
+    #[prost(bool, tag = "13")]
+    pub(crate) shared: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub(crate) enum Kind {
+    Unspecified = 0,
+    Client = 1,
+    Server = 2,
+    Producer = 3,
+    Consumer = 4,
+}
+
+/// The request body, the uploader writes its `spans` field directly from a slice of spans.
+#[cfg(test)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub(crate) struct ListOfSpans {
+    #[prost(message, repeated, tag = "1")]
+    pub(crate) spans: Vec<Span>,
+}
+
+/// Decode the lower case hex ids of the JSON model.
+pub(crate) fn id_from_hex(id: &str) -> Vec<u8> {
+    (0..id.len())
+        .step_by(2)
+        .filter_map(|start| id.get(start..start + 2))
+        .filter_map(|byte| u8::from_str_radix(byte, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::id_from_hex;
+
+    #[test]
+    fn test_id_from_hex() {
+        assert_eq!(
+            id_from_hex("00f067aa0ba902b7"),
+            vec![0, 240, 103, 170, 11, 169, 2, 183]
+        );
+        assert!(id_from_hex("").is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "proto3")]
+use crate::exporter::model::proto;
 use crate::exporter::model::{annotation::Annotation, endpoint::Endpoint};
 use serde::Serialize;
 use std::collections::HashMap;
@@ -53,6 +55,42 @@ pub(crate) struct Span {
     shared: bool,
 }
 
+#[cfg(feature = "proto3")]
+impl From<Span> for proto::Span {
+    fn from(span: Span) -> Self {
+        let kind = match span.kind {
+            Some(Kind::Client) => proto::Kind::Client,
+            Some(Kind::Server) => proto::Kind::Server,
+            Some(Kind::Producer) => proto::Kind::Producer,
+            Some(Kind::Consumer) => proto::Kind::Consumer,
+            None => proto::Kind::Unspecified,
+        };
+        let id_bytes =
+            |id: Option<String>| id.as_deref().map(proto::id_from_hex).unwrap_or_default();
+
+        proto::Span {
+            trace_id: id_bytes(span.trace_id),
+            parent_id: id_bytes(span.parent_id),
+            id: id_bytes(span.id),
+            kind: kind as i32,
+            name: span.name.unwrap_or_default(),
+            timestamp: span.timestamp.unwrap_or_default(),
+            duration: span.duration.unwrap_or_default(),
+            local_endpoint: span.local_endpoint.map(Into::into),
+            remote_endpoint: span.remote_endpoint.map(Into::into),
+            annotations: span
+                .annotations
+                .into_iter()
+                .flatten()
+                .map(Into::into)
+                .collect(),
+            tags: span.tags.unwrap_or_default(),
+            debug: span.debug,
+            shared: span.shared,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::exporter::model::annotation::Annotation;
@@ -1,7 +1,12 @@
 //! # Zipkin Span Exporter
+#[cfg(feature = "proto3")]
+use crate::exporter::model::proto;
 use crate::exporter::model::span::Span;
-use crate::exporter::Error;
-use http::{header::CONTENT_TYPE, Method, Request, Uri};
+use crate::exporter::{Compression, Encoding, Error};
+use http::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE},
+    Method, Request, Uri,
+};
 use opentelemetry::sdk::export::trace::ExportResult;
 use opentelemetry_http::{HttpClient, ResponseExt};
 use std::fmt::Debug;
@@ -10,40 +15,382 @@ use std::sync::Arc;
 #[derive(Debug, Clone)]
 pub(crate) enum Uploader {
     Http(JsonV2Client),
+    #[cfg(feature = "proto3")]
+    Proto3(Proto3Client),
 }
 
 impl Uploader {
-    /// Create a new http uploader
-    pub(crate) fn new(client: Arc<dyn HttpClient>, collector_endpoint: Uri) -> Self {
-        Uploader::Http(JsonV2Client {
+    /// Create a new http uploader using the given encoding and compression.
+    ///
+    /// Returns an error if the crate feature needed by the encoding or the compression is not
+    /// enabled.
+    pub(crate) fn new(
+        client: Arc<dyn HttpClient>,
+        collector_endpoint: Uri,
+        encoding: Encoding,
+        compression: Option<Compression>,
+        max_payload_size: Option<usize>,
+    ) -> Result<Self, Error> {
+        #[cfg(not(feature = "gzip"))]
+        if let Some(compression) = compression {
+            return Err(Error::FeatureRequiredForCompression("gzip", compression));
+        }
+
+        let sender = Sender {
             client,
             collector_endpoint,
-        })
+            compression,
+            max_payload_size,
+        };
+        match encoding {
+            Encoding::Json => Ok(Uploader::Http(JsonV2Client { sender })),
+            #[cfg(feature = "proto3")]
+            Encoding::Proto3 => Ok(Uploader::Proto3(Proto3Client { sender })),
+            #[cfg(not(feature = "proto3"))]
+            Encoding::Proto3 => Err(Error::FeatureRequiredForEncoding("proto3", encoding)),
+        }
     }
 
     /// Upload spans to Zipkin
     pub(crate) async fn upload(&self, spans: Vec<Span>) -> ExportResult {
         match self {
             Uploader::Http(client) => client.upload(spans).await,
+            #[cfg(feature = "proto3")]
+            Uploader::Proto3(client) => client.upload(spans).await,
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct JsonV2Client {
-    client: Arc<dyn HttpClient>,
-    collector_endpoint: Uri,
+    sender: Sender,
 }
 
 impl JsonV2Client {
     async fn upload(&self, spans: Vec<Span>) -> ExportResult {
-        let req = Request::builder()
+        self.sender
+            .upload("application/json", spans, encode_json)
+            .await
+    }
+}
+
+#[cfg(feature = "proto3")]
+#[derive(Debug, Clone)]
+pub(crate) struct Proto3Client {
+    sender: Sender,
+}
+
+#[cfg(feature = "proto3")]
+impl Proto3Client {
+    async fn upload(&self, spans: Vec<Span>) -> ExportResult {
+        // spans are converted by value, splitting the batch later works on the converted spans
+        let spans: Vec<proto::Span> = spans.into_iter().map(Into::into).collect();
+        self.sender
+            .upload("application/x-protobuf", spans, encode_proto3)
+            .await
+    }
+}
+
+fn encode_json(spans: &[Span]) -> Vec<u8> {
+    serde_json::to_vec(spans).unwrap_or_default()
+}
+
+/// Encode the spans as a `ListOfSpans` message.
+///
+/// The message only has the repeated `spans` field, so it is written span by span rather than
+/// building a `ListOfSpans` that would need to own a copy of the spans.
+#[cfg(feature = "proto3")]
+fn encode_proto3(spans: &[proto::Span]) -> Vec<u8> {
+    use prost::encoding::message;
+
+    let mut buf = Vec::with_capacity(message::encoded_len_repeated(1, spans));
+    for span in spans {
+        message::encode(1, span, &mut buf);
+    }
+    buf
+}
+
+/// Posts encoded spans to the collector, compressing them if required.
+#[derive(Debug, Clone)]
+struct Sender {
+    client: Arc<dyn HttpClient>,
+    collector_endpoint: Uri,
+    compression: Option<Compression>,
+    max_payload_size: Option<usize>,
+}
+
+impl Sender {
+    /// Encode and send the spans.
+    ///
+    /// If a maximum payload size is set, spans are split in halves until each request body fits
+    /// in it. The requests are sent one after the other, a failed request doesn't stop the
+    /// remaining ones.
+    async fn upload<T, F>(
+        &self,
+        content_type: &'static str,
+        spans: Vec<T>,
+        encode: F,
+    ) -> ExportResult
+    where
+        F: Fn(&[T]) -> Vec<u8>,
+    {
+        let max_payload_size = match self.max_payload_size {
+            Some(max_payload_size) => max_payload_size,
+            None => {
+                let (body, content_encoding) = self.compress(encode(&spans))?;
+                return self.send(content_type, body, content_encoding).await;
+            }
+        };
+
+        let total_spans = spans.len();
+        let mut failed_spans = 0;
+        let mut errors = Vec::new();
+        let mut split = false;
+        // chunks are popped from the end, the first spans of the batch are sent first
+        let mut chunks = vec![spans];
+        while let Some(mut chunk) = chunks.pop() {
+            let (body, content_encoding) = match self.compress(encode(&chunk)) {
+                Ok(compressed) => compressed,
+                Err(err) => {
+                    failed_spans += chunk.len();
+                    errors.push(err.into());
+                    continue;
+                }
+            };
+            if body.len() > max_payload_size && chunk.len() > 1 {
+                let second_half = chunk.split_off(chunk.len() / 2);
+                chunks.push(second_half);
+                chunks.push(chunk);
+                split = true;
+                continue;
+            }
+
+            let result = if body.len() > max_payload_size {
+                Err(Error::PayloadTooLarge {
+                    size: body.len(),
+                    max_payload_size,
+                }
+                .into())
+            } else {
+                self.send(content_type, body, content_encoding).await
+            };
+            if let Err(err) = result {
+                failed_spans += chunk.len();
+                errors.push(err);
+            }
+        }
+
+        if !split {
+            // a single request was attempted, its error is returned as is
+            return errors.pop().map_or(Ok(()), Err);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::PartialUploadFailed {
+                failed_spans,
+                total_spans,
+                errors: errors.iter().map(ToString::to_string).collect(),
+            }
+            .into())
+        }
+    }
+
+    async fn send(
+        &self,
+        content_type: &'static str,
+        body: Vec<u8>,
+        content_encoding: Option<&'static str>,
+    ) -> ExportResult {
+        let mut builder = Request::builder()
             .method(Method::POST)
             .uri(self.collector_endpoint.clone())
-            .header(CONTENT_TYPE, "application/json")
-            .body(serde_json::to_vec(&spans).unwrap_or_default())
-            .map_err::<Error, _>(Into::into)?;
+            .header(CONTENT_TYPE, content_type);
+        if let Some(content_encoding) = content_encoding {
+            builder = builder.header(CONTENT_ENCODING, content_encoding);
+        }
+        let req = builder.body(body).map_err::<Error, _>(Into::into)?;
         let _ = self.client.send(req).await?.error_for_status()?;
         Ok(())
     }
+
+    /// Compress the body, returns it with its content encoding.
+    fn compress(&self, body: Vec<u8>) -> Result<(Vec<u8>, Option<&'static str>), Error> {
+        match self.compression {
+            #[cfg(feature = "gzip")]
+            Some(Compression::Gzip) => {
+                Ok((gzip(&body).map_err(Error::CompressionFailed)?, Some("gzip")))
+            }
+            // `Uploader::new` rejects compressions whose feature is disabled
+            #[cfg(not(feature = "gzip"))]
+            Some(Compression::Gzip) => Ok((body, None)),
+            None => Ok((body, None)),
+        }
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip(body: &[u8]) -> std::io::Result<Vec<u8>> {
+    use flate2::{write::GzEncoder, Compression as GzCompression};
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), GzCompression::default());
+    encoder.write_all(body)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures_util::FutureExt;
+    use opentelemetry::trace::TraceError;
+    use opentelemetry_http::HttpError;
+    use std::sync::Mutex;
+
+    /// Records the bodies it receives, rejects the ones containing `"fail"`.
+    #[derive(Debug, Default)]
+    struct RecordingClient {
+        bodies: Mutex<Vec<Vec<u8>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpClient for RecordingClient {
+        async fn send(
+            &self,
+            request: Request<Vec<u8>>,
+        ) -> Result<http::Response<Bytes>, HttpError> {
+            let body = request.into_body();
+            let failed = String::from_utf8_lossy(&body).contains("\"fail\"");
+            self.bodies.lock().unwrap().push(body);
+            let status = if failed { 500 } else { 202 };
+            Ok(http::Response::builder()
+                .status(status)
+                .body(Bytes::new())
+                .unwrap())
+        }
+    }
+
+    fn upload(
+        client: &Arc<RecordingClient>,
+        max_payload_size: Option<usize>,
+        names: &[&str],
+    ) -> ExportResult {
+        let uploader = Uploader::new(
+            client.clone(),
+            Uri::from_static("http://localhost:9411/api/v2/spans"),
+            Encoding::Json,
+            None,
+            max_payload_size,
+        )
+        .unwrap();
+        let spans = names
+            .iter()
+            .map(|name| Span::builder().name(name.to_string()).build())
+            .collect();
+        uploader.upload(spans).now_or_never().unwrap()
+    }
+
+    fn span_names(body: &[u8]) -> Vec<String> {
+        let spans: Vec<serde_json::Value> = serde_json::from_slice(body).unwrap();
+        spans
+            .iter()
+            .map(|span| span["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_upload_without_max_payload_size() {
+        let client = Arc::new(RecordingClient::default());
+        upload(&client, None, &["a", "b", "c"]).unwrap();
+        let bodies = client.bodies.lock().unwrap();
+        assert_eq!(bodies.len(), 1);
+        assert_eq!(span_names(&bodies[0]), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_split_upload() {
+        // a single span body is 43 bytes, two spans fit in 100 bytes
+        let client = Arc::new(RecordingClient::default());
+        upload(&client, Some(100), &["a", "b", "c", "d", "e"]).unwrap();
+        let bodies = client.bodies.lock().unwrap();
+        assert!(bodies.iter().all(|body| body.len() <= 100));
+        let names: Vec<Vec<String>> = bodies.iter().map(|body| span_names(body)).collect();
+        assert_eq!(names, vec![vec!["a", "b"], vec!["c"], vec!["d", "e"]]);
+    }
+
+    #[test]
+    fn test_split_upload_partial_failure() {
+        let client = Arc::new(RecordingClient::default());
+        let err = upload(&client, Some(100), &["a", "fail", "c", "d"]).unwrap_err();
+        // the requests after the failed one are still sent
+        assert_eq!(client.bodies.lock().unwrap().len(), 2);
+        match err {
+            TraceError::ExportFailed(err) => {
+                assert!(err.to_string().starts_with("2 of 4 spans failed to upload"))
+            }
+            err => panic!("unexpected error {:?}", err),
+        }
+
+        let client = Arc::new(RecordingClient::default());
+        let too_large = "x".repeat(100);
+        let err = upload(&client, Some(100), &["a", &too_large, "c"]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("1 of 3 spans failed to upload: Exporter zipkin encountered the following error(s): payload size of"));
+        let names: Vec<Vec<String>> = client
+            .bodies
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|body| span_names(body))
+            .collect();
+        assert_eq!(names, vec![vec!["a"], vec!["c"]]);
+    }
+
+    #[cfg(feature = "proto3")]
+    #[test]
+    fn test_encode_proto3() {
+        use prost::Message;
+
+        let spans = vec![
+            Span::builder()
+                .id("efdc9cd9a1849df3".to_owned())
+                .name("first".to_owned())
+                .build(),
+            Span::builder().name("second".to_owned()).build(),
+        ];
+        let spans: Vec<proto::Span> = spans.into_iter().map(Into::into).collect();
+        let encoded = encode_proto3(&spans);
+        assert_eq!(
+            encoded,
+            proto::ListOfSpans {
+                spans: spans.clone()
+            }
+            .encode_to_vec()
+        );
+        let decoded = proto::ListOfSpans::decode(encoded.as_slice()).unwrap();
+        assert_eq!(decoded.spans.len(), 2);
+        assert_eq!(decoded.spans[0].name, "first");
+        assert_eq!(
+            decoded.spans[0].id,
+            vec![0xef, 0xdc, 0x9c, 0xd9, 0xa1, 0x84, 0x9d, 0xf3]
+        );
+        assert_eq!(decoded.spans[1].name, "second");
+        assert!(decoded.spans[1].id.is_empty());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip() {
+        use std::io::Read;
+
+        let body = br#"[{"name":"span"}]"#;
+        let compressed = super::gzip(body).unwrap();
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, body);
+    }
 }
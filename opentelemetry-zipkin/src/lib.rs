@@ -163,6 +163,8 @@
 //!   client (enabled by default).
 //! * `reqwest-client`: Export spans using the reqwest non-blocking http client.
 //! * `surf-client`: Export spans using the surf non-blocking http client.
+//! * `proto3`: Enable [`Encoding::Proto3`] to send spans in the protobuf format.
+//! * `gzip`: Enable [`Compression::Gzip`] to compress the request bodies.
 //!
 //! ## Supported Rust Versions
 //!
@@ -202,5 +204,5 @@ extern crate typed_builder;
 mod exporter;
 mod propagator;
 
-pub use exporter::{new_pipeline, Error, Exporter, ZipkinPipelineBuilder};
+pub use exporter::{new_pipeline, Compression, Encoding, Error, Exporter, ZipkinPipelineBuilder};
 pub use propagator::{B3Encoding, Propagator};